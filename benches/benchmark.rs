@@ -1,45 +1,60 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use mdv::tui::ui::truncate_with_markers;
+use mdv::markdown::InlineSpan;
+use mdv::tui::ui::truncate_spans;
 
 fn bench_truncate_short(c: &mut Criterion) {
-    let text = "Hello ⟨INLINE_CODE⟩code⟨/INLINE_CODE⟩ world";
+    let spans = vec![
+        InlineSpan::plain("Hello "),
+        InlineSpan::code("code"),
+        InlineSpan::plain(" world"),
+    ];
     c.bench_function("truncate_short", |b| {
-        b.iter(|| truncate_with_markers(black_box(text), 20))
+        b.iter(|| truncate_spans(black_box(&spans), 20))
     });
 }
 
 fn bench_truncate_medium(c: &mut Criterion) {
-    let text = "This is a test string with ⟨INLINE_CODE⟩inline code⟨/INLINE_CODE⟩ and more text that continues here.";
+    let spans = vec![
+        InlineSpan::plain("This is a test string with "),
+        InlineSpan::code("inline code"),
+        InlineSpan::plain(" and more text that continues here."),
+    ];
     c.bench_function("truncate_medium", |b| {
-        b.iter(|| truncate_with_markers(black_box(text), 30))
+        b.iter(|| truncate_spans(black_box(&spans), 30))
     });
 }
 
 fn bench_truncate_long(c: &mut Criterion) {
-    let text = "A".repeat(1000);
+    let spans = vec![InlineSpan::plain("A".repeat(1000))];
     c.bench_function("truncate_long_1000", |b| {
-        b.iter(|| truncate_with_markers(black_box(&text), 500))
+        b.iter(|| truncate_spans(black_box(&spans), 500))
     });
 }
 
 fn bench_truncate_very_long(c: &mut Criterion) {
-    let text = "A".repeat(10000);
+    let spans = vec![InlineSpan::plain("A".repeat(10000))];
     c.bench_function("truncate_long_10000", |b| {
-        b.iter(|| truncate_with_markers(black_box(&text), 5000))
+        b.iter(|| truncate_spans(black_box(&spans), 5000))
     });
 }
 
 fn bench_truncate_cjk(c: &mut Criterion) {
-    let text = "日本語のテスト⟨INLINE_CODE⟩コード⟨/INLINE_CODE⟩文章";
+    let spans = vec![
+        InlineSpan::plain("日本語のテスト"),
+        InlineSpan::code("コード"),
+        InlineSpan::plain("文章"),
+    ];
     c.bench_function("truncate_cjk", |b| {
-        b.iter(|| truncate_with_markers(black_box(text), 15))
+        b.iter(|| truncate_spans(black_box(&spans), 15))
     });
 }
 
 fn bench_truncate_no_markers(c: &mut Criterion) {
-    let text = "This is a long text without any markers that should be truncated properly.";
+    let spans = vec![InlineSpan::plain(
+        "This is a long text without any markers that should be truncated properly.",
+    )];
     c.bench_function("truncate_no_markers", |b| {
-        b.iter(|| truncate_with_markers(black_box(text), 30))
+        b.iter(|| truncate_spans(black_box(&spans), 30))
     });
 }
 
@@ -1,4 +1,6 @@
-use mdv::markdown::{highlighter::CodeHighlighter, parser::MarkdownDocument, ParsedLine};
+use mdv::markdown::{
+    highlighter::CodeHighlighter, parser::MarkdownDocument, plain_text, ParsedLine,
+};
 use std::path::PathBuf;
 
 fn main() {
@@ -14,7 +16,7 @@ fn main() {
     // Find the keybindings table
     for line in &doc.parsed_lines {
         if let ParsedLine::Table { headers, rows, .. } = line {
-            if headers.len() == 2 && headers[0] == "Key" {
+            if headers.len() == 2 && plain_text(&headers[0]) == "Key" {
                 println!("Keybindings Table:");
                 println!("Headers: {headers:?}");
                 for (i, row) in rows.iter().enumerate() {
@@ -1,4 +1,6 @@
-use mdv::markdown::{highlighter::CodeHighlighter, parser::MarkdownDocument, ParsedLine};
+use mdv::markdown::{
+    highlighter::CodeHighlighter, parser::MarkdownDocument, plain_text, ParsedLine,
+};
 use std::path::PathBuf;
 
 fn main() {
@@ -23,6 +25,7 @@ fn main() {
             ..
         } = line
         {
+            let content = plain_text(content);
             if content.contains("Parent")
                 || content.contains("Child")
                 || content.contains("Another parent")
@@ -1,39 +1,253 @@
-use clap::Parser;
-use std::path::PathBuf;
+use crate::error::MdError;
+use crate::markdown::STDIN_PATH;
+use crate::output::{ColorMode, PrintOptions, TocFormat};
+use crate::tui::hyperlink::HyperlinkMode;
+use clap::{Parser, Subcommand};
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
 
 #[derive(Parser, Debug)]
 #[command(
     name = "mdv",
     version,
     about = "Ultra-lightweight markdown viewer for terminal",
-    long_about = None
+    long_about = None,
+    args_conflicts_with_subcommands = true
 )]
 pub struct Cli {
-    /// Path to the markdown file
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Markdown files or a directory to open (`FILE#anchor` jumps to a heading, globs are expanded,
+    /// `-` or no FILE with piped input reads standard input)
     #[arg(value_name = "FILE")]
-    pub path: PathBuf,
+    pub paths: Vec<PathBuf>,
 
     /// Disable live reload
     #[arg(short = 'n', long)]
     pub no_watch: bool,
 
     /// Syntax highlighting theme (default: base16-eighties.dark)
-    #[arg(short = 't', long, default_value = "base16-eighties.dark")]
+    #[arg(
+        short = 't',
+        long,
+        default_value = "base16-eighties.dark",
+        global = true
+    )]
     pub theme: String,
 
     /// UI theme (default: dark)
-    #[arg(long, default_value = "dark")]
+    #[arg(long, default_value = "dark", global = true)]
     pub ui_theme: String,
 
     /// Start with table of contents open
     #[arg(long)]
     pub show_toc: bool,
 
-    /// Jump to specific line number
+    /// Jump to a line of the source file (1-based)
     #[arg(short = 'l', long)]
     pub line: Option<usize>,
 
     /// Jump to heading (fuzzy search)
     #[arg(short = 'H', long)]
     pub heading: Option<String>,
+
+    /// Number nested ordered lists as 1. / a. / i.
+    #[arg(long)]
+    pub nested_list_numbers: bool,
+
+    /// Emit clickable OSC 8 hyperlinks
+    #[arg(long, value_enum, default_value = "auto")]
+    pub hyperlinks: HyperlinkMode,
+
+    /// Command used to open external links (default: xdg-open / open / explorer)
+    #[arg(long, value_name = "CMD")]
+    pub opener: Option<String>,
+
+    /// Disable mouse capture (keeps the terminal's own text selection)
+    #[arg(long)]
+    pub no_mouse: bool,
+
+    /// Start at the top instead of the position where you stopped reading last time
+    #[arg(long)]
+    pub no_restore: bool,
+
+    /// Print the rendered document and exit (default when stdout is not a terminal)
+    #[arg(long)]
+    pub print: bool,
+
+    /// Width to print at (default: terminal width, $COLUMNS or 80)
+    #[arg(long, value_name = "COLUMNS", value_parser = clap::value_parser!(u16).range(1..))]
+    pub width: Option<u16>,
+
+    /// Color printed output
+    #[arg(long, value_enum, default_value = "auto")]
+    pub color: ColorMode,
+
+    /// Print plain ASCII text without colors or box-drawing characters (implies --print)
+    #[arg(long, conflicts_with = "color")]
+    pub plain: bool,
+
+    /// Print the parsed document as versioned JSON and exit
+    #[arg(long, conflicts_with_all = ["dump_toc", "print", "plain"])]
+    pub dump_json: bool,
+
+    /// Print the table of contents as JSON or a nested markdown link list and exit
+    #[arg(
+        long,
+        value_enum,
+        value_name = "FORMAT",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "json",
+        conflicts_with_all = ["print", "plain"]
+    )]
+    pub dump_toc: Option<TocFormat>,
+}
+
+/// ビューアーを開かずに実行するコマンド
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Export a markdown file to a standalone HTML file
+    Export {
+        /// Write self-contained HTML to this file (`-` writes to stdout)
+        #[arg(long, value_name = "OUT", required = true)]
+        html: PathBuf,
+
+        /// Markdown file to export (`-` reads standard input)
+        #[arg(value_name = "FILE")]
+        path: PathBuf,
+    },
+    /// Print fenced code blocks, or tangle them into the files named by `file=`/`title=`
+    Extract {
+        /// Markdown file to read (`-` reads standard input)
+        #[arg(value_name = "FILE")]
+        path: PathBuf,
+
+        /// Only blocks in this language (e.g. bash, sql)
+        #[arg(short, long)]
+        lang: Option<String>,
+
+        /// Only blocks in the section of the first heading containing this text
+        #[arg(short = 'H', long)]
+        heading: Option<String>,
+
+        /// Only the N-th matching block (1-based)
+        #[arg(short = 'n', long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+        index: Option<u64>,
+
+        /// Write blocks with a `file=`/`title=` attribute to those files (others go to stdout)
+        #[arg(long)]
+        tangle: bool,
+
+        /// Directory to write tangled files into
+        #[arg(long, value_name = "DIR", default_value = ".", requires = "tangle")]
+        dir: PathBuf,
+    },
+}
+
+impl Cli {
+    /// 開くファイルとアンカー（引数の順）
+    ///
+    /// 存在しないパスに `*` `?` `[` が含まれていればグロブとして展開する
+    /// （シェルが展開しない Windows などのため）。
+    /// ディレクトリは含めない（[`Cli::directory`] を参照）。
+    /// `-` と、パスを指定せずに入力をパイプで渡したときは標準入力を [`STDIN_PATH`] として返す。
+    pub fn files(&self) -> Result<Vec<(PathBuf, Option<String>)>, MdError> {
+        if self.paths.is_empty() {
+            if std::io::stdin().is_terminal() {
+                return Err(MdError::NoInput);
+            }
+            return Ok(vec![(PathBuf::from(STDIN_PATH), None)]);
+        }
+
+        let mut files = Vec::new();
+        for path in &self.paths {
+            if path == Path::new("-") {
+                // 標準入力は一度しか読めない
+                let stdin = (PathBuf::from(STDIN_PATH), None);
+                if !files.contains(&stdin) {
+                    files.push(stdin);
+                }
+                continue;
+            }
+            if path.is_dir() {
+                continue;
+            }
+            let pattern = path.to_string_lossy();
+            if path.exists() || !pattern.contains(['*', '?', '[']) {
+                files.push(split_anchor(path));
+                continue;
+            }
+
+            let matches: Vec<PathBuf> = glob::glob(&pattern)
+                .map(|paths| {
+                    paths
+                        .filter_map(Result::ok)
+                        .filter(|path| !path.is_dir())
+                        .collect()
+                })
+                .unwrap_or_default();
+            if matches.is_empty() {
+                return Err(MdError::FileNotFound(path.clone()));
+            }
+            files.extend(matches.into_iter().map(|path| (path, None)));
+        }
+        Ok(files)
+    }
+
+    /// 端末を使わずに出力するときの設定（`--print` / `--plain` か、標準出力が端末でないとき）
+    pub fn print_options(&self) -> Option<PrintOptions> {
+        if !self.print && !self.plain && std::io::stdout().is_terminal() {
+            return None;
+        }
+        Some(PrintOptions {
+            width: self.width.unwrap_or_else(PrintOptions::default_width),
+            color: !self.plain && self.color.enabled(),
+            ascii: self.plain,
+        })
+    }
+
+    /// ファイルツリーで一覧にするディレクトリ（最初に指定されたもの）
+    pub fn directory(&self) -> Option<&Path> {
+        self.paths
+            .iter()
+            .map(PathBuf::as_path)
+            .find(|path| path.is_dir())
+    }
+}
+
+/// `README.md#installation` をファイルパスとアンカーに分ける
+///
+/// `#` を含む名前のファイルが実在する場合はそのまま扱う。
+pub fn split_anchor(path: &Path) -> (PathBuf, Option<String>) {
+    if path.exists() {
+        return (path.to_path_buf(), None);
+    }
+
+    let split = path
+        .to_str()
+        .and_then(|path| path.rsplit_once('#'))
+        .filter(|(file, anchor)| !file.is_empty() && !anchor.is_empty());
+    match split {
+        Some((file, anchor)) => (PathBuf::from(file), Some(anchor.to_string())),
+        None => (path.to_path_buf(), None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dash_reads_stdin_once() {
+        let cli = Cli::parse_from(["mdv", "-", "README.md#usage", "-"]);
+        assert_eq!(
+            cli.files().unwrap(),
+            vec![
+                (PathBuf::from(STDIN_PATH), None),
+                (PathBuf::from("README.md"), Some("usage".to_string())),
+            ]
+        );
+    }
 }
@@ -1,3 +1,3 @@
 pub mod args;
 
-pub use args::Cli;
+pub use args::{Cli, Command};
@@ -6,12 +6,27 @@ pub enum MdError {
     #[error("File not found: {0}")]
     FileNotFound(PathBuf),
 
+    #[error("No markdown files in {0}")]
+    NoMarkdownFiles(PathBuf),
+
+    #[error("No input: pass a FILE or pipe markdown to standard input")]
+    NoInput,
+
     #[error("Failed to read file: {0}")]
     FileReadError(#[from] std::io::Error),
 
     #[error("Failed to parse markdown: {0}")]
     ParseError(String),
 
+    #[error("Heading not found: {0}")]
+    HeadingNotFound(String),
+
+    #[error("No code blocks matched")]
+    NoCodeBlocks,
+
+    #[error("Refusing to write outside the output directory: {0}")]
+    UnsafeOutputPath(String),
+
     #[error("Theme '{0}' not found. Available themes: {1:?}")]
     ThemeNotFound(String, Vec<String>),
 
@@ -21,6 +36,9 @@ pub enum MdError {
     #[error("File watcher error: {0}")]
     WatcherError(#[from] notify::Error),
 
+    #[error("Failed to save state: {0}")]
+    StateSaveError(String),
+
     #[error("Terminal error: {0}")]
     TerminalError(String),
 }
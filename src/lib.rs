@@ -1,5 +1,7 @@
 pub mod cli;
 pub mod error;
 pub mod markdown;
+pub mod output;
+pub mod state;
 pub mod tui;
 pub mod watcher;
@@ -1,46 +1,173 @@
 use clap::Parser;
 use crossterm::{
+    event::{
+        DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, EnableBracketedPaste,
+        EnableFocusChange, EnableMouseCapture, KeyboardEnhancementFlags,
+        PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
     execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    terminal::{
+        disable_raw_mode, enable_raw_mode, supports_keyboard_enhancement, EnterAlternateScreen,
+        LeaveAlternateScreen,
+    },
 };
 use mdv::{
-    cli::Cli,
+    cli::{Cli, Command},
     error::MdError,
-    markdown::{CodeHighlighter, MarkdownDocument},
-    tui::{self, App, ThemeManager},
+    markdown::{CodeHighlighter, MarkdownDocument, STDIN_PATH},
+    output::{extract, html, json, print},
+    state::StateStore,
+    tui::{self, events::AppEvent, file_tree::FileTree, hyperlink, App, AppAction, ThemeManager},
     watcher::{LiveReloader, ReloadEvent},
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
-use std::io;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 #[tokio::main]
 async fn main() -> Result<(), MdError> {
     let cli = Cli::parse();
+    if let Some(command) = &cli.command {
+        return run_command(command, &cli);
+    }
+
+    let mut files = cli.files()?;
 
-    let content = std::fs::read_to_string(&cli.path)?;
+    // ディレクトリだけを指定したら README.md / index.md（なければ最初のファイル）を表示する
+    let file_tree = cli.directory().map(FileTree::new);
+    if let Some(tree) = &file_tree {
+        if files.is_empty() {
+            let path = tree
+                .default_file()
+                .ok_or_else(|| MdError::NoMarkdownFiles(tree.root.clone()))?;
+            files.push((path, None));
+        }
+    }
 
     let highlighter = CodeHighlighter::new(cli.theme.clone());
 
-    let document = MarkdownDocument::parse(cli.path.clone(), content, &highlighter)?;
+    // 標準入力は一度しか読めないので、リンク先から戻ったときのために取っておく
+    let stdin = files
+        .iter()
+        .any(|(path, _)| is_stdin(path))
+        .then(|| io::read_to_string(io::stdin()))
+        .transpose()?;
 
-    let mut theme_manager = ThemeManager::new();
-    theme_manager.set_theme(&cli.ui_theme);
+    let mut documents = Vec::with_capacity(files.len());
+    for (path, _) in &files {
+        let content = read_source(path, stdin.as_deref())?;
+        documents.push(MarkdownDocument::parse(
+            path.clone(),
+            content,
+            &highlighter,
+        )?);
+    }
+
+    // 解析した結果を出力して終わる
+    if cli.dump_json {
+        return write_stdout(&json::dump_documents(&documents));
+    }
+    if let Some(format) = cli.dump_toc {
+        return write_stdout(&json::dump_toc(&documents, format));
+    }
+
+    let mut documents = documents.into_iter();
+    let Some(document) = documents.next() else {
+        return Ok(());
+    };
+
+    let theme_manager = theme_manager(&cli);
+
+    // 端末を使わずに描画して終わる
+    if let Some(options) = cli.print_options() {
+        let theme = theme_manager.current_theme();
+        let rendered: Vec<String> = std::iter::once(document)
+            .chain(documents)
+            .map(|document| print::render_document(&document, &theme, options))
+            .collect();
+        return write_stdout(&rendered.join("\n"));
+    }
 
     let mut app = App::new(document, cli.show_toc, cli.line, &theme_manager);
+    for document in documents {
+        app.add_buffer(document);
+    }
+
+    // 前回読んでいた位置に戻る（位置を指定して開いたファイルは除く）
+    let state_path = StateStore::default_path();
+    if let Some(store) = state_path
+        .as_deref()
+        .filter(|_| !cli.no_restore)
+        .map(StateStore::load)
+    {
+        for (index, (path, anchor)) in files.iter().enumerate() {
+            let explicit =
+                anchor.is_some() || index == 0 && (cli.line.is_some() || cli.heading.is_some());
+            let Some(position) = store.get(path).filter(|_| !explicit) else {
+                continue;
+            };
+            app.switch_buffer(index);
+            app.restore_reading_position(position);
+            if index == 0 {
+                app.show_toc |= position.show_toc;
+            }
+        }
+        app.switch_buffer(0);
+    }
 
     if let Some(heading) = &cli.heading {
         app.jump_to_heading_by_name(heading);
     }
 
-    let mut watcher = if !cli.no_watch {
-        Some(LiveReloader::new(cli.path.clone())?)
+    // `FILE#anchor` のアンカーはそれぞれのファイルで探す
+    for (index, (_, anchor)) in files.iter().enumerate().rev() {
+        if let Some(anchor) = anchor {
+            app.switch_buffer(index);
+            if !app.jump_to_anchor(anchor) {
+                app.status_message = Some(format!("Anchor not found: #{anchor}"));
+            }
+        }
+    }
+    app.switch_buffer(0);
+
+    // ディレクトリの中のファイルはディレクトリごと監視する（標準入力は監視できない）
+    let watched: Vec<PathBuf> = files
+        .into_iter()
+        .map(|(path, _)| path)
+        .filter(|path| !is_stdin(path) && !in_file_tree(&file_tree, path))
+        .collect();
+    let mut watcher = if !cli.no_watch && (!watched.is_empty() || file_tree.is_some()) {
+        let mut watcher = LiveReloader::new(&watched)?;
+        if let Some(tree) = &file_tree {
+            watcher.watch_directory(&tree.root)?;
+        }
+        Some(watcher)
     } else {
         None
     };
 
+    if let Some(tree) = file_tree {
+        app.set_file_tree(tree);
+    }
+
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen)?;
+    if !cli.no_mouse {
+        execute!(stdout, EnableMouseCapture)?;
+    }
+    // 対応していない端末ではフォーカスと貼り付けのイベントが届かないだけなので無視する
+    let _ = execute!(stdout, EnableFocusChange, EnableBracketedPaste);
+    // `Ctrl+I` と `Tab` を区別して受け取る（対応している端末だけ）
+    let keyboard_enhanced = supports_keyboard_enhancement().unwrap_or(false);
+    if keyboard_enhanced {
+        let _ = execute!(
+            stdout,
+            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
+        );
+    }
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
@@ -51,16 +178,269 @@ async fn main() -> Result<(), MdError> {
         &highlighter,
         &theme_manager,
         &cli,
+        stdin.as_deref(),
     )
     .await;
 
+    if keyboard_enhanced {
+        let _ = execute!(terminal.backend_mut(), PopKeyboardEnhancementFlags);
+    }
     disable_raw_mode()?;
+    let _ = execute!(
+        terminal.backend_mut(),
+        DisableFocusChange,
+        DisableBracketedPaste
+    );
+    if !cli.no_mouse {
+        execute!(terminal.backend_mut(), DisableMouseCapture)?;
+    }
     execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
     terminal.show_cursor()?;
 
+    if let Some(path) = &state_path {
+        if let Err(e) = save_reading_positions(&mut app, path) {
+            eprintln!("{e}");
+        }
+    }
+
     result
 }
 
+/// `--ui-theme` などを反映した UI テーマ
+fn theme_manager(cli: &Cli) -> ThemeManager {
+    let mut theme_manager = ThemeManager::new();
+    theme_manager.set_theme(&cli.ui_theme);
+    if cli.nested_list_numbers {
+        let mut theme = theme_manager.current_theme();
+        theme.layout.nested_list_numbering = true;
+        let name = theme_manager.current_theme_name().to_string();
+        theme_manager.add_theme(name, theme);
+    }
+    theme_manager
+}
+
+/// ビューアーを開かずにコマンドを実行する
+fn run_command(command: &Command, cli: &Cli) -> Result<(), MdError> {
+    let highlighter = CodeHighlighter::new(cli.theme.clone());
+    match command {
+        Command::Export { html: out, path } => {
+            let document = read_document(path, &highlighter)?;
+            let theme = theme_manager(cli).current_theme();
+            let exported = html::export_html(&document, &theme, &highlighter);
+            if out == Path::new("-") {
+                write_stdout(&exported)
+            } else {
+                Ok(std::fs::write(out, exported)?)
+            }
+        }
+        Command::Extract {
+            path,
+            lang,
+            heading,
+            index,
+            tangle,
+            dir,
+        } => {
+            let document = read_document(path, &highlighter)?;
+            let filter = extract::CodeFilter {
+                lang: lang.clone(),
+                heading: heading.clone(),
+                index: index.map(|index| index as usize),
+            };
+            let blocks = extract::extract(&document, &filter)?;
+            if !tangle {
+                return write_stdout(&extract::concat(&blocks));
+            }
+            let mut unnamed = String::new();
+            for (name, content) in extract::group_by_file(&blocks) {
+                match name {
+                    Some(name) => {
+                        let written = extract::write_file(dir, &name, &content)?;
+                        eprintln!("Wrote {}", written.display());
+                    }
+                    None => unnamed = content,
+                }
+            }
+            write_stdout(&unnamed)
+        }
+    }
+}
+
+/// コマンドに渡されたファイル（`-` は標準入力）を読む
+fn read_document(path: &Path, highlighter: &CodeHighlighter) -> Result<MarkdownDocument, MdError> {
+    let (path, content) = if path == Path::new("-") {
+        (PathBuf::from(STDIN_PATH), io::read_to_string(io::stdin())?)
+    } else {
+        (path.to_path_buf(), std::fs::read_to_string(path)?)
+    };
+    MarkdownDocument::parse(path, content, highlighter)
+}
+
+/// 標準出力に書く（`head` などに途中で閉じられても失敗にしない）
+fn write_stdout(text: &str) -> Result<(), MdError> {
+    let mut stdout = io::stdout().lock();
+    match stdout
+        .write_all(text.as_bytes())
+        .and_then(|_| stdout.flush())
+    {
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => Ok(result?),
+    }
+}
+
+/// 開いているファイルごとに読んでいた位置を残す（表示中のファイルを最後に開いたものにする）
+fn save_reading_positions(app: &mut App, path: &Path) -> Result<(), MdError> {
+    let mut store = StateStore::load(path);
+    // 閉じたファイルを先に書き、開いているファイルを後から書いて新しくする
+    for (file, position) in app.take_closed_positions() {
+        store.set(&file, position);
+    }
+    let current = app.current_buffer();
+    let others = (0..app.buffer_count()).filter(|&index| index != current);
+    for index in others.chain([current]) {
+        app.switch_buffer(index);
+        if !app.document.is_stdin() {
+            let position = app.reading_position();
+            store.set(&app.document.path, position);
+        }
+    }
+    store.save(path)
+}
+
+/// リンク先のファイルを開く、または外部コマンドに渡す
+fn handle_action(
+    action: AppAction,
+    app: &mut App,
+    watcher: &mut Option<LiveReloader>,
+    highlighter: &CodeHighlighter,
+    opener: &str,
+    stdin: Option<&str>,
+) {
+    match action {
+        // すでに開いているファイルならそのバッファに切り替える
+        AppAction::OpenFile { path, anchor } if app.find_buffer(&path).is_some() => {
+            if let Some(index) = app.find_buffer(&path) {
+                app.switch_buffer(index);
+            }
+            if let Some(anchor) = anchor {
+                if !app.jump_to_anchor(&anchor) {
+                    app.status_message = Some(format!("Anchor not found: #{anchor}"));
+                }
+            }
+        }
+        AppAction::OpenFile { path, anchor } => {
+            let document = read_source(&path, stdin)
+                .and_then(|content| MarkdownDocument::parse(path.clone(), content, highlighter));
+            match document {
+                Ok(document) => {
+                    app.open_document(document, anchor.as_deref());
+                    // 新しいファイルも監視する
+                    if let Some(watcher) = watcher
+                        .as_mut()
+                        .filter(|_| !in_file_tree(&app.file_tree, &path))
+                    {
+                        if let Err(e) = watcher.watch(&path) {
+                            app.status_message = Some(format!("{e}"));
+                        }
+                    }
+                }
+                Err(e) => {
+                    app.status_message = Some(format!("{}: {e}", path.display()));
+                }
+            }
+        }
+        AppAction::OpenExternal(target) => {
+            if let Err(e) = hyperlink::open_external(opener, &target) {
+                app.status_message = Some(format!("Failed to open {target}: {e}"));
+            }
+        }
+        AppAction::CopyToClipboard(text) => {
+            if let Err(e) = hyperlink::copy_to_clipboard(&mut io::stdout(), &text) {
+                app.status_message = Some(format!("Failed to copy: {e}"));
+            }
+        }
+    }
+}
+
+fn is_stdin(path: &Path) -> bool {
+    path == Path::new(STDIN_PATH)
+}
+
+/// ファイルの内容（標準入力なら起動時に読んだ内容）
+fn read_source(path: &Path, stdin: Option<&str>) -> Result<String, MdError> {
+    match stdin.filter(|_| is_stdin(path)) {
+        Some(content) => Ok(content.to_string()),
+        None => Ok(std::fs::read_to_string(path)?),
+    }
+}
+
+/// ファイルの最終更新時刻（取得できなければ `None`）
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// ファイルツリーのディレクトリの中のパスか
+fn in_file_tree(file_tree: &Option<FileTree>, path: &Path) -> bool {
+    file_tree
+        .as_ref()
+        .is_some_and(|tree| tree.is_affected_by(path))
+}
+
+/// 開いているファイルの更新時刻
+fn modified_times(app: &App) -> HashMap<PathBuf, Option<SystemTime>> {
+    app.documents()
+        .map(|document| (document.path.clone(), modified_time(&document.path)))
+        .collect()
+}
+
+/// 更新時刻が前に読み込んだときから変わったファイルを読み直す
+fn reload_changed(
+    app: &mut App,
+    highlighter: &CodeHighlighter,
+    last_modified: &mut HashMap<PathBuf, Option<SystemTime>>,
+) {
+    let current = modified_times(app);
+    for index in 0..app.buffer_count() {
+        let Some(path) = app
+            .documents()
+            .nth(index)
+            .map(|document| document.path.clone())
+        else {
+            continue;
+        };
+        if last_modified.get(&path) != current.get(&path) {
+            reload_buffer(app, index, highlighter);
+        }
+    }
+    *last_modified = current;
+}
+
+/// `index` 番目のバッファのファイルを読み直す
+fn reload_buffer(app: &mut App, index: usize, highlighter: &CodeHighlighter) {
+    let Some(path) = app
+        .documents()
+        .nth(index)
+        .map(|document| document.path.clone())
+    else {
+        return;
+    };
+    match std::fs::read_to_string(&path) {
+        Ok(content) => match MarkdownDocument::parse(path, content, highlighter) {
+            Ok(new_document) => {
+                app.update_buffer(index, new_document);
+            }
+            Err(e) => {
+                eprintln!("Failed to parse markdown: {e}");
+            }
+        },
+        Err(e) => {
+            eprintln!("Failed to read file: {e}");
+        }
+    }
+}
+
 async fn run_app<'a>(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     app: &mut App<'a>,
@@ -68,19 +448,53 @@ async fn run_app<'a>(
     highlighter: &CodeHighlighter,
     theme_manager: &'a ThemeManager,
     cli: &Cli,
+    stdin: Option<&str>,
 ) -> Result<(), MdError> {
     let mut event_handler = tui::events::EventHandler::new();
+    let hyperlinks = cli.hyperlinks.enabled();
+    let opener = cli
+        .opener
+        .clone()
+        .unwrap_or_else(|| hyperlink::default_opener().to_string());
+    // フォーカスが戻ったときに変更を確かめるための、読み込んだファイルの更新時刻
+    let mut last_modified = modified_times(app);
+    let mut needs_redraw = true;
 
     loop {
-        terminal.draw(|f| tui::ui::render(f, app, theme_manager))?;
+        if needs_redraw {
+            let frame = terminal.draw(|f| tui::ui::render(f, app, theme_manager))?;
+            if hyperlinks {
+                hyperlink::write_hyperlinks(&mut io::stdout(), frame.buffer, &app.link_regions)?;
+            }
+        }
+        needs_redraw = true;
 
         tokio::select! {
-            key_event = event_handler.next_key() => {
-                if let Some(key) = key_event? {
-                    app.handle_key(key.code, key.modifiers);
-                    if app.should_quit {
-                        break;
+            input = event_handler.next_event() => {
+                // 端末が閉じられたら入力はもう来ないので終了する
+                let Some(event) = input? else {
+                    break;
+                };
+                match event {
+                    AppEvent::Key(key) => app.handle_key(key.code, key.modifiers),
+                    AppEvent::Mouse(mouse) => app.handle_mouse(mouse),
+                    AppEvent::Resize(..) => app.handle_resize(),
+                    AppEvent::FocusGained => {
+                        // ライブリロード中は監視で取りこぼした変更をここで読み直す
+                        if watcher.is_some() {
+                            reload_changed(app, highlighter, &mut last_modified);
+                        }
                     }
+                    AppEvent::FocusLost => needs_redraw = false,
+                    AppEvent::Paste(text) => app.handle_paste(&text),
+                    AppEvent::Tick => needs_redraw = app.tick(),
+                }
+                if app.should_quit {
+                    break;
+                }
+                if let Some(action) = app.pending_action.take() {
+                    handle_action(action, app, watcher, highlighter, &opener, stdin);
+                    last_modified = modified_times(app);
                 }
             }
             reload_event = async {
@@ -91,25 +505,18 @@ async fn run_app<'a>(
             } => {
                 if let Some(event) = reload_event {
                     match event {
-                        ReloadEvent::FileChanged(_) => {
-                            match std::fs::read_to_string(&cli.path) {
-                                Ok(content) => {
-                                    match MarkdownDocument::parse(
-                                        cli.path.clone(),
-                                        content,
-                                        highlighter,
-                                    ) {
-                                        Ok(new_document) => {
-                                            app.update_document(new_document);
-                                        }
-                                        Err(e) => {
-                                            eprintln!("Failed to parse markdown: {e}");
-                                        }
-                                    }
-                                }
-                                Err(e) => {
-                                    eprintln!("Failed to read file: {e}");
+                        ReloadEvent::FileChanged(path) => {
+                            // ディレクトリのファイルが増えたり減ったりしたらツリーを読み直す
+                            if in_file_tree(&app.file_tree, &path) {
+                                app.refresh_file_tree();
+                            }
+                            // 変更されたファイルのバッファだけを読み直す
+                            match app.find_buffer(&path) {
+                                Some(index) => {
+                                    reload_buffer(app, index, highlighter);
+                                    last_modified = modified_times(app);
                                 }
+                                None => reload_changed(app, highlighter, &mut last_modified),
                             }
                         }
                         ReloadEvent::Error(err) => {
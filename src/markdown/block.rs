@@ -0,0 +1,369 @@
+use super::inline::{push_span, InlineSpan};
+use super::parser::{AlertType, Alignment, ListMarker, ParsedLine};
+use super::source::SourceRange;
+
+/// CommonMark の入れ子構造を保ったブロックの木
+#[derive(Debug, Clone)]
+pub enum Block {
+    /// 子を持たないブロック（見出し・段落・コード・テーブルなど）
+    Leaf {
+        line: ParsedLine,
+        source: SourceRange,
+    },
+    /// 子ブロックを持つコンテナ
+    Container {
+        kind: ContainerKind,
+        children: Vec<Block>,
+        source: SourceRange,
+    },
+}
+
+/// コンテナの種類
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContainerKind {
+    BlockQuote,
+    /// `> [!NOTE]` などの GitHub Alerts
+    Alert(AlertType),
+    /// 箇条書き・番号付きリスト（`start` は番号付きの場合の開始番号）
+    List {
+        start: Option<u64>,
+    },
+    ListItem {
+        marker: ListMarker,
+        checked: Option<bool>,
+    },
+    /// 文末の脚注セクションの脚注
+    FootnoteDefinition {
+        number: usize,
+        label: String,
+    },
+    /// HTML の `<details>`（閉じていれば中身は表示しない）
+    Details {
+        index: usize,
+        open: bool,
+    },
+    /// HTML の `align` 属性や `<center>` で寄せたブロック
+    Aligned(Alignment),
+}
+
+/// 平坦化した行を囲むコンテナ（外側から順に並ぶ）
+#[derive(Debug, Clone, PartialEq)]
+pub struct Nesting {
+    pub kind: ContainerKind,
+    /// コンテナ内の最初の行か
+    pub first: bool,
+    /// コンテナ内の最後の行か
+    pub last: bool,
+}
+
+/// 平坦化した木（3つの Vec は同じ長さで、添字が `parsed_lines` の添字に対応する）
+#[derive(Debug, Clone, Default)]
+pub struct Flattened {
+    pub lines: Vec<ParsedLine>,
+    pub nesting: Vec<Vec<Nesting>>,
+    pub sources: Vec<SourceRange>,
+}
+
+impl Block {
+    pub fn leaf(line: ParsedLine, source: SourceRange) -> Self {
+        Block::Leaf { line, source }
+    }
+
+    pub fn source(&self) -> &SourceRange {
+        match self {
+            Block::Leaf { source, .. } | Block::Container { source, .. } => source,
+        }
+    }
+
+    /// 葉を文書順に巡回する
+    pub fn for_each_leaf_mut(blocks: &mut [Block], f: &mut impl FnMut(&mut ParsedLine)) {
+        for block in blocks {
+            match block {
+                Block::Leaf { line, .. } => f(line),
+                Block::Container { children, .. } => Self::for_each_leaf_mut(children, f),
+            }
+        }
+    }
+}
+
+/// 木を `parsed_lines` 形式に平坦化する
+///
+/// リスト項目・引用・アラート直下の段落は `ListItem` / `BlockQuote` / `Alert` に変換され、
+/// 変換に使ったコンテナはその行の `Nesting` には含まれない。
+pub fn flatten(blocks: &[Block]) -> Flattened {
+    let mut flat = Flattened::default();
+    flatten_into(blocks, &mut Vec::new(), &mut flat);
+    flat
+}
+
+fn flatten_into(blocks: &[Block], path: &mut Vec<ContainerKind>, flat: &mut Flattened) {
+    for block in blocks {
+        match block {
+            Block::Leaf { line, source } => push_line(line.clone(), source.clone(), path, flat),
+            Block::Container {
+                kind,
+                children,
+                source,
+            } => {
+                let start = flat.lines.len();
+                flatten_container(kind, children, source, path, flat);
+                mark_first_last(kind, path.len(), &mut flat.nesting[start..]);
+            }
+        }
+    }
+}
+
+fn flatten_container(
+    kind: &ContainerKind,
+    children: &[Block],
+    source: &SourceRange,
+    path: &mut Vec<ContainerKind>,
+    flat: &mut Flattened,
+) {
+    // 先頭の段落をコンテナ自身の行に変換する
+    let first_text = match children.first() {
+        Some(Block::Leaf {
+            line: ParsedLine::Text { content },
+            source,
+        }) => Some((content, source)),
+        _ => None,
+    };
+
+    let rest = match kind {
+        ContainerKind::ListItem { marker, checked } => {
+            let indent = path
+                .iter()
+                .filter(|kind| matches!(kind, ContainerKind::List { .. }))
+                .count()
+                .saturating_sub(1);
+            // マーカーから先頭の段落の末尾までを項目の行とする
+            let item_source = match first_text {
+                Some((_, text_source)) => source.join(text_source),
+                None => source.clone(),
+            };
+            push_line(
+                ParsedLine::ListItem {
+                    indent,
+                    content: first_text
+                        .map(|(content, _)| content.clone())
+                        .unwrap_or_default(),
+                    checked: *checked,
+                    marker: *marker,
+                },
+                item_source,
+                path,
+                flat,
+            );
+            if first_text.is_some() {
+                &children[1..]
+            } else {
+                children
+            }
+        }
+        // 本文のないアラート（`> [!WARNING]` だけ）も見出しの行は残す
+        ContainerKind::Alert(alert_type)
+            if children.is_empty() || (children.len() == 1 && first_text.is_some()) =>
+        {
+            let content = first_text
+                .map(|(content, _)| content.clone())
+                .unwrap_or_default();
+            push_line(
+                ParsedLine::Alert {
+                    alert_type: *alert_type,
+                    content,
+                },
+                source.clone(),
+                path,
+                flat,
+            );
+            return;
+        }
+        ContainerKind::FootnoteDefinition { number, label } => {
+            // 脚注の段落はひとつの本文にまとめる
+            let mut content: Vec<InlineSpan> = Vec::new();
+            let mut others = Vec::new();
+            for child in children {
+                match child {
+                    Block::Leaf {
+                        line: ParsedLine::Text { content: text },
+                        ..
+                    } => {
+                        if !content.is_empty() {
+                            push_span(&mut content, InlineSpan::plain("\n"));
+                        }
+                        for span in text {
+                            push_span(&mut content, span.clone());
+                        }
+                    }
+                    other => others.push(other.clone()),
+                }
+            }
+            push_line(
+                ParsedLine::FootnoteDefinition {
+                    number: *number,
+                    label: label.clone(),
+                    content,
+                },
+                source.clone(),
+                path,
+                flat,
+            );
+            path.push(kind.clone());
+            flatten_into(&others, path, flat);
+            path.pop();
+            return;
+        }
+        ContainerKind::Details { index, open } => {
+            // `<summary>` がなければブラウザと同じく "Details" と表示する
+            match children.first() {
+                Some(Block::Leaf {
+                    line: line @ ParsedLine::Details { .. },
+                    source,
+                }) => {
+                    push_line(line.clone(), source.clone(), path, flat);
+                    &children[1..]
+                }
+                _ => {
+                    push_line(
+                        ParsedLine::Details {
+                            index: *index,
+                            summary: vec![InlineSpan::plain("Details")],
+                            open: *open,
+                        },
+                        source.clone(),
+                        path,
+                        flat,
+                    );
+                    children
+                }
+            }
+        }
+        _ => children,
+    };
+
+    path.push(kind.clone());
+    for child in rest {
+        match (kind, child) {
+            (
+                ContainerKind::BlockQuote,
+                Block::Leaf {
+                    line: ParsedLine::Text { content },
+                    source,
+                },
+            ) => {
+                // 引用内の段落は引用の行として描く（このコンテナの線は行自身が描く）
+                path.pop();
+                push_line(
+                    ParsedLine::BlockQuote {
+                        content: content.clone(),
+                    },
+                    source.clone(),
+                    path,
+                    flat,
+                );
+                path.push(kind.clone());
+            }
+            _ => flatten_into(std::slice::from_ref(child), path, flat),
+        }
+    }
+    path.pop();
+}
+
+fn push_line(line: ParsedLine, source: SourceRange, path: &[ContainerKind], flat: &mut Flattened) {
+    flat.lines.push(line);
+    flat.sources.push(source);
+    flat.nesting.push(
+        path.iter()
+            .map(|kind| Nesting {
+                kind: kind.clone(),
+                first: false,
+                last: false,
+            })
+            .collect(),
+    );
+}
+
+/// `depth` 番目のコンテナが `kind` である行のうち最初と最後に印を付ける
+fn mark_first_last(kind: &ContainerKind, depth: usize, nesting: &mut [Vec<Nesting>]) {
+    let mut inside = nesting
+        .iter_mut()
+        .filter_map(|line| line.get_mut(depth))
+        .filter(|entry| entry.kind == *kind)
+        .peekable();
+
+    if let Some(first) = inside.next() {
+        first.first = true;
+        match inside.last() {
+            Some(last) => last.last = true,
+            None => first.last = true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> Block {
+        Block::leaf(
+            ParsedLine::Text {
+                content: vec![InlineSpan::plain(s)],
+            },
+            SourceRange::default(),
+        )
+    }
+
+    #[test]
+    fn test_flatten_nested_containers() {
+        let blocks = vec![Block::Container {
+            kind: ContainerKind::BlockQuote,
+            children: vec![
+                text("quote"),
+                Block::Container {
+                    kind: ContainerKind::List { start: None },
+                    children: vec![Block::Container {
+                        kind: ContainerKind::ListItem {
+                            marker: ListMarker::Bullet('-'),
+                            checked: None,
+                        },
+                        children: vec![
+                            text("item"),
+                            Block::leaf(ParsedLine::HorizontalRule, SourceRange::default()),
+                        ],
+                        source: SourceRange::default(),
+                    }],
+                    source: SourceRange::default(),
+                },
+            ],
+            source: SourceRange::default(),
+        }];
+
+        let Flattened {
+            lines,
+            nesting,
+            sources,
+        } = flatten(&blocks);
+        assert_eq!(sources.len(), lines.len());
+
+        assert_eq!(lines.len(), 3);
+        assert!(matches!(lines[0], ParsedLine::BlockQuote { .. }));
+        assert!(matches!(lines[1], ParsedLine::ListItem { indent: 0, .. }));
+        assert!(matches!(lines[2], ParsedLine::HorizontalRule));
+
+        // 引用の段落は引用自身が線を描くので外側のコンテナを持たない
+        assert!(nesting[0].is_empty());
+        let kinds =
+            |i: usize| -> Vec<&ContainerKind> { nesting[i].iter().map(|n| &n.kind).collect() };
+        assert_eq!(
+            kinds(1),
+            [
+                &ContainerKind::BlockQuote,
+                &ContainerKind::List { start: None }
+            ]
+        );
+        assert_eq!(kinds(2).len(), 3);
+        assert!(nesting[2][2].first && nesting[2][2].last);
+        assert!(nesting[1][0].first && !nesting[1][0].last);
+        assert!(nesting[2][0].last);
+    }
+}
@@ -0,0 +1,78 @@
+//! コードブロックの info string（```` ```bash file=deploy.sh ```` の `bash file=deploy.sh`）
+
+/// info string を言語と `key=value` の属性に分けたもの
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CodeInfo {
+    /// 最初の語（`=` を含まなければ）
+    pub language: Option<String>,
+    /// 記述順の属性（値の `"` は外す）
+    pub attributes: Vec<(String, String)>,
+}
+
+impl CodeInfo {
+    pub fn parse(info: &str) -> Self {
+        let mut code_info = CodeInfo::default();
+        for (position, word) in split_words(info).into_iter().enumerate() {
+            match word.split_once('=') {
+                Some((key, value)) => code_info
+                    .attributes
+                    .push((key.to_string(), value.to_string())),
+                None if position == 0 => code_info.language = Some(word),
+                None => {}
+            }
+        }
+        code_info
+    }
+
+    pub fn attr(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// ブロックの名前（`file=`、なければ `title=`）
+    pub fn file_name(&self) -> Option<&str> {
+        self.attr("file").or_else(|| self.attr("title"))
+    }
+}
+
+/// 空白で区切る（`"` で囲んだ部分の空白は区切らない）
+fn split_words(info: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut quoted = false;
+    for c in info.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+            }
+            c => word.push(c),
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_info_string() {
+        let info = CodeInfo::parse(r#"bash file=deploy.sh title="Deploy script""#);
+        assert_eq!(info.language.as_deref(), Some("bash"));
+        assert_eq!(info.attr("file"), Some("deploy.sh"));
+        assert_eq!(info.attr("title"), Some("Deploy script"));
+        assert_eq!(info.file_name(), Some("deploy.sh"));
+
+        let info = CodeInfo::parse("file=query.sql");
+        assert_eq!(info.language, None);
+        assert_eq!(info.attr("file"), Some("query.sql"));
+    }
+}
@@ -0,0 +1,232 @@
+use std::fmt;
+use yaml_rust::{Yaml, YamlLoader};
+
+/// フロントマターの形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrontMatterFormat {
+    /// `---` で囲まれた YAML
+    Yaml,
+    /// `+++` で囲まれた TOML
+    Toml,
+}
+
+/// フロントマターの値
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetadataValue {
+    Null,
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+    List(Vec<MetadataValue>),
+    /// キーはフロントマターでの記述順
+    Map(Vec<(String, MetadataValue)>),
+}
+
+impl MetadataValue {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            MetadataValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    fn from_yaml(yaml: &Yaml) -> Self {
+        match yaml {
+            Yaml::Boolean(b) => MetadataValue::Bool(*b),
+            Yaml::Integer(i) => MetadataValue::Integer(*i),
+            Yaml::Real(s) => s
+                .parse()
+                .map(MetadataValue::Float)
+                .unwrap_or_else(|_| MetadataValue::String(s.clone())),
+            Yaml::String(s) => MetadataValue::String(s.clone()),
+            Yaml::Array(items) => MetadataValue::List(items.iter().map(Self::from_yaml).collect()),
+            Yaml::Hash(hash) => MetadataValue::Map(
+                hash.iter()
+                    .map(|(key, value)| (yaml_key(key), Self::from_yaml(value)))
+                    .collect(),
+            ),
+            Yaml::Alias(_) | Yaml::Null | Yaml::BadValue => MetadataValue::Null,
+        }
+    }
+
+    fn from_toml(value: &toml::Value) -> Self {
+        match value {
+            toml::Value::String(s) => MetadataValue::String(s.clone()),
+            toml::Value::Integer(i) => MetadataValue::Integer(*i),
+            toml::Value::Float(f) => MetadataValue::Float(*f),
+            toml::Value::Boolean(b) => MetadataValue::Bool(*b),
+            toml::Value::Datetime(datetime) => MetadataValue::String(datetime.to_string()),
+            toml::Value::Array(items) => {
+                MetadataValue::List(items.iter().map(Self::from_toml).collect())
+            }
+            toml::Value::Table(table) => MetadataValue::Map(
+                table
+                    .iter()
+                    .map(|(key, value)| (key.clone(), Self::from_toml(value)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// 1行に収まるように表示する（リストは `, ` 区切り、表は `{key: value}`）
+impl fmt::Display for MetadataValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MetadataValue::Null => Ok(()),
+            MetadataValue::Bool(b) => write!(f, "{b}"),
+            MetadataValue::Integer(i) => write!(f, "{i}"),
+            MetadataValue::Float(x) => write!(f, "{x}"),
+            MetadataValue::String(s) => write!(f, "{s}"),
+            MetadataValue::List(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{item}")?;
+                }
+                Ok(())
+            }
+            MetadataValue::Map(entries) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{key}: {value}")?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn yaml_key(key: &Yaml) -> String {
+    match key {
+        Yaml::String(s) | Yaml::Real(s) => s.clone(),
+        other => MetadataValue::from_yaml(other).to_string(),
+    }
+}
+
+/// 文書先頭のフロントマター
+#[derive(Debug, Clone, PartialEq)]
+pub struct Metadata {
+    pub format: FrontMatterFormat,
+    /// トップレベルのキーと値（記述順）
+    pub entries: Vec<(String, MetadataValue)>,
+}
+
+impl Metadata {
+    pub fn get(&self, key: &str) -> Option<&MetadataValue> {
+        self.entries
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value)
+    }
+
+    /// `title` が空でない文字列なら返す
+    pub fn title(&self) -> Option<&str> {
+        self.get("title")
+            .and_then(MetadataValue::as_str)
+            .map(str::trim)
+            .filter(|title| !title.is_empty())
+    }
+}
+
+/// 先頭のフロントマターを読み取り、本文が始まるバイト位置とともに返す
+///
+/// 区切り行が閉じていない場合や、中身がキーと値の組として読めない場合は
+/// フロントマターとみなさず `(None, 0)` を返す（通常のマークダウンとして表示する）。
+pub fn extract(content: &str) -> (Option<Metadata>, usize) {
+    let mut lines = content.split_inclusive('\n');
+    let Some(first) = lines.next() else {
+        return (None, 0);
+    };
+    let (format, closers): (_, &[&str]) = match first.trim_end() {
+        "---" => (FrontMatterFormat::Yaml, &["---", "..."]),
+        "+++" => (FrontMatterFormat::Toml, &["+++"]),
+        _ => return (None, 0),
+    };
+
+    let body_start = first.len();
+    let mut offset = body_start;
+    for line in lines {
+        if closers.contains(&line.trim_end()) {
+            let source = &content[body_start..offset];
+            return match parse(format, source) {
+                Some(entries) => (Some(Metadata { format, entries }), offset + line.len()),
+                None => (None, 0),
+            };
+        }
+        offset += line.len();
+    }
+
+    (None, 0)
+}
+
+fn parse(format: FrontMatterFormat, source: &str) -> Option<Vec<(String, MetadataValue)>> {
+    match format {
+        FrontMatterFormat::Yaml => {
+            let docs = YamlLoader::load_from_str(source).ok()?;
+            match docs.first() {
+                None => Some(Vec::new()),
+                Some(yaml @ Yaml::Hash(_)) => match MetadataValue::from_yaml(yaml) {
+                    MetadataValue::Map(entries) => Some(entries),
+                    _ => None,
+                },
+                Some(_) => None,
+            }
+        }
+        FrontMatterFormat::Toml => {
+            let table: toml::Table = source.parse().ok()?;
+            Some(
+                table
+                    .iter()
+                    .map(|(key, value)| (key.clone(), MetadataValue::from_toml(value)))
+                    .collect(),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_yaml_front_matter() {
+        let content = "---\ntitle: Hello\ntags: [a, b]\ndraft: false\n---\n# Body\n";
+        let (metadata, body_start) = extract(content);
+        let metadata = metadata.unwrap();
+
+        assert_eq!(metadata.format, FrontMatterFormat::Yaml);
+        assert_eq!(metadata.title(), Some("Hello"));
+        assert_eq!(metadata.get("tags").unwrap().to_string(), "a, b");
+        assert_eq!(metadata.get("draft"), Some(&MetadataValue::Bool(false)));
+        assert_eq!(&content[body_start..], "# Body\n");
+    }
+
+    #[test]
+    fn test_toml_front_matter_keeps_order() {
+        let content = "+++\nzeta = 1\nalpha = \"x\"\n+++\nbody";
+        let (metadata, body_start) = extract(content);
+        let keys: Vec<_> = metadata
+            .unwrap()
+            .entries
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect();
+
+        assert_eq!(keys, ["zeta", "alpha"]);
+        assert_eq!(&content[body_start..], "body");
+    }
+
+    #[test]
+    fn test_not_front_matter() {
+        // 閉じていない区切り行や、キーと値でない中身は本文として扱う
+        assert_eq!(extract("---\ntitle: x\n"), (None, 0));
+        assert_eq!(extract("---\njust text\n---\n"), (None, 0));
+        assert_eq!(extract("# Title\n---\n"), (None, 0));
+    }
+}
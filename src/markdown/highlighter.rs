@@ -1,6 +1,6 @@
 use once_cell::sync::Lazy;
 use syntect::easy::HighlightLines;
-use syntect::highlighting::{Style, Theme, ThemeSet};
+use syntect::highlighting::{Color, Style, Theme, ThemeSet};
 use syntect::parsing::SyntaxSet;
 
 static SYNTAX_SET: Lazy<SyntaxSet> = Lazy::new(SyntaxSet::load_defaults_newlines);
@@ -30,6 +30,11 @@ impl CodeHighlighter {
             .unwrap() // Safe: ThemeSet.load_defaults() always returns a non-empty theme set
     }
 
+    /// テーマのコードの背景色
+    pub fn background(&self) -> Option<Color> {
+        self.get_theme().settings.background
+    }
+
     pub fn highlight(&self, code: &str, lang: Option<&str>) -> Vec<Vec<StyledSpan>> {
         let syntax = lang
             .and_then(|l| SYNTAX_SET.find_syntax_by_token(l))
@@ -0,0 +1,247 @@
+//! マークダウン中の HTML の小さなサブセットを解釈するための字句解析
+
+/// HTML の字句
+#[derive(Debug, Clone, PartialEq)]
+pub enum HtmlToken {
+    /// 開始タグ（名前は小文字）
+    Open {
+        name: String,
+        attrs: Vec<(String, String)>,
+        self_closing: bool,
+        /// タグの元の文字列
+        raw: String,
+    },
+    /// 終了タグ（名前は小文字）
+    Close { name: String, raw: String },
+    /// 実体参照を展開したテキスト
+    Text(String),
+    /// `<!-- -->` などの表示しない部分
+    Comment,
+}
+
+impl HtmlToken {
+    pub fn attr(&self, key: &str) -> Option<&str> {
+        match self {
+            HtmlToken::Open { attrs, .. } => attrs
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, value)| value.as_str()),
+            _ => None,
+        }
+    }
+}
+
+pub fn tokenize(source: &str) -> Vec<HtmlToken> {
+    let mut tokens = Vec::new();
+    let mut rest = source;
+
+    while !rest.is_empty() {
+        let Some(start) = rest.find('<') else {
+            tokens.push(HtmlToken::Text(decode_entities(rest)));
+            break;
+        };
+        if start > 0 {
+            tokens.push(HtmlToken::Text(decode_entities(&rest[..start])));
+            rest = &rest[start..];
+        }
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            tokens.push(HtmlToken::Comment);
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        // `<!DOCTYPE>` や `<?xml ?>` も表示しない
+        if rest.starts_with("<!") || rest.starts_with("<?") {
+            tokens.push(HtmlToken::Comment);
+            rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+            continue;
+        }
+
+        match parse_tag(rest) {
+            Some((token, len)) => {
+                tokens.push(token);
+                rest = &rest[len..];
+            }
+            None => {
+                // タグとして読めない `<` は文字として扱う
+                tokens.push(HtmlToken::Text("<".to_string()));
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    tokens
+}
+
+/// `<` から始まるタグを読み、字句と読んだバイト数を返す
+fn parse_tag(source: &str) -> Option<(HtmlToken, usize)> {
+    let body = source.strip_prefix('<')?;
+    let (closing, body) = match body.strip_prefix('/') {
+        Some(body) => (true, body),
+        None => (false, body),
+    };
+
+    let name_len = body
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+        .unwrap_or(body.len());
+    if name_len == 0 || !body.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let name = body[..name_len].to_ascii_lowercase();
+
+    // 引用符の中の `>` で終わらないように属性を読む
+    let mut attrs = Vec::new();
+    let mut chars = body[name_len..].char_indices().peekable();
+    let mut self_closing = false;
+    let end = loop {
+        let (i, c) = chars.next()?;
+        match c {
+            '>' => break i,
+            '/' => self_closing = true,
+            c if c.is_whitespace() => {}
+            _ => {
+                self_closing = false;
+                let mut key = c.to_string();
+                while let Some(&(_, c)) = chars.peek() {
+                    if c.is_whitespace() || matches!(c, '=' | '>' | '/') {
+                        break;
+                    }
+                    key.push(c);
+                    chars.next();
+                }
+                while chars.peek().is_some_and(|&(_, c)| c.is_whitespace()) {
+                    chars.next();
+                }
+                let mut value = String::new();
+                if chars.peek().is_some_and(|&(_, c)| c == '=') {
+                    chars.next();
+                    while chars.peek().is_some_and(|&(_, c)| c.is_whitespace()) {
+                        chars.next();
+                    }
+                    match chars.peek().map(|&(_, c)| c) {
+                        Some(quote @ ('"' | '\'')) => {
+                            chars.next();
+                            for (_, c) in chars.by_ref() {
+                                if c == quote {
+                                    break;
+                                }
+                                value.push(c);
+                            }
+                        }
+                        _ => {
+                            while let Some(&(_, c)) = chars.peek() {
+                                if c.is_whitespace() || c == '>' {
+                                    break;
+                                }
+                                value.push(c);
+                                chars.next();
+                            }
+                        }
+                    }
+                }
+                attrs.push((key.to_ascii_lowercase(), decode_entities(&value)));
+            }
+        }
+    };
+
+    let len = 1 + usize::from(closing) + name_len + end + 1;
+    let raw = source[..len].to_string();
+    let token = if closing {
+        HtmlToken::Close { name, raw }
+    } else {
+        HtmlToken::Open {
+            name,
+            attrs,
+            self_closing,
+            raw,
+        }
+    };
+    Some((token, len))
+}
+
+/// よく使われる実体参照と数値文字参照を展開する
+pub fn decode_entities(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some('\u{a0}'),
+                "copy" => Some('©'),
+                "reg" => Some('®'),
+                "trade" => Some('™'),
+                "hellip" => Some('…'),
+                "mdash" => Some('—'),
+                "ndash" => Some('–'),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                    .and_then(char::from_u32),
+            };
+            c.map(|c| (c, end + 1))
+        });
+
+        match decoded {
+            Some((c, len)) => {
+                result.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                result.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize() {
+        let tokens =
+            tokenize(r#"<p align="center"><img src="a.png" alt='A > B'/>x &amp; y</p><!-- c -->"#);
+
+        assert!(matches!(&tokens[0], HtmlToken::Open { name, .. } if name == "p"));
+        assert_eq!(tokens[0].attr("align"), Some("center"));
+        assert!(matches!(
+            &tokens[1],
+            HtmlToken::Open { name, self_closing: true, .. } if name == "img"
+        ));
+        assert_eq!(tokens[1].attr("alt"), Some("A > B"));
+        assert_eq!(tokens[2], HtmlToken::Text("x & y".to_string()));
+        assert!(matches!(&tokens[3], HtmlToken::Close { name, .. } if name == "p"));
+        assert_eq!(tokens[4], HtmlToken::Comment);
+        assert_eq!(tokens.len(), 5);
+    }
+
+    #[test]
+    fn test_not_a_tag() {
+        let tokens = tokenize("a < b");
+        let text: String = tokens
+            .iter()
+            .map(|token| match token {
+                HtmlToken::Text(text) => text.as_str(),
+                _ => "",
+            })
+            .collect();
+
+        assert_eq!(text, "a < b");
+    }
+}
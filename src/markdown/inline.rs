@@ -0,0 +1,228 @@
+/// インライン要素の装飾
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InlineStyle {
+    pub code: bool,
+    pub emphasis: bool,
+    pub strong: bool,
+    pub strikethrough: bool,
+    /// Unicode に組版したインライン数式
+    pub math: bool,
+    /// `<kbd>` のキー表記
+    pub kbd: bool,
+    /// 解釈できない HTML タグ（薄く表示する）
+    pub html: bool,
+}
+
+impl InlineStyle {
+    pub fn is_plain(&self) -> bool {
+        *self == InlineStyle::default()
+    }
+}
+
+/// リンク先
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    /// ドキュメント内で何番目のリンクか（0始まり）
+    pub index: usize,
+    pub url: String,
+}
+
+/// 同じ装飾を持つインラインテキストの断片
+#[derive(Debug, Clone, PartialEq)]
+pub struct InlineSpan {
+    pub text: String,
+    pub style: InlineStyle,
+    /// リンク先（リンク内のテキストの場合のみ）
+    pub link: Option<Link>,
+    /// 脚注参照の番号（`[^label]` のマーカーの場合のみ）
+    pub footnote: Option<usize>,
+}
+
+impl InlineSpan {
+    pub fn plain(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            style: InlineStyle::default(),
+            link: None,
+            footnote: None,
+        }
+    }
+
+    pub fn code(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            style: InlineStyle {
+                code: true,
+                ..InlineStyle::default()
+            },
+            link: None,
+            footnote: None,
+        }
+    }
+
+    /// 上付き数字で表示する脚注参照のマーカー
+    pub fn footnote_ref(number: usize) -> Self {
+        Self {
+            text: superscript(number),
+            style: InlineStyle::default(),
+            link: None,
+            footnote: Some(number),
+        }
+    }
+
+    fn same_format(&self, other: &InlineSpan) -> bool {
+        self.style == other.style && self.link == other.link && self.footnote == other.footnote
+    }
+}
+
+/// スパンを追加する。直前のスパンと装飾が同じなら結合する
+pub fn push_span(spans: &mut Vec<InlineSpan>, span: InlineSpan) {
+    if span.text.is_empty() {
+        return;
+    }
+    match spans.last_mut() {
+        Some(last) if last.same_format(&span) => last.text.push_str(&span.text),
+        _ => spans.push(span),
+    }
+}
+
+/// 数値を上付き数字（`¹²³`）に変換する
+pub fn superscript(number: usize) -> String {
+    const DIGITS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+
+    number
+        .to_string()
+        .chars()
+        .filter_map(|c| c.to_digit(10).map(|d| DIGITS[d as usize]))
+        .collect()
+}
+
+/// 装飾を取り除いたテキストを返す
+pub fn plain_text(spans: &[InlineSpan]) -> String {
+    spans.iter().map(|s| s.text.as_str()).collect()
+}
+
+/// 空白以外の文字を含まない場合に true
+pub fn is_blank(spans: &[InlineSpan]) -> bool {
+    spans.iter().all(|s| s.text.trim().is_empty())
+}
+
+/// 先頭と末尾の空白を取り除く（`str::trim` のスパン版）
+pub fn trim_spans(spans: Vec<InlineSpan>) -> Vec<InlineSpan> {
+    let mut spans: Vec<InlineSpan> = spans;
+
+    while let Some(first) = spans.first_mut() {
+        let trimmed = first.text.trim_start();
+        if trimmed.is_empty() {
+            spans.remove(0);
+        } else {
+            first.text = trimmed.to_string();
+            break;
+        }
+    }
+
+    while let Some(last) = spans.last_mut() {
+        let trimmed = last.text.trim_end();
+        if trimmed.is_empty() {
+            spans.pop();
+        } else {
+            last.text = trimmed.to_string();
+            break;
+        }
+    }
+
+    spans
+}
+
+/// 先頭のテキストが `prefix` で始まる場合、それを取り除いたスパン列を返す
+pub fn strip_prefix_spans(spans: &[InlineSpan], prefix: &str) -> Option<Vec<InlineSpan>> {
+    let first = spans.first()?;
+    let rest = first.text.strip_prefix(prefix)?;
+
+    let mut result = Vec::with_capacity(spans.len());
+    push_span(
+        &mut result,
+        InlineSpan {
+            text: rest.to_string(),
+            ..first.clone()
+        },
+    );
+    for span in &spans[1..] {
+        push_span(&mut result, span.clone());
+    }
+    Some(result)
+}
+
+/// `\n` でスパン列を行ごとに分割する
+pub fn split_lines(spans: &[InlineSpan]) -> Vec<Vec<InlineSpan>> {
+    let mut lines = vec![Vec::new()];
+
+    for span in spans {
+        for (i, part) in span.text.split('\n').enumerate() {
+            if i > 0 {
+                lines.push(Vec::new());
+            }
+            if let Some(line) = lines.last_mut() {
+                push_span(
+                    line,
+                    InlineSpan {
+                        text: part.to_string(),
+                        ..span.clone()
+                    },
+                );
+            }
+        }
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_span_merges_same_format() {
+        let mut spans = Vec::new();
+        push_span(&mut spans, InlineSpan::plain("["));
+        push_span(&mut spans, InlineSpan::plain("!NOTE"));
+        push_span(&mut spans, InlineSpan::plain("]"));
+        push_span(&mut spans, InlineSpan::code("x"));
+
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[0].text, "[!NOTE]");
+        assert_eq!(spans[1].text, "x");
+    }
+
+    #[test]
+    fn test_trim_spans() {
+        let spans = vec![
+            InlineSpan::plain("  "),
+            InlineSpan::plain(" hello "),
+            InlineSpan::code("x"),
+            InlineSpan::plain("  "),
+        ];
+        let trimmed = trim_spans(spans);
+
+        assert_eq!(plain_text(&trimmed), "hello x");
+        assert_eq!(trimmed[0].text, "hello ");
+    }
+
+    #[test]
+    fn test_superscript() {
+        assert_eq!(superscript(1), "¹");
+        assert_eq!(superscript(10), "¹⁰");
+        assert_eq!(superscript(2048), "²⁰⁴⁸");
+    }
+
+    #[test]
+    fn test_split_lines() {
+        let spans = vec![InlineSpan::plain("a\nb"), InlineSpan::code("c")];
+        let lines = split_lines(&spans);
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(plain_text(&lines[0]), "a");
+        assert_eq!(plain_text(&lines[1]), "bc");
+        assert!(lines[1][1].style.code);
+    }
+}
@@ -0,0 +1,204 @@
+use super::block::Block;
+use super::inline::{InlineSpan, Link};
+use super::parser::ParsedLine;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// ドキュメント内のリンク（`Link::index` の順に並ぶ）
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocLink {
+    pub url: String,
+    pub text: String,
+    /// リンクを含む `parsed_lines` のインデックス
+    pub line_index: usize,
+}
+
+/// リンクをたどった先
+#[derive(Debug, Clone, PartialEq)]
+pub enum LinkTarget {
+    /// 同じドキュメント内の見出し (`#anchor`)
+    Anchor(String),
+    /// ビューアーで開くマークダウンファイル
+    Markdown {
+        path: PathBuf,
+        anchor: Option<String>,
+    },
+    /// 外部コマンドで開くURLまたはファイル
+    External(String),
+}
+
+impl LinkTarget {
+    /// リンク先を分類する。相対パスは `base_dir` を基準に解決する
+    pub fn resolve(url: &str, base_dir: &Path) -> Self {
+        if let Some(anchor) = url.strip_prefix('#') {
+            return LinkTarget::Anchor(anchor.to_string());
+        }
+
+        if has_scheme(url) {
+            return LinkTarget::External(url.to_string());
+        }
+
+        let (path_part, anchor) = match url.split_once('#') {
+            Some((path, anchor)) => (path, Some(anchor.to_string())),
+            None => (url, None),
+        };
+        let path = base_dir.join(path_part);
+
+        if is_markdown_path(&path) {
+            LinkTarget::Markdown { path, anchor }
+        } else {
+            LinkTarget::External(path.display().to_string())
+        }
+    }
+}
+
+/// `https:` や `mailto:` のようなスキームを持つか
+fn has_scheme(url: &str) -> bool {
+    match url.split_once(':') {
+        // `C:\foo` のようなWindowsのドライブ名はスキームとみなさない
+        Some((scheme, _)) => {
+            scheme.len() > 1
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        }
+        None => false,
+    }
+}
+
+/// マークダウンとして扱うファイルの拡張子（リンク先とファイルツリーで共通）
+pub const MARKDOWN_EXTENSIONS: &[&str] = &["md", "markdown", "mdown", "mkd", "mkdn"];
+
+pub fn is_markdown_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            MARKDOWN_EXTENSIONS
+                .iter()
+                .any(|markdown| extension.eq_ignore_ascii_case(markdown))
+        })
+}
+
+/// `parsed_lines` に含まれるリンクを `Link::index` 順に集める
+pub fn collect_links(parsed_lines: &[ParsedLine]) -> Vec<DocLink> {
+    let mut links: BTreeMap<usize, DocLink> = BTreeMap::new();
+
+    for (line_index, line) in parsed_lines.iter().enumerate() {
+        for_each_inline(line, |spans| {
+            for span in spans {
+                if let Some(Link { index, url }) = &span.link {
+                    let link = links.entry(*index).or_insert_with(|| DocLink {
+                        url: url.clone(),
+                        text: String::new(),
+                        line_index,
+                    });
+                    if link.line_index == line_index {
+                        link.text.push_str(&span.text);
+                    }
+                }
+            }
+        });
+    }
+
+    links.into_values().collect()
+}
+
+/// ブロックに含まれるインラインテキストを順番に渡す
+pub fn for_each_inline(line: &ParsedLine, mut f: impl FnMut(&[InlineSpan])) {
+    match line {
+        ParsedLine::Heading { text, .. } | ParsedLine::Details { summary: text, .. } => f(text),
+        ParsedLine::Text { content }
+        | ParsedLine::ListItem { content, .. }
+        | ParsedLine::BlockQuote { content }
+        | ParsedLine::Alert { content, .. }
+        | ParsedLine::FootnoteDefinition { content, .. } => f(content),
+        ParsedLine::Table { headers, rows, .. } => {
+            for cell in headers.iter().chain(rows.iter().flatten()) {
+                f(cell);
+            }
+        }
+        ParsedLine::Code { .. }
+        | ParsedLine::Math { .. }
+        | ParsedLine::Image { .. }
+        | ParsedLine::HorizontalRule
+        | ParsedLine::Empty => {}
+    }
+}
+
+/// `for_each_inline` の可変版
+pub fn for_each_inline_mut(line: &mut ParsedLine, mut f: impl FnMut(&mut Vec<InlineSpan>)) {
+    match line {
+        ParsedLine::Heading { text, .. } | ParsedLine::Details { summary: text, .. } => f(text),
+        ParsedLine::Text { content }
+        | ParsedLine::ListItem { content, .. }
+        | ParsedLine::BlockQuote { content }
+        | ParsedLine::Alert { content, .. }
+        | ParsedLine::FootnoteDefinition { content, .. } => f(content),
+        ParsedLine::Table { headers, rows, .. } => {
+            for cell in headers.iter_mut().chain(rows.iter_mut().flatten()) {
+                f(cell);
+            }
+        }
+        ParsedLine::Code { .. }
+        | ParsedLine::Math { .. }
+        | ParsedLine::Image { .. }
+        | ParsedLine::HorizontalRule
+        | ParsedLine::Empty => {}
+    }
+}
+
+/// `Link::index` を文書での出現順に振り直す
+pub fn renumber_links(blocks: &mut [Block]) {
+    let mut mapping: HashMap<usize, usize> = HashMap::new();
+
+    Block::for_each_leaf_mut(blocks, &mut |line| {
+        for_each_inline_mut(line, |spans| {
+            for link in spans.iter_mut().filter_map(|span| span.link.as_mut()) {
+                let next = mapping.len();
+                link.index = *mapping.entry(link.index).or_insert(next);
+            }
+        });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_link_targets() {
+        let base = Path::new("docs");
+
+        assert_eq!(
+            LinkTarget::resolve("#install", base),
+            LinkTarget::Anchor("install".to_string())
+        );
+        assert_eq!(
+            LinkTarget::resolve("https://example.com", base),
+            LinkTarget::External("https://example.com".to_string())
+        );
+        assert_eq!(
+            LinkTarget::resolve("mailto:me@example.com", base),
+            LinkTarget::External("mailto:me@example.com".to_string())
+        );
+        assert_eq!(
+            LinkTarget::resolve("guide/usage.md#keys", base),
+            LinkTarget::Markdown {
+                path: PathBuf::from("docs/guide/usage.md"),
+                anchor: Some("keys".to_string()),
+            }
+        );
+        // ファイルツリーに載る拡張子はリンクでもマークダウンとして開く
+        assert_eq!(
+            LinkTarget::resolve("NOTES.MKDN", base),
+            LinkTarget::Markdown {
+                path: PathBuf::from("docs/NOTES.MKDN"),
+                anchor: None,
+            }
+        );
+        assert_eq!(
+            LinkTarget::resolve("logo.png", base),
+            LinkTarget::External(PathBuf::from("docs/logo.png").display().to_string())
+        );
+    }
+}
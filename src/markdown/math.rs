@@ -0,0 +1,1373 @@
+//! LaTeX の数式を Unicode 文字で組版する
+//!
+//! 分数・行列・上下付き添字などを複数行のテキストとして描画する。
+//! 対応していない構文は `MathError::Unsupported` を返し、呼び出し側でソースを表示する。
+
+use thiserror::Error;
+use unicode_width::UnicodeWidthStr;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum MathError {
+    #[error("unsupported LaTeX: {0}")]
+    Unsupported(String),
+
+    #[error("invalid LaTeX: {0}")]
+    Syntax(String),
+}
+
+type Result<T> = std::result::Result<T, MathError>;
+
+/// ディスプレイ数式（`$$...$$`）を複数行のテキストに組版する
+pub fn render_display(source: &str) -> Result<Vec<String>> {
+    let node = MathParser::new(source).parse()?;
+    let rendered = layout(&node, Style::DISPLAY)?;
+    Ok(rendered
+        .rows
+        .into_iter()
+        .map(|row| row.trim_end().to_string())
+        .collect())
+}
+
+/// インライン数式（`$...$`）を1行のテキストに組版する。1行に収まらなければ None
+pub fn render_inline(source: &str) -> Option<String> {
+    let node = MathParser::new(source).parse().ok()?;
+    let rendered = layout(&node, Style::INLINE).ok()?;
+    match rendered.rows.as_slice() {
+        [row] => Some(row.trim().to_string()),
+        _ => None,
+    }
+}
+
+/// 数式の構文木
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    /// 文字・記号
+    Text(String),
+    /// 前後に空白を入れる演算子・関係記号
+    Op(String),
+    Row(Vec<Node>),
+    Scripts {
+        base: Box<Node>,
+        sub: Option<Box<Node>>,
+        sup: Option<Box<Node>>,
+    },
+    Frac(Box<Node>, Box<Node>),
+    Sqrt {
+        index: Option<Box<Node>>,
+        body: Box<Node>,
+    },
+    /// `\sum` `\int` `\lim` など。`limits` なら添字を記号の上下に置く
+    BigOp {
+        symbol: &'static str,
+        limits: bool,
+        sub: Option<Box<Node>>,
+        sup: Option<Box<Node>>,
+    },
+    /// `\left( ... \right)`
+    Delimited {
+        left: String,
+        right: String,
+        body: Box<Node>,
+    },
+    Matrix {
+        kind: MatrixKind,
+        rows: Vec<Vec<Node>>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MatrixKind {
+    Plain,
+    Paren,
+    Bracket,
+    Brace,
+    Vert,
+    DoubleVert,
+    Cases,
+    Aligned,
+}
+
+impl MatrixKind {
+    fn from_env(name: &str) -> Option<Self> {
+        Some(match name {
+            "matrix" | "smallmatrix" | "array" => MatrixKind::Plain,
+            "pmatrix" => MatrixKind::Paren,
+            "bmatrix" => MatrixKind::Bracket,
+            "Bmatrix" => MatrixKind::Brace,
+            "vmatrix" => MatrixKind::Vert,
+            "Vmatrix" => MatrixKind::DoubleVert,
+            "cases" => MatrixKind::Cases,
+            "aligned" | "align" | "align*" | "gathered" | "split" => MatrixKind::Aligned,
+            _ => return None,
+        })
+    }
+
+    fn delimiters(self) -> (&'static str, &'static str) {
+        match self {
+            MatrixKind::Plain | MatrixKind::Aligned => ("", ""),
+            MatrixKind::Paren => ("(", ")"),
+            MatrixKind::Bracket => ("[", "]"),
+            MatrixKind::Brace => ("{", "}"),
+            MatrixKind::Vert => ("|", "|"),
+            MatrixKind::DoubleVert => ("‖", "‖"),
+            MatrixKind::Cases => ("{", ""),
+        }
+    }
+}
+
+struct MathParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl MathParser {
+    fn new(source: &str) -> Self {
+        Self {
+            chars: source.chars().collect(),
+            pos: 0,
+        }
+    }
+
+    fn parse(mut self) -> Result<Node> {
+        let row = self.parse_row()?;
+        self.skip_whitespace();
+        match self.peek() {
+            None => Ok(row),
+            Some(c) => Err(MathError::Syntax(format!("unexpected '{c}'"))),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        s.chars()
+            .enumerate()
+            .all(|(i, c)| self.chars.get(self.pos + i) == Some(&c))
+    }
+
+    /// `\right` `\end` のようにコマンド名が完全に一致するか
+    fn at_command(&self, name: &str) -> bool {
+        self.starts_with(name)
+            && !self
+                .chars
+                .get(self.pos + name.chars().count())
+                .is_some_and(char::is_ascii_alphabetic)
+    }
+
+    fn expect(&mut self, c: char) -> Result<()> {
+        self.skip_whitespace();
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(MathError::Syntax(format!("expected '{c}'")))
+        }
+    }
+
+    /// `}` `&` `\\` `\right` `\end` または入力の終わりまでを読む
+    fn parse_row(&mut self) -> Result<Node> {
+        let mut nodes = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None | Some('}') | Some('&') | Some(']') => break,
+                Some('\\')
+                    if self.starts_with("\\\\")
+                        || self.at_command("\\right")
+                        || self.at_command("\\end") =>
+                {
+                    break
+                }
+                _ => {}
+            }
+            let atom = self.parse_atom()?;
+            nodes.push(self.parse_scripts(atom)?);
+        }
+
+        Ok(match nodes.len() {
+            1 => nodes.remove(0),
+            _ => Node::Row(nodes),
+        })
+    }
+
+    fn parse_scripts(&mut self, base: Node) -> Result<Node> {
+        let mut sub = None;
+        let mut sup = None;
+
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('_') if sub.is_none() => {
+                    self.pos += 1;
+                    sub = Some(Box::new(self.parse_arg()?));
+                }
+                Some('^') if sup.is_none() => {
+                    self.pos += 1;
+                    sup = Some(Box::new(self.parse_arg()?));
+                }
+                Some('\'') => {
+                    // x' は x^{′} と同じ
+                    let mut primes = String::new();
+                    while self.peek() == Some('\'') {
+                        self.pos += 1;
+                        primes.push('′');
+                    }
+                    sup = Some(Box::new(match sup.take() {
+                        Some(existing) => Node::Row(vec![Node::Text(primes), *existing]),
+                        None => Node::Text(primes),
+                    }));
+                }
+                _ => break,
+            }
+        }
+
+        if sub.is_none() && sup.is_none() {
+            return Ok(base);
+        }
+
+        Ok(match base {
+            Node::BigOp {
+                symbol,
+                limits,
+                sub: None,
+                sup: None,
+            } => Node::BigOp {
+                symbol,
+                limits,
+                sub,
+                sup,
+            },
+            base => Node::Scripts {
+                base: Box::new(base),
+                sub,
+                sup,
+            },
+        })
+    }
+
+    /// コマンドの引数（`{...}` または1文字・1コマンド）
+    fn parse_arg(&mut self) -> Result<Node> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => self.parse_group(),
+            Some(_) => self.parse_atom(),
+            None => Err(MathError::Syntax("missing argument".to_string())),
+        }
+    }
+
+    fn parse_group(&mut self) -> Result<Node> {
+        self.expect('{')?;
+        let row = self.parse_row()?;
+        self.expect('}')?;
+        Ok(row)
+    }
+
+    /// `{...}` の中身をそのまま読む（`\text` など）
+    fn parse_raw_group(&mut self) -> Result<String> {
+        self.expect('{')?;
+        let mut depth = 0;
+        let mut text = String::new();
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                '{' => depth += 1,
+                '}' if depth == 0 => return Ok(text),
+                '}' => depth -= 1,
+                _ => {}
+            }
+            text.push(c);
+        }
+        Err(MathError::Syntax("unclosed '{'".to_string()))
+    }
+
+    fn parse_atom(&mut self) -> Result<Node> {
+        self.skip_whitespace();
+        let Some(c) = self.peek() else {
+            return Err(MathError::Syntax("unexpected end".to_string()));
+        };
+
+        match c {
+            '{' => self.parse_group(),
+            '\\' => {
+                self.pos += 1;
+                self.parse_command()
+            }
+            // 基底のない添字 (`^2`)
+            '^' | '_' => Ok(Node::Text(String::new())),
+            _ => {
+                self.pos += 1;
+                Ok(match c {
+                    '=' | '<' | '>' | '+' | '±' | '×' | '÷' => Node::Op(c.to_string()),
+                    '-' => Node::Op("−".to_string()),
+                    '*' => Node::Op("∗".to_string()),
+                    ',' | ';' => Node::Text(format!("{c} ")),
+                    _ => Node::Text(c.to_string()),
+                })
+            }
+        }
+    }
+
+    fn parse_command(&mut self) -> Result<Node> {
+        let name: String = match self.peek() {
+            Some(c) if c.is_ascii_alphabetic() => {
+                let start = self.pos;
+                while self.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+                    self.pos += 1;
+                }
+                self.chars[start..self.pos].iter().collect()
+            }
+            Some(c) => {
+                self.pos += 1;
+                c.to_string()
+            }
+            None => return Err(MathError::Syntax("trailing '\\'".to_string())),
+        };
+
+        match name.as_str() {
+            "frac" | "dfrac" | "tfrac" | "cfrac" => {
+                let num = self.parse_arg()?;
+                let den = self.parse_arg()?;
+                Ok(Node::Frac(Box::new(num), Box::new(den)))
+            }
+            "sqrt" => {
+                self.skip_whitespace();
+                let index = if self.peek() == Some('[') {
+                    self.pos += 1;
+                    let index = self.parse_row()?;
+                    self.expect(']')?;
+                    Some(Box::new(index))
+                } else {
+                    None
+                };
+                let body = self.parse_arg()?;
+                Ok(Node::Sqrt {
+                    index,
+                    body: Box::new(body),
+                })
+            }
+            "left" => {
+                let left = self.parse_delimiter()?;
+                let body = self.parse_row()?;
+                self.skip_whitespace();
+                if !self.at_command("\\right") {
+                    return Err(MathError::Syntax("missing \\right".to_string()));
+                }
+                self.pos += "\\right".len();
+                let right = self.parse_delimiter()?;
+                Ok(Node::Delimited {
+                    left,
+                    right,
+                    body: Box::new(body),
+                })
+            }
+            "begin" => {
+                let env = self.parse_raw_group()?;
+                let kind = MatrixKind::from_env(&env)
+                    .ok_or_else(|| MathError::Unsupported(format!("\\begin{{{env}}}")))?;
+                if env == "array" {
+                    // 列指定 `{cc}` は読み飛ばす
+                    self.skip_whitespace();
+                    if self.peek() == Some('{') {
+                        self.parse_raw_group()?;
+                    }
+                }
+                let rows = self.parse_matrix_rows()?;
+                self.pos += "\\end".len();
+                let end = self.parse_raw_group()?;
+                if end != env {
+                    return Err(MathError::Syntax(format!(
+                        "\\begin{{{env}}} closed by \\end{{{end}}}"
+                    )));
+                }
+                Ok(Node::Matrix { kind, rows })
+            }
+            "text" | "textrm" | "textit" | "textbf" | "mathrm" | "mathit" | "mathbf" | "mathsf"
+            | "mathtt" | "boldsymbol" | "operatorname" | "mathcal" => {
+                let text = self.parse_raw_group()?;
+                if text.contains('\\') {
+                    // `\mathbf{\alpha}` などは中身を数式として読む
+                    let node = MathParser::new(&text).parse()?;
+                    Ok(node)
+                } else {
+                    Ok(Node::Text(text))
+                }
+            }
+            "mathbb" => {
+                let text = self.parse_raw_group()?;
+                Ok(Node::Text(text.chars().map(double_struck).collect()))
+            }
+            "hat" | "widehat" | "bar" | "overline" | "vec" | "dot" | "ddot" | "tilde"
+            | "widetilde" => {
+                let arg = self.parse_arg()?;
+                let mark = match name.as_str() {
+                    "hat" | "widehat" => '\u{0302}',
+                    "bar" | "overline" => '\u{0304}',
+                    "vec" => '\u{20D7}',
+                    "dot" => '\u{0307}',
+                    "ddot" => '\u{0308}',
+                    _ => '\u{0303}',
+                };
+                match arg {
+                    Node::Text(text) if text.chars().count() == 1 => {
+                        Ok(Node::Text(format!("{text}{mark}")))
+                    }
+                    _ => Err(MathError::Unsupported(format!("\\{name} over a group"))),
+                }
+            }
+            "sum" => Ok(big_op("∑", true)),
+            "prod" => Ok(big_op("∏", true)),
+            "coprod" => Ok(big_op("∐", true)),
+            "bigcup" => Ok(big_op("⋃", true)),
+            "bigcap" => Ok(big_op("⋂", true)),
+            "bigoplus" => Ok(big_op("⨁", true)),
+            "bigotimes" => Ok(big_op("⨂", true)),
+            "lim" => Ok(big_op("lim", true)),
+            "max" => Ok(big_op("max", true)),
+            "min" => Ok(big_op("min", true)),
+            "sup" => Ok(big_op("sup", true)),
+            "inf" => Ok(big_op("inf", true)),
+            "int" => Ok(big_op("∫", false)),
+            "iint" => Ok(big_op("∬", false)),
+            "iiint" => Ok(big_op("∭", false)),
+            "oint" => Ok(big_op("∮", false)),
+            "sin" | "cos" | "tan" | "cot" | "sec" | "csc" | "arcsin" | "arccos" | "arctan"
+            | "sinh" | "cosh" | "tanh" | "log" | "ln" | "lg" | "exp" | "det" | "gcd" | "deg"
+            | "dim" | "ker" | "arg" | "Pr" | "mod" | "bmod" => {
+                // `\sin x` は間を空け、`\sin(x)` や `\sin^2` は詰める
+                self.skip_whitespace();
+                Ok(match self.peek() {
+                    None | Some('(' | '^' | '_' | '}' | '&') => Node::Text(name),
+                    _ => Node::Text(format!("{name} ")),
+                })
+            }
+            "pmod" => {
+                let arg = self.parse_arg()?;
+                Ok(Node::Row(vec![
+                    Node::Text(" (mod ".to_string()),
+                    arg,
+                    Node::Text(")".to_string()),
+                ]))
+            }
+            // 空白
+            "," | ":" | ";" | " " | "quad" => Ok(Node::Text(" ".to_string())),
+            "qquad" => Ok(Node::Text("  ".to_string())),
+            "!" => Ok(Node::Text(String::new())),
+            "displaystyle" | "textstyle" | "limits" | "nolimits" => Ok(Node::Row(Vec::new())),
+            _ => {
+                if let Some(symbol) = relation(&name) {
+                    Ok(Node::Op(symbol.to_string()))
+                } else if let Some(symbol) = symbol(&name) {
+                    Ok(Node::Text(symbol.to_string()))
+                } else {
+                    Err(MathError::Unsupported(format!("\\{name}")))
+                }
+            }
+        }
+    }
+
+    /// `\left` / `\right` の後の区切り文字
+    fn parse_delimiter(&mut self) -> Result<String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('\\') => {
+                self.pos += 1;
+                match self.parse_command()? {
+                    Node::Text(text) | Node::Op(text) => Ok(text),
+                    _ => Err(MathError::Syntax("invalid delimiter".to_string())),
+                }
+            }
+            Some('.') => {
+                self.pos += 1;
+                Ok(String::new())
+            }
+            Some(c) => {
+                self.pos += 1;
+                Ok(c.to_string())
+            }
+            None => Err(MathError::Syntax("missing delimiter".to_string())),
+        }
+    }
+
+    /// `a & b \\ c & d \end` の行と列を読む
+    fn parse_matrix_rows(&mut self) -> Result<Vec<Vec<Node>>> {
+        let mut rows = Vec::new();
+        let mut row = Vec::new();
+
+        loop {
+            row.push(self.parse_row()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some('&') => self.pos += 1,
+                Some('\\') if self.starts_with("\\\\") => {
+                    self.pos += 2;
+                    rows.push(std::mem::take(&mut row));
+                }
+                Some('\\') if self.at_command("\\end") => {
+                    // 末尾の `\\` による空行は捨てる
+                    if !(row.len() == 1 && row[0] == Node::Row(Vec::new())) {
+                        rows.push(row);
+                    }
+                    return Ok(rows);
+                }
+                _ => return Err(MathError::Syntax("missing \\end".to_string())),
+            }
+        }
+    }
+}
+
+fn big_op(symbol: &'static str, limits: bool) -> Node {
+    Node::BigOp {
+        symbol,
+        limits,
+        sub: None,
+        sup: None,
+    }
+}
+
+/// 前後に空白を入れる関係記号・二項演算子
+fn relation(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "leq" | "le" => "≤",
+        "geq" | "ge" => "≥",
+        "neq" | "ne" => "≠",
+        "approx" => "≈",
+        "equiv" => "≡",
+        "sim" => "∼",
+        "simeq" => "≃",
+        "cong" => "≅",
+        "propto" => "∝",
+        "ll" => "≪",
+        "gg" => "≫",
+        "to" | "rightarrow" => "→",
+        "leftarrow" | "gets" => "←",
+        "Rightarrow" => "⇒",
+        "Leftarrow" => "⇐",
+        "leftrightarrow" => "↔",
+        "Leftrightarrow" | "iff" => "⇔",
+        "implies" => "⟹",
+        "mapsto" => "↦",
+        "in" => "∈",
+        "notin" => "∉",
+        "ni" => "∋",
+        "subset" => "⊂",
+        "subseteq" => "⊆",
+        "supset" => "⊃",
+        "supseteq" => "⊇",
+        "cup" => "∪",
+        "cap" => "∩",
+        "setminus" => "∖",
+        "pm" => "±",
+        "mp" => "∓",
+        "times" => "×",
+        "div" => "÷",
+        "cdot" => "⋅",
+        "ast" => "∗",
+        "star" => "⋆",
+        "circ" => "∘",
+        "oplus" => "⊕",
+        "otimes" => "⊗",
+        "land" | "wedge" => "∧",
+        "lor" | "vee" => "∨",
+        "perp" => "⊥",
+        "parallel" => "∥",
+        "mid" => "∣",
+        _ => return None,
+    })
+}
+
+/// ギリシャ文字やその他の記号
+fn symbol(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "alpha" => "α",
+        "beta" => "β",
+        "gamma" => "γ",
+        "delta" => "δ",
+        "epsilon" => "ϵ",
+        "varepsilon" => "ε",
+        "zeta" => "ζ",
+        "eta" => "η",
+        "theta" => "θ",
+        "vartheta" => "ϑ",
+        "iota" => "ι",
+        "kappa" => "κ",
+        "lambda" => "λ",
+        "mu" => "μ",
+        "nu" => "ν",
+        "xi" => "ξ",
+        "pi" => "π",
+        "varpi" => "ϖ",
+        "rho" => "ρ",
+        "varrho" => "ϱ",
+        "sigma" => "σ",
+        "varsigma" => "ς",
+        "tau" => "τ",
+        "upsilon" => "υ",
+        "phi" => "ϕ",
+        "varphi" => "φ",
+        "chi" => "χ",
+        "psi" => "ψ",
+        "omega" => "ω",
+        "Gamma" => "Γ",
+        "Delta" => "Δ",
+        "Theta" => "Θ",
+        "Lambda" => "Λ",
+        "Xi" => "Ξ",
+        "Pi" => "Π",
+        "Sigma" => "Σ",
+        "Upsilon" => "Υ",
+        "Phi" => "Φ",
+        "Psi" => "Ψ",
+        "Omega" => "Ω",
+        "infty" => "∞",
+        "partial" => "∂",
+        "nabla" => "∇",
+        "forall" => "∀",
+        "exists" => "∃",
+        "nexists" => "∄",
+        "emptyset" | "varnothing" => "∅",
+        "neg" | "lnot" => "¬",
+        "angle" => "∠",
+        "triangle" => "△",
+        "prime" => "′",
+        "degree" => "°",
+        "ell" => "ℓ",
+        "hbar" => "ℏ",
+        "Re" => "ℜ",
+        "Im" => "ℑ",
+        "aleph" => "ℵ",
+        "ldots" | "dots" => "…",
+        "cdots" => "⋯",
+        "vdots" => "⋮",
+        "ddots" => "⋱",
+        "langle" => "⟨",
+        "rangle" => "⟩",
+        "lfloor" => "⌊",
+        "rfloor" => "⌋",
+        "lceil" => "⌈",
+        "rceil" => "⌉",
+        "lvert" | "rvert" | "vert" => "|",
+        "lVert" | "rVert" | "Vert" | "|" => "‖",
+        "{" | "lbrace" => "{",
+        "}" | "rbrace" => "}",
+        "#" => "#",
+        "%" => "%",
+        "&" => "&",
+        "$" => "$",
+        "_" => "_",
+        _ => return None,
+    })
+}
+
+/// `\mathbb` の白抜き文字
+fn double_struck(c: char) -> char {
+    match c {
+        'C' => 'ℂ',
+        'H' => 'ℍ',
+        'N' => 'ℕ',
+        'P' => 'ℙ',
+        'Q' => 'ℚ',
+        'R' => 'ℝ',
+        'Z' => 'ℤ',
+        'A'..='Z' => char::from_u32(0x1D538 + (c as u32 - 'A' as u32)).unwrap_or(c),
+        '0'..='9' => char::from_u32(0x1D7D8 + (c as u32 - '0' as u32)).unwrap_or(c),
+        _ => c,
+    }
+}
+
+pub(crate) fn to_superscript(c: char) -> Option<char> {
+    Some(match c {
+        '0' => '⁰',
+        '1' => '¹',
+        '2' => '²',
+        '3' => '³',
+        '4' => '⁴',
+        '5' => '⁵',
+        '6' => '⁶',
+        '7' => '⁷',
+        '8' => '⁸',
+        '9' => '⁹',
+        '+' => '⁺',
+        '−' | '-' => '⁻',
+        '=' => '⁼',
+        '(' => '⁽',
+        ')' => '⁾',
+        'a' => 'ᵃ',
+        'b' => 'ᵇ',
+        'c' => 'ᶜ',
+        'd' => 'ᵈ',
+        'e' => 'ᵉ',
+        'f' => 'ᶠ',
+        'g' => 'ᵍ',
+        'h' => 'ʰ',
+        'i' => 'ⁱ',
+        'j' => 'ʲ',
+        'k' => 'ᵏ',
+        'l' => 'ˡ',
+        'm' => 'ᵐ',
+        'n' => 'ⁿ',
+        'o' => 'ᵒ',
+        'p' => 'ᵖ',
+        'r' => 'ʳ',
+        's' => 'ˢ',
+        't' => 'ᵗ',
+        'u' => 'ᵘ',
+        'v' => 'ᵛ',
+        'w' => 'ʷ',
+        'x' => 'ˣ',
+        'y' => 'ʸ',
+        'z' => 'ᶻ',
+        'A' => 'ᴬ',
+        'B' => 'ᴮ',
+        'D' => 'ᴰ',
+        'E' => 'ᴱ',
+        'G' => 'ᴳ',
+        'H' => 'ᴴ',
+        'I' => 'ᴵ',
+        'J' => 'ᴶ',
+        'K' => 'ᴷ',
+        'L' => 'ᴸ',
+        'M' => 'ᴹ',
+        'N' => 'ᴺ',
+        'O' => 'ᴼ',
+        'P' => 'ᴾ',
+        'R' => 'ᴿ',
+        'T' => 'ᵀ',
+        'U' => 'ᵁ',
+        'V' => 'ⱽ',
+        'W' => 'ᵂ',
+        'α' => 'ᵅ',
+        'β' => 'ᵝ',
+        'γ' => 'ᵞ',
+        'δ' => 'ᵟ',
+        'θ' => 'ᶿ',
+        'φ' | 'ϕ' => 'ᵠ',
+        'χ' => 'ᵡ',
+        '′' => '′',
+        '∗' => '*',
+        // 既に上付きの文字はそのまま
+        '⁰' | '¹' | '²' | '³' | '⁴' | '⁵' | '⁶' | '⁷' | '⁸' | '⁹' => c,
+        _ => return None,
+    })
+}
+
+pub(crate) fn to_subscript(c: char) -> Option<char> {
+    Some(match c {
+        '0' => '₀',
+        '1' => '₁',
+        '2' => '₂',
+        '3' => '₃',
+        '4' => '₄',
+        '5' => '₅',
+        '6' => '₆',
+        '7' => '₇',
+        '8' => '₈',
+        '9' => '₉',
+        '+' => '₊',
+        '−' | '-' => '₋',
+        '=' => '₌',
+        '(' => '₍',
+        ')' => '₎',
+        'a' => 'ₐ',
+        'e' => 'ₑ',
+        'h' => 'ₕ',
+        'i' => 'ᵢ',
+        'j' => 'ⱼ',
+        'k' => 'ₖ',
+        'l' => 'ₗ',
+        'm' => 'ₘ',
+        'n' => 'ₙ',
+        'o' => 'ₒ',
+        'p' => 'ₚ',
+        'r' => 'ᵣ',
+        's' => 'ₛ',
+        't' => 'ₜ',
+        'u' => 'ᵤ',
+        'v' => 'ᵥ',
+        'x' => 'ₓ',
+        'β' => 'ᵦ',
+        'γ' => 'ᵧ',
+        'ρ' => 'ᵨ',
+        'φ' | 'ϕ' => 'ᵩ',
+        'χ' => 'ᵪ',
+        _ => return None,
+    })
+}
+
+/// 上付き文字を元の文字に戻す（`to_superscript` の逆）
+pub(crate) fn from_superscript(c: char) -> Option<char> {
+    script_bases().find(|&base| to_superscript(base) == Some(c))
+}
+
+/// 下付き文字を元の文字に戻す（`to_subscript` の逆）
+pub(crate) fn from_subscript(c: char) -> Option<char> {
+    script_bases().find(|&base| to_subscript(base) == Some(c))
+}
+
+/// 上付き・下付きにできる文字（ASCII を先に探す）
+fn script_bases() -> impl Iterator<Item = char> {
+    ('!'..='~').chain("αβγδθφϕχρ".chars())
+}
+
+/// 組版スタイル
+#[derive(Debug, Clone, Copy)]
+struct Style {
+    /// ディスプレイ数式（分数を縦に積む）
+    display: bool,
+    /// 添字の中（演算子の前後に空白を入れない）
+    script: bool,
+}
+
+impl Style {
+    const DISPLAY: Style = Style {
+        display: true,
+        script: false,
+    };
+    const INLINE: Style = Style {
+        display: false,
+        script: false,
+    };
+
+    fn script(self) -> Style {
+        Style {
+            script: true,
+            ..self
+        }
+    }
+}
+
+/// 組版済みの矩形。`baseline` は他の要素と揃える行
+#[derive(Debug, Clone)]
+struct MathBox {
+    rows: Vec<String>,
+    baseline: usize,
+    width: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+impl MathBox {
+    fn text(text: impl Into<String>) -> Self {
+        let text = text.into();
+        Self {
+            width: text.width(),
+            rows: vec![text],
+            baseline: 0,
+        }
+    }
+
+    fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    fn single_row(&self) -> Option<&str> {
+        match self.rows.as_slice() {
+            [row] => Some(row),
+            _ => None,
+        }
+    }
+
+    /// 幅 `width` に合わせて行を寄せる
+    fn padded_row(&self, index: usize, width: usize, align: Align) -> String {
+        let row = self.rows.get(index).map(String::as_str).unwrap_or("");
+        let gap = width.saturating_sub(row.width());
+        let left = match align {
+            Align::Left => 0,
+            Align::Center => gap / 2,
+            Align::Right => gap,
+        };
+        format!("{}{row}{}", " ".repeat(left), " ".repeat(gap - left))
+    }
+
+    /// ベースラインを揃えて横に並べる
+    fn beside(boxes: Vec<MathBox>) -> MathBox {
+        let above = boxes.iter().map(|b| b.baseline).max().unwrap_or(0);
+        let below = boxes
+            .iter()
+            .map(|b| b.height() - b.baseline - 1)
+            .max()
+            .unwrap_or(0);
+        let height = above + below + 1;
+
+        let mut rows = vec![String::new(); height];
+        for b in &boxes {
+            let offset = above - b.baseline;
+            for (r, row) in rows.iter_mut().enumerate() {
+                let index = r.wrapping_sub(offset);
+                if r >= offset && index < b.height() {
+                    row.push_str(&b.padded_row(index, b.width, Align::Left));
+                } else {
+                    row.push_str(&" ".repeat(b.width));
+                }
+            }
+        }
+
+        MathBox {
+            rows,
+            baseline: above,
+            width: boxes.iter().map(|b| b.width).sum(),
+        }
+    }
+
+    /// 縦に積む。ベースラインは `baseline_box` 番目の箱のベースライン
+    fn stack(boxes: Vec<MathBox>, baseline_box: usize, align: Align) -> MathBox {
+        let width = boxes.iter().map(|b| b.width).max().unwrap_or(0);
+        let mut rows = Vec::new();
+        let mut baseline = 0;
+
+        for (i, b) in boxes.iter().enumerate() {
+            if i == baseline_box {
+                baseline = rows.len() + b.baseline;
+            }
+            for r in 0..b.height() {
+                rows.push(b.padded_row(r, width, align));
+            }
+        }
+
+        MathBox {
+            rows,
+            baseline,
+            width,
+        }
+    }
+
+    /// 何も描かない高さ `height` の箱
+    fn spacer(height: usize) -> MathBox {
+        MathBox {
+            rows: vec![String::new(); height.max(1)],
+            baseline: 0,
+            width: 0,
+        }
+    }
+}
+
+fn layout(node: &Node, style: Style) -> Result<MathBox> {
+    match node {
+        Node::Text(text) => Ok(MathBox::text(text.clone())),
+        Node::Op(op) => Ok(if style.script {
+            MathBox::text(op.clone())
+        } else {
+            MathBox::text(format!(" {op} "))
+        }),
+        Node::Row(nodes) => {
+            let mut boxes = Vec::with_capacity(nodes.len());
+            for (i, node) in nodes.iter().enumerate() {
+                // 先頭や演算子の直後の演算子は単項（`-x`）なので空白を入れない
+                let unary =
+                    matches!(node, Node::Op(_)) && (i == 0 || matches!(nodes[i - 1], Node::Op(_)));
+                boxes.push(match node {
+                    Node::Op(op) if unary => MathBox::text(op.clone()),
+                    _ => layout(node, style)?,
+                });
+            }
+            if boxes.is_empty() {
+                return Ok(MathBox::text(""));
+            }
+            Ok(MathBox::beside(boxes))
+        }
+        Node::Scripts { base, sub, sup } => {
+            let base = layout(base, style)?;
+            attach_scripts(base, sub.as_deref(), sup.as_deref(), style)
+        }
+        Node::Frac(num, den) => {
+            let num = layout(num, style)?;
+            let den = layout(den, style)?;
+
+            if !style.display {
+                // インラインでは `a/b` と書く
+                return match (num.single_row(), den.single_row()) {
+                    (Some(n), Some(d)) => Ok(MathBox::text(format!(
+                        "{}/{}",
+                        parenthesize(n),
+                        parenthesize(d)
+                    ))),
+                    _ => Ok(MathBox::stack(vec![num, den], 0, Align::Center)),
+                };
+            }
+
+            let width = num.width.max(den.width) + 2;
+            let bar = MathBox::text("─".repeat(width));
+            let mut frac = MathBox::stack(vec![num, bar, den], 1, Align::Center);
+            frac.baseline = frac.rows.iter().position(|r| r.contains('─')).unwrap_or(0);
+            Ok(frac)
+        }
+        Node::Sqrt { index, body } => {
+            let body = layout(body, style)?;
+            let prefix = match index {
+                Some(index) => {
+                    let index = layout(index, style.script())?;
+                    let text = index.single_row().unwrap_or_default().trim().to_string();
+                    text.chars()
+                        .map(to_superscript)
+                        .collect::<Option<String>>()
+                        .ok_or_else(|| MathError::Unsupported("\\sqrt[...]".to_string()))?
+                }
+                None => String::new(),
+            };
+
+            if let Some(row) = body.single_row() {
+                if !style.display {
+                    let row = row.trim();
+                    let body = if row.chars().count() == 1 {
+                        row.to_string()
+                    } else {
+                        format!("({row})")
+                    };
+                    return Ok(MathBox::text(format!("{prefix}√{body}")));
+                }
+            }
+
+            // 本体の上に横線を引き、左に根号を置く
+            let prefix_width = prefix.width();
+            let mut rows = vec![format!(
+                "{}{}",
+                " ".repeat(prefix_width + 1),
+                "_".repeat(body.width)
+            )];
+            for r in 0..body.height() {
+                let sign = if r + 1 == body.height() {
+                    format!("{prefix}√")
+                } else {
+                    format!("{}│", " ".repeat(prefix_width))
+                };
+                rows.push(format!(
+                    "{sign}{}",
+                    body.padded_row(r, body.width, Align::Left)
+                ));
+            }
+            Ok(MathBox {
+                rows,
+                baseline: body.baseline + 1,
+                width: prefix_width + 1 + body.width,
+            })
+        }
+        Node::BigOp {
+            symbol,
+            limits,
+            sub,
+            sup,
+        } => {
+            let is_integral = !limits;
+            let symbol_box = if style.display && is_integral && (sub.is_some() || sup.is_some()) {
+                // ディスプレイの積分記号は3行で描く
+                let extended = match *symbol {
+                    "∫" => Some(("⌠", "⎮", "⌡")),
+                    _ => None,
+                };
+                match extended {
+                    Some((top, middle, bottom)) => MathBox {
+                        rows: vec![top.to_string(), middle.to_string(), bottom.to_string()],
+                        baseline: 1,
+                        width: 1,
+                    },
+                    None => MathBox::text(*symbol),
+                }
+            } else {
+                MathBox::text(*symbol)
+            };
+
+            let spaced = |b: MathBox| {
+                if style.script {
+                    b
+                } else {
+                    MathBox::beside(vec![b, MathBox::text(" ")])
+                }
+            };
+
+            if *limits && style.display && (sub.is_some() || sup.is_some()) {
+                let mut boxes = Vec::new();
+                if let Some(sup) = sup {
+                    boxes.push(layout(sup, style.script())?);
+                }
+                let symbol_index = boxes.len();
+                boxes.push(symbol_box);
+                if let Some(sub) = sub {
+                    boxes.push(layout(sub, style.script())?);
+                }
+                return Ok(spaced(MathBox::stack(boxes, symbol_index, Align::Center)));
+            }
+
+            let scripted = attach_scripts(symbol_box, sub.as_deref(), sup.as_deref(), style)?;
+            Ok(spaced(scripted))
+        }
+        Node::Delimited { left, right, body } => {
+            let body = layout(body, style)?;
+            Ok(delimit(body, left, right))
+        }
+        Node::Matrix { kind, rows } => {
+            let (left, right) = kind.delimiters();
+            let grid = layout_matrix(*kind, rows, style)?;
+            Ok(delimit(grid, left, right))
+        }
+    }
+}
+
+/// インライン分数の分子・分母を必要なら括弧で囲む
+fn parenthesize(text: &str) -> String {
+    let text = text.trim();
+    if text.chars().all(|c| c.is_alphanumeric() || c == '.') {
+        text.to_string()
+    } else {
+        format!("({text})")
+    }
+}
+
+/// 上下付き添字を付ける。1行に収まる添字は Unicode の上付き・下付き文字を使う
+fn attach_scripts(
+    base: MathBox,
+    sub: Option<&Node>,
+    sup: Option<&Node>,
+    style: Style,
+) -> Result<MathBox> {
+    let sub = sub.map(|n| layout(n, style.script())).transpose()?;
+    let sup = sup.map(|n| layout(n, style.script())).transpose()?;
+
+    let compact = |b: &Option<MathBox>, convert: fn(char) -> Option<char>| match b {
+        None => Some(String::new()),
+        Some(b) => b.single_row()?.trim().chars().map(convert).collect(),
+    };
+    if base.height() == 1 {
+        if let (Some(sub_text), Some(sup_text)) =
+            (compact(&sub, to_subscript), compact(&sup, to_superscript))
+        {
+            let base_text = base.single_row().unwrap_or_default();
+            return Ok(MathBox::text(format!("{base_text}{sub_text}{sup_text}")));
+        }
+
+        // インラインでは `x_(ij)^(n+1)` と書く
+        if !style.display {
+            let linear = |b: &Option<MathBox>, mark: &str| -> Option<String> {
+                match b {
+                    None => Some(String::new()),
+                    Some(b) => {
+                        let text = b.single_row()?.trim();
+                        Some(if text.chars().count() == 1 {
+                            format!("{mark}{text}")
+                        } else {
+                            format!("{mark}({text})")
+                        })
+                    }
+                }
+            };
+            if let (Some(sub_text), Some(sup_text)) = (linear(&sub, "_"), linear(&sup, "^")) {
+                let base_text = base.single_row().unwrap_or_default();
+                return Ok(MathBox::text(format!("{base_text}{sub_text}{sup_text}")));
+            }
+        }
+    }
+
+    let base_height = base.height();
+    let has_sup = sup.is_some();
+    let has_sub = sub.is_some();
+    let mut column = Vec::new();
+
+    let mut scripts = if base_height == 1 {
+        // 上付きは基底の上、下付きは基底の下の行に置く
+        if let Some(sup) = sup {
+            column.push(sup);
+        }
+        column.push(MathBox::spacer(1));
+        if let Some(sub) = sub {
+            column.push(sub);
+        }
+        MathBox::stack(column, usize::from(has_sup), Align::Left)
+    } else {
+        // 背の高い基底（積分記号や括弧）では上付きを最上行、下付きを最下行に揃える
+        let sup_height = sup.as_ref().map_or(0, MathBox::height);
+        let gap = base_height - usize::from(has_sup) - usize::from(has_sub);
+        if let Some(sup) = sup {
+            column.push(sup);
+        }
+        if gap > 0 {
+            column.push(MathBox::spacer(gap));
+        }
+        if let Some(sub) = sub {
+            column.push(sub);
+        }
+        let mut scripts = MathBox::stack(column, 0, Align::Left);
+        scripts.baseline = sup_height.saturating_sub(1);
+        scripts
+    };
+    scripts.baseline += base.baseline;
+
+    Ok(MathBox::beside(vec![base, scripts]))
+}
+
+fn layout_matrix(kind: MatrixKind, rows: &[Vec<Node>], style: Style) -> Result<MathBox> {
+    let cells: Vec<Vec<MathBox>> = rows
+        .iter()
+        .map(|row| row.iter().map(|cell| layout(cell, style)).collect())
+        .collect::<Result<_>>()?;
+
+    let columns = cells.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|c| {
+            cells
+                .iter()
+                .filter_map(|row| row.get(c))
+                .map(|cell| cell.width)
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let (gap, align_of): (&str, fn(usize) -> Align) = match kind {
+        MatrixKind::Cases => ("  ", |_| Align::Left),
+        // `a &= b` は `&` の左を右寄せ、右を左寄せにする
+        MatrixKind::Aligned => ("", |c| {
+            if c % 2 == 0 {
+                Align::Right
+            } else {
+                Align::Left
+            }
+        }),
+        _ => ("  ", |_| Align::Center),
+    };
+
+    let mut row_boxes = Vec::new();
+    for row in &cells {
+        let mut boxes = Vec::new();
+        for (c, width) in widths.iter().enumerate() {
+            if c > 0 {
+                boxes.push(MathBox::text(gap));
+            }
+            let cell = row.get(c).cloned().unwrap_or_else(|| MathBox::text(""));
+            let align = align_of(c);
+            boxes.push(MathBox {
+                rows: (0..cell.height())
+                    .map(|r| cell.padded_row(r, *width, align))
+                    .collect(),
+                baseline: cell.baseline,
+                width: *width,
+            });
+        }
+        row_boxes.push(MathBox::beside(boxes));
+    }
+
+    let mut grid = MathBox::stack(row_boxes, 0, Align::Left);
+    grid.baseline = grid.height().saturating_sub(1) / 2;
+    Ok(grid)
+}
+
+/// 本体の高さに合わせて区切り文字を伸ばす
+fn delimit(body: MathBox, left: &str, right: &str) -> MathBox {
+    let height = body.height();
+    let baseline = body.baseline;
+    let column = |delimiter: &str, is_left: bool| -> Option<MathBox> {
+        if delimiter.is_empty() {
+            return None;
+        }
+        let rows = if height == 1 {
+            vec![delimiter.to_string()]
+        } else {
+            let pieces = match (delimiter, is_left) {
+                ("(", _) => Some(("⎛", "⎜", "⎝", "⎜")),
+                (")", _) => Some(("⎞", "⎟", "⎠", "⎟")),
+                ("[", _) => Some(("⎡", "⎢", "⎣", "⎢")),
+                ("]", _) => Some(("⎤", "⎥", "⎦", "⎥")),
+                ("{", _) => Some(("⎧", "⎪", "⎩", "⎨")),
+                ("}", _) => Some(("⎫", "⎪", "⎭", "⎬")),
+                ("|", _) => Some(("│", "│", "│", "│")),
+                ("‖", _) => Some(("‖", "‖", "‖", "‖")),
+                _ => None,
+            };
+            match pieces {
+                Some((top, middle, bottom, center)) => (0..height)
+                    .map(|r| {
+                        if r == 0 {
+                            top
+                        } else if r + 1 == height {
+                            bottom
+                        } else if r == (height - 1) / 2 && height > 2 {
+                            center
+                        } else {
+                            middle
+                        }
+                        .to_string()
+                    })
+                    .collect(),
+                // 伸ばせない区切り文字はベースラインにだけ置く
+                None => (0..height)
+                    .map(|r| {
+                        if r == baseline {
+                            delimiter.to_string()
+                        } else {
+                            " ".repeat(delimiter.width())
+                        }
+                    })
+                    .collect(),
+            }
+        };
+        Some(MathBox {
+            rows,
+            baseline,
+            width: delimiter.width(),
+        })
+    };
+
+    let mut boxes = Vec::new();
+    boxes.extend(column(left, true));
+    boxes.push(body);
+    boxes.extend(column(right, false));
+    MathBox::beside(boxes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inline_symbols_and_scripts() {
+        assert_eq!(render_inline(r"\alpha + \beta").as_deref(), Some("α + β"));
+        assert_eq!(render_inline("x^2 + y_i").as_deref(), Some("x² + yᵢ"));
+        assert_eq!(
+            render_inline(r"e^{i\pi} = -1").as_deref(),
+            Some("e^(iπ) = −1")
+        );
+        assert_eq!(
+            render_inline(r"\frac{a+b}{2}").as_deref(),
+            Some("(a + b)/2")
+        );
+        assert_eq!(render_inline(r"x \in \mathbb{R}").as_deref(), Some("x ∈ ℝ"));
+        assert_eq!(render_inline(r"\sqrt{2}").as_deref(), Some("√2"));
+    }
+
+    #[test]
+    fn test_display_fraction_is_stacked() {
+        let rows = render_display(r"\frac{a+b}{c}").unwrap();
+        assert_eq!(rows, [" a + b", "───────", "   c"]);
+    }
+
+    #[test]
+    fn test_display_sum_with_limits() {
+        let rows = render_display(r"\sum_{i=1}^{n} i").unwrap();
+        assert_eq!(rows, [" n", " ∑  i", "i=1"]);
+    }
+
+    #[test]
+    fn test_display_matrix() {
+        let rows = render_display(r"\begin{pmatrix} 1 & 0 \\ 0 & 1 \end{pmatrix}").unwrap();
+        assert_eq!(rows, ["⎛1  0⎞", "⎝0  1⎠"]);
+    }
+
+    #[test]
+    fn test_unsupported_command() {
+        assert_eq!(
+            render_display(r"\foo{x}"),
+            Err(MathError::Unsupported(r"\foo".to_string()))
+        );
+        assert_eq!(render_inline(r"\foo"), None);
+    }
+}
@@ -1,10 +1,24 @@
+pub mod block;
+pub mod code_info;
+pub mod front_matter;
 pub mod highlighter;
+pub mod html;
+pub mod inline;
+pub mod link;
+pub mod math;
 pub mod parser;
+pub mod source;
 pub mod toc;
 
 #[cfg(test)]
 mod parser_test;
 
+pub use block::{Block, ContainerKind, Nesting};
+pub use code_info::CodeInfo;
+pub use front_matter::{FrontMatterFormat, Metadata, MetadataValue};
 pub use highlighter::CodeHighlighter;
-pub use parser::{Alignment, MarkdownDocument, ParsedLine};
-pub use toc::TocEntry;
+pub use inline::{plain_text, InlineSpan, InlineStyle, Link};
+pub use link::{DocLink, LinkTarget};
+pub use parser::{Alignment, FoldKey, ListMarker, MarkdownDocument, ParsedLine, STDIN_PATH};
+pub use source::SourceRange;
+pub use toc::{slugify, TocEntry};
@@ -1,15 +1,39 @@
+use super::block::{flatten, Block, ContainerKind, Flattened, Nesting};
+use super::code_info::CodeInfo;
+use super::front_matter::{self, Metadata};
 use super::highlighter::{CodeHighlighter, StyledSpan};
-use super::toc::TocEntry;
+use super::html::{self, HtmlToken};
+use super::inline::{
+    is_blank, plain_text, push_span, strip_prefix_spans, trim_spans, InlineSpan, InlineStyle, Link,
+};
+use super::link::{collect_links, renumber_links, DocLink};
+use super::math;
+use super::source::{LineIndex, SourceRange};
+use super::toc::{SlugCounter, TocEntry};
 use crate::error::MdError;
 use pulldown_cmark::{Event, HeadingLevel, Options, Parser, Tag, TagEnd};
-use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// 再読み込みしても同じ所を指す折りたたみの位置
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FoldKey {
+    /// 見出しのセクション（見出しのアンカー）
+    Section(String),
+    /// セクション（最初の見出しより前なら `None`）の中で `nth` 番目のコードブロックかテーブル
+    Block { section: Option<String>, nth: usize },
+}
 
 #[derive(Debug, Clone)]
 pub enum ParsedLine {
     Heading {
         level: usize,
-        text: String,
+        text: Vec<InlineSpan>,
         line_num: usize,
+        /// GitHub互換のアンカー名（`#` なし）
+        anchor: String,
     },
     Code {
         lang: Option<String>,
@@ -17,33 +41,82 @@ pub enum ParsedLine {
         highlighted: Vec<Vec<StyledSpan>>,
     },
     Text {
-        content: String,
+        content: Vec<InlineSpan>,
     },
     ListItem {
         indent: usize,
-        content: String,
+        content: Vec<InlineSpan>,
         checked: Option<bool>, // None = 通常のリスト, Some(true) = チェック済み, Some(false) = 未チェック
+        marker: ListMarker,
     },
     Table {
-        headers: Vec<String>,
-        rows: Vec<Vec<String>>,
+        headers: Vec<Vec<InlineSpan>>,
+        rows: Vec<Vec<Vec<InlineSpan>>>,
         alignments: Vec<Alignment>,
     },
     BlockQuote {
-        content: String,
+        content: Vec<InlineSpan>,
     },
     Alert {
         alert_type: AlertType,
-        content: String,
+        content: Vec<InlineSpan>,
     },
     Image {
         alt_text: String,
         url: String,
     },
+    /// ディスプレイ数式（`$$...$$`）
+    Math {
+        source: String,
+        /// 組版できない場合に表示するソースのハイライト
+        highlighted: Vec<Vec<StyledSpan>>,
+    },
+    /// 文末の脚注セクションに並ぶ脚注の本文
+    FootnoteDefinition {
+        number: usize,
+        label: String,
+        content: Vec<InlineSpan>,
+    },
+    /// `<details>` の `<summary>`（中身は `ContainerKind::Details` の子になる）
+    Details {
+        /// 文書内で何番目の `<details>` か
+        index: usize,
+        summary: Vec<InlineSpan>,
+        /// 開いているか（初期値は `open` 属性）
+        open: bool,
+    },
     HorizontalRule,
     Empty,
 }
 
+/// リスト項目のマーカー
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListMarker {
+    /// `-` `*` `+` の箇条書き
+    Bullet(char),
+    /// 番号付きリスト（`7.` や `7)`）。`number` は開始番号を反映した実際の番号
+    Ordered { number: u64, delimiter: char },
+}
+
+impl ListMarker {
+    /// 項目の先頭（`range` の位置）にあるマーカーを読む
+    fn from_source(source: &str, number: Option<u64>) -> Self {
+        let text = source.trim_start();
+        match number {
+            Some(number) => {
+                let delimiter = text
+                    .trim_start_matches(|c: char| c.is_ascii_digit())
+                    .chars()
+                    .next()
+                    .filter(|c| *c == ')')
+                    .unwrap_or('.');
+                ListMarker::Ordered { number, delimiter }
+            }
+            None => ListMarker::Bullet(text.chars().next().unwrap_or('-')),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum AlertType {
     Note,
@@ -53,6 +126,19 @@ pub enum AlertType {
     Caution,
 }
 
+impl AlertType {
+    /// 小文字の種類名（`note` など）
+    pub fn name(self) -> &'static str {
+        match self {
+            AlertType::Note => "note",
+            AlertType::Tip => "tip",
+            AlertType::Important => "important",
+            AlertType::Warning => "warning",
+            AlertType::Caution => "caution",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Alignment {
     None,
@@ -61,11 +147,353 @@ pub enum Alignment {
     Right,
 }
 
+/// 強調・リンクなど、入れ子になったインライン装飾の状態
+#[derive(Default)]
+struct InlineState {
+    emphasis: usize,
+    strong: usize,
+    strikethrough: usize,
+    link: Option<Link>,
+    link_count: usize,
+    /// HTML の `<code>` / `<kbd>` / `<sup>` / `<sub>` の入れ子の深さ
+    code: usize,
+    kbd: usize,
+    sup: usize,
+    sub: usize,
+}
+
+impl InlineState {
+    fn span(&self, text: &str, code: bool) -> InlineSpan {
+        let text = if self.sup > 0 {
+            script_text(text, math::to_superscript, '^')
+        } else if self.sub > 0 {
+            script_text(text, math::to_subscript, '_')
+        } else {
+            text.to_string()
+        };
+        InlineSpan {
+            text,
+            style: InlineStyle {
+                code: code || self.code > 0,
+                emphasis: self.emphasis > 0,
+                strong: self.strong > 0,
+                strikethrough: self.strikethrough > 0,
+                math: false,
+                kbd: self.kbd > 0,
+                html: false,
+            },
+            link: self.link.clone(),
+            footnote: None,
+        }
+    }
+
+    /// 解釈できない HTML タグ
+    fn html_tag(&self, raw: &str) -> InlineSpan {
+        InlineSpan {
+            text: raw.to_string(),
+            style: InlineStyle {
+                html: true,
+                ..InlineStyle::default()
+            },
+            link: None,
+            footnote: None,
+        }
+    }
+
+    /// 葉ブロックの終わりで装飾をすべて閉じる（リンクの通し番号は残す）
+    fn end_block(&mut self) {
+        *self = Self {
+            link_count: self.link_count,
+            ..Self::default()
+        };
+    }
+
+    /// HTML のインライン要素の開始・終了を反映する。対応する要素なら true
+    fn apply_html(&mut self, name: &str, open: bool, href: Option<&str>) -> bool {
+        let counter = match name {
+            "b" | "strong" => &mut self.strong,
+            "i" | "em" => &mut self.emphasis,
+            "s" | "del" | "strike" => &mut self.strikethrough,
+            "code" | "tt" => &mut self.code,
+            "kbd" => &mut self.kbd,
+            "sup" => &mut self.sup,
+            "sub" => &mut self.sub,
+            "a" => {
+                if !open {
+                    self.link = None;
+                } else if let Some(url) = href {
+                    self.link = Some(Link {
+                        index: self.link_count,
+                        url: url.to_string(),
+                    });
+                    self.link_count += 1;
+                }
+                return true;
+            }
+            _ => return false,
+        };
+        if open {
+            *counter += 1;
+        } else {
+            *counter = counter.saturating_sub(1);
+        }
+        true
+    }
+}
+
+/// 組み立て中のコンテナ
+struct OpenContainer {
+    kind: ContainerKind,
+    children: Vec<Block>,
+    /// 開始位置のバイト位置
+    start: usize,
+}
+
+/// イベント列からブロックの木を組み立てる
+struct TreeBuilder<'a> {
+    index: &'a LineIndex,
+    root: Vec<Block>,
+    stack: Vec<OpenContainer>,
+    /// 確定前のテキストのソース範囲
+    text_range: Option<Range<usize>>,
+}
+
+impl<'a> TreeBuilder<'a> {
+    fn new(index: &'a LineIndex) -> Self {
+        Self {
+            index,
+            root: Vec::new(),
+            stack: Vec::new(),
+            text_range: None,
+        }
+    }
+
+    fn push(&mut self, block: Block) {
+        match self.stack.last_mut() {
+            Some(container) => container.children.push(block),
+            None => self.root.push(block),
+        }
+    }
+
+    fn push_line(&mut self, line: ParsedLine, range: Range<usize>) {
+        let source = self.index.range(range);
+        self.push(Block::leaf(line, source));
+    }
+
+    fn push_container(&mut self, kind: ContainerKind, children: Vec<Block>, range: Range<usize>) {
+        let source = self.index.range(range);
+        self.push(Block::Container {
+            kind,
+            children,
+            source,
+        });
+    }
+
+    fn open(&mut self, kind: ContainerKind, start: usize) {
+        self.stack.push(OpenContainer {
+            kind,
+            children: Vec::new(),
+            start,
+        });
+    }
+
+    /// HTML で開いたコンテナを閉じる
+    fn close_html(&mut self, end: usize) {
+        if let Some(container) = self.close() {
+            self.push_container(container.kind, container.children, container.start..end);
+        }
+    }
+
+    /// 内側に残っている HTML のコンテナ（`<details>` など）を閉じる
+    fn close_html_containers(&mut self, end: usize) {
+        while matches!(
+            self.innermost(),
+            Some(ContainerKind::Details { .. } | ContainerKind::Aligned(_))
+        ) {
+            self.close_html(end);
+        }
+    }
+
+    fn close(&mut self) -> Option<OpenContainer> {
+        self.stack.pop()
+    }
+
+    fn innermost(&self) -> Option<&ContainerKind> {
+        self.stack.last().map(|container| &container.kind)
+    }
+
+    /// テキストの範囲を `range` まで広げる
+    fn extend_text(&mut self, range: Range<usize>) {
+        self.text_range = Some(match self.text_range.take() {
+            Some(current) => current.start.min(range.start)..current.end.max(range.end),
+            None => range,
+        });
+    }
+
+    /// 段落（またはタイトなリスト項目）のテキストを確定する
+    fn flush_text(&mut self, text: &mut Vec<InlineSpan>) {
+        let range = self.text_range.take().unwrap_or_default();
+        let content = trim_spans(std::mem::take(text));
+        if !is_blank(&content) {
+            self.push_line(ParsedLine::Text { content }, range);
+        }
+    }
+
+    fn finish(mut self) -> Vec<Block> {
+        // 閉じられなかったコンテナは子の範囲をまとめる
+        while let Some(container) = self.close() {
+            let source = match (container.children.first(), container.children.last()) {
+                (Some(first), Some(last)) => first.source().join(last.source()),
+                _ => SourceRange::default(),
+            };
+            self.push(Block::Container {
+                kind: container.kind,
+                children: container.children,
+                source,
+            });
+        }
+        self.root
+    }
+}
+
+/// 見出しを目次とブロックの木に追加する
+fn push_heading(
+    tree: &mut TreeBuilder,
+    toc: &mut Vec<TocEntry>,
+    slugs: &mut SlugCounter,
+    level: usize,
+    text: Vec<InlineSpan>,
+    id: Option<String>,
+    range: Range<usize>,
+) {
+    let line_num = tree.index.line_of(range.start);
+    let title = plain_text(&text);
+    // `{#custom-id}` が指定されていればそれを優先する
+    let anchor = match id {
+        Some(id) => slugs.reserve(&id),
+        None => slugs.slug(&title),
+    };
+    toc.push(TocEntry {
+        level,
+        title,
+        line_number: line_num,
+        anchor: anchor.clone(),
+    });
+    tree.push_line(
+        ParsedLine::Heading {
+            level,
+            text,
+            line_num,
+            anchor,
+        },
+        range,
+    );
+}
+
+/// HTML ブロックの空白の並びを1つの空白にまとめる
+fn collapse_whitespace(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut in_space = false;
+    for c in text.chars() {
+        if c.is_whitespace() && c != '\u{a0}' {
+            if !in_space {
+                result.push(' ');
+            }
+            in_space = true;
+        } else {
+            result.push(c);
+            in_space = false;
+        }
+    }
+    result
+}
+
+/// `align` 属性の値
+fn parse_align(value: &str) -> Option<Alignment> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "center" | "middle" => Some(Alignment::Center),
+        "right" => Some(Alignment::Right),
+        "left" => Some(Alignment::Left),
+        _ => None,
+    }
+}
+
+/// `<sup>` / `<sub>` の中身を上付き・下付き文字にする（できない文字があれば `^(...)` の形にする）
+fn script_text(text: &str, convert: fn(char) -> Option<char>, marker: char) -> String {
+    let converted: Option<String> = text
+        .chars()
+        .map(|c| if c == ' ' { Some(c) } else { convert(c) })
+        .collect();
+    converted.unwrap_or_else(|| format!("{marker}({text})"))
+}
+
+/// 引用の先頭が `[!NOTE]` などならアラートとして扱う
+fn detect_alert(children: &mut Vec<Block>) -> Option<AlertType> {
+    let Some(Block::Leaf {
+        line: ParsedLine::Text { content },
+        ..
+    }) = children.first_mut()
+    else {
+        return None;
+    };
+
+    let trimmed = trim_spans(content.clone());
+    let (alert_type, rest) = [
+        ("[!NOTE]", AlertType::Note),
+        ("[!TIP]", AlertType::Tip),
+        ("[!IMPORTANT]", AlertType::Important),
+        ("[!WARNING]", AlertType::Warning),
+        ("[!CAUTION]", AlertType::Caution),
+    ]
+    .into_iter()
+    .find_map(|(marker, alert_type)| {
+        strip_prefix_spans(&trimmed, marker).map(|rest| (alert_type, rest))
+    })?;
+
+    let rest = trim_spans(rest);
+    if is_blank(&rest) {
+        children.remove(0);
+    } else {
+        *content = rest;
+    }
+    Some(alert_type)
+}
+
+/// インライン数式のスパン。1行に組版できなければ `$...$` のソースをコードとして表示する
+fn math_span(inline: &InlineState, source: &str, delimiter: &str) -> InlineSpan {
+    match math::render_inline(source) {
+        Some(rendered) => {
+            let mut span = inline.span(&rendered, false);
+            span.style.math = true;
+            span
+        }
+        None => inline.span(&format!("{delimiter}{source}{delimiter}"), true),
+    }
+}
+
+/// 標準入力から読んだ文書の `path`（タブやステータスバーにはこの名前を表示する）
+pub const STDIN_PATH: &str = "<stdin>";
+
+#[derive(Clone)]
 pub struct MarkdownDocument {
     pub path: PathBuf,
     pub content: String,
+    /// 入れ子構造を保ったブロックの木
+    pub blocks: Vec<Block>,
+    /// `blocks` を平坦化したもの（スクロールや目次はこの単位で扱う）
     pub parsed_lines: Vec<ParsedLine>,
+    /// `parsed_lines` の各行を囲むコンテナ
+    pub nesting: Vec<Vec<Nesting>>,
+    /// `parsed_lines` の各行のソース上の位置
+    pub sources: Vec<SourceRange>,
+    /// 先頭の YAML / TOML フロントマター
+    pub metadata: Option<Metadata>,
     pub toc: Vec<TocEntry>,
+    pub links: Vec<DocLink>,
+    /// 折りたたんだ見出し・コードブロック・テーブル（`parsed_lines` のインデックス）
+    folded: BTreeSet<usize>,
+    /// 折りたたんだ見出しのセクションの中にあって表示しないブロック
+    folded_away: Vec<bool>,
 }
 
 impl MarkdownDocument {
@@ -74,7 +502,8 @@ impl MarkdownDocument {
         content: String,
         highlighter: &CodeHighlighter,
     ) -> Result<Self, MdError> {
-        let mut parsed_lines = Vec::new();
+        let index = LineIndex::new(&content);
+        let mut tree = TreeBuilder::new(&index);
         let mut toc = Vec::new();
 
         // テーブルや他の拡張機能を有効にする
@@ -82,24 +511,28 @@ impl MarkdownDocument {
         options.insert(Options::ENABLE_TABLES);
         options.insert(Options::ENABLE_STRIKETHROUGH);
         options.insert(Options::ENABLE_TASKLISTS);
+        options.insert(Options::ENABLE_HEADING_ATTRIBUTES);
+        options.insert(Options::ENABLE_FOOTNOTES);
+        options.insert(Options::ENABLE_MATH);
+
+        // フロントマターは本文から外し、位置はファイル全体のバイト位置のまま扱う
+        let (metadata, body_start) = front_matter::extract(&content);
+        let parser = Parser::new_ext(&content[body_start..], options);
 
-        let parser = Parser::new_ext(&content, options);
-        let mut current_line = 0;
+        let mut inline = InlineState::default();
 
         let mut in_heading = false;
         let mut heading_level = 0;
-        let mut heading_text = String::new();
+        let mut heading_text: Vec<InlineSpan> = Vec::new();
+        let mut heading_id: Option<String> = None;
+        let mut slugs = SlugCounter::default();
 
         let mut in_code_block = false;
         let mut code_lang: Option<String> = None;
         let mut code_content = String::new();
 
-        let mut in_list = false;
-        let mut list_depth: usize = 0;
-        let mut list_item_stack: Vec<(String, Option<bool>, usize)> = Vec::new(); // (content, checked, indent)のスタック
-
-        let mut in_blockquote = false;
-        let mut blockquote_content = String::new();
+        // 入れ子のリストごとの次の番号（箇条書きは None）
+        let mut list_numbers: Vec<Option<u64>> = Vec::new();
 
         let mut in_image = false;
         let mut image_url = String::new();
@@ -107,22 +540,82 @@ impl MarkdownDocument {
 
         let mut in_table = false;
         let mut in_table_head = false;
-        let mut table_headers: Vec<String> = Vec::new();
-        let mut table_rows: Vec<Vec<String>> = Vec::new();
-        let mut current_row: Vec<String> = Vec::new();
-        let mut current_cell = String::new();
+        let mut table_headers: Vec<Vec<InlineSpan>> = Vec::new();
+        let mut table_rows: Vec<Vec<Vec<InlineSpan>>> = Vec::new();
+        let mut current_row: Vec<Vec<InlineSpan>> = Vec::new();
+        let mut current_cell: Vec<InlineSpan> = Vec::new();
         let mut table_alignments: Vec<Alignment> = Vec::new();
 
-        let mut current_text = String::new();
+        // 脚注番号は GitHub と同じく最初に参照された順に振る
+        let mut footnote_numbers: HashMap<String, usize> = HashMap::new();
+        let mut footnote_bodies: HashMap<String, (Vec<Block>, Range<usize>)> = HashMap::new();
+
+        // 段落・タイトなリスト項目のテキスト
+        let mut current_text: Vec<InlineSpan> = Vec::new();
+
+        // HTML ブロックは閉じるまでまとめてから解釈する
+        let mut html_block = String::new();
+        // HTML の `<p>` `<div>` などの開いている要素（`align` でコンテナを開いたか）
+        let mut html_elements: Vec<(String, bool)> = Vec::new();
+        let mut in_summary = false;
+        let mut summary_text: Vec<InlineSpan> = Vec::new();
+        let mut details_count = 0;
+
+        for (event, range) in parser.into_offset_iter() {
+            let range = range.start + body_start..range.end + body_start;
+            // 段落のテキストになるイベントの範囲（処理後にテキストへ加える）
+            let text_range = (!in_heading
+                && !in_table
+                && !in_code_block
+                && !in_image
+                && matches!(
+                    event,
+                    Event::Start(
+                        Tag::Paragraph
+                            | Tag::Emphasis
+                            | Tag::Strong
+                            | Tag::Strikethrough
+                            | Tag::Link { .. }
+                    ) | Event::Text(_)
+                        | Event::Code(_)
+                        | Event::InlineMath(_)
+                        | Event::FootnoteReference(_)
+                        | Event::SoftBreak
+                        | Event::HardBreak
+                        | Event::InlineHtml(_)
+                ))
+            .then(|| range.clone());
+            // 解釈する HTML（ブロックなら true）
+            let mut html: Option<(String, bool)> = None;
+            let html_range = range.clone();
+
+            // インラインテキストの追加先（見出し・セル・段落）
+            let inline_target = if in_heading {
+                &mut heading_text
+            } else if in_summary {
+                &mut summary_text
+            } else if in_table {
+                &mut current_cell
+            } else {
+                &mut current_text
+            };
+
+            // インラインの HTML はブロックをまたがないので、閉じ忘れたタグは葉ブロックの終わりで閉じる
+            if matches!(
+                event,
+                Event::End(
+                    TagEnd::Paragraph | TagEnd::Heading(_) | TagEnd::TableCell | TagEnd::Item
+                ) | Event::Start(Tag::List(_) | Tag::Item)
+            ) {
+                inline.end_block();
+            }
 
-        for event in parser {
             match event {
-                Event::Start(Tag::Heading { level, .. }) => {
-                    if !current_text.is_empty() {
-                        parsed_lines.push(ParsedLine::Text {
-                            content: std::mem::take(&mut current_text),
-                        });
-                    }
+                Event::Start(Tag::Paragraph) | Event::End(TagEnd::Paragraph) => {
+                    tree.flush_text(&mut current_text);
+                }
+                Event::Start(Tag::Heading { level, id, .. }) => {
+                    tree.flush_text(&mut current_text);
                     in_heading = true;
                     heading_level = match level {
                         HeadingLevel::H1 => 1,
@@ -133,28 +626,22 @@ impl MarkdownDocument {
                         HeadingLevel::H6 => 6,
                     };
                     heading_text.clear();
+                    heading_id = id.map(|id| id.to_string());
                 }
                 Event::End(TagEnd::Heading(_)) => {
                     in_heading = false;
-                    let line_num = current_line;
-                    parsed_lines.push(ParsedLine::Heading {
-                        level: heading_level,
-                        text: heading_text.clone(),
-                        line_num,
-                    });
-                    toc.push(TocEntry {
-                        level: heading_level,
-                        title: heading_text.clone(),
-                        line_number: line_num,
-                    });
-                    current_line += 1;
+                    push_heading(
+                        &mut tree,
+                        &mut toc,
+                        &mut slugs,
+                        heading_level,
+                        std::mem::take(&mut heading_text),
+                        heading_id.take(),
+                        range,
+                    );
                 }
                 Event::Start(Tag::CodeBlock(kind)) => {
-                    if !current_text.is_empty() {
-                        parsed_lines.push(ParsedLine::Text {
-                            content: std::mem::take(&mut current_text),
-                        });
-                    }
+                    tree.flush_text(&mut current_text);
                     in_code_block = true;
                     code_lang = match kind {
                         pulldown_cmark::CodeBlockKind::Fenced(lang) => {
@@ -170,179 +657,173 @@ impl MarkdownDocument {
                 }
                 Event::End(TagEnd::CodeBlock) => {
                     in_code_block = false;
-                    let highlighted = highlighter.highlight(&code_content, code_lang.as_deref());
-                    parsed_lines.push(ParsedLine::Code {
-                        lang: code_lang.clone(),
-                        content: code_content.clone(),
-                        highlighted,
-                    });
-                    current_line += code_content.lines().count() + 2;
-                }
-                Event::Start(Tag::List(_)) => {
-                    if !current_text.is_empty() {
-                        parsed_lines.push(ParsedLine::Text {
-                            content: std::mem::take(&mut current_text),
-                        });
-                    }
-
-                    // ネストされたリストが開始する前に、親アイテムの内容を出力
-                    if in_list && !list_item_stack.is_empty() {
-                        if let Some(item) = list_item_stack.last_mut() {
-                            if !item.0.trim().is_empty() {
-                                parsed_lines.push(ParsedLine::ListItem {
-                                    indent: item.2,
-                                    content: item.0.trim().to_string(),
-                                    checked: item.1,
-                                });
-                                // 出力済みなので内容をクリア（インデントレベルは保持）
-                                item.0.clear();
-                            }
-                        }
-                    }
-
-                    in_list = true;
-                    list_depth += 1;
+                    // `bash file=deploy.sh` のような属性は言語に含めない
+                    let language = code_lang
+                        .as_deref()
+                        .and_then(|info| CodeInfo::parse(info).language);
+                    let highlighted = highlighter.highlight(&code_content, language.as_deref());
+                    tree.push_line(
+                        ParsedLine::Code {
+                            lang: code_lang.clone(),
+                            content: code_content.clone(),
+                            highlighted,
+                        },
+                        range,
+                    );
                 }
-                Event::End(TagEnd::List(_)) => {
-                    list_depth = list_depth.saturating_sub(1);
-                    if list_depth == 0 {
-                        in_list = false;
-                    }
+                Event::Start(Tag::List(start)) => {
+                    // タイトなリストでは親項目のテキストがここで確定する
+                    tree.flush_text(&mut current_text);
+                    list_numbers.push(start);
+                    tree.open(ContainerKind::List { start }, range.start);
                 }
                 Event::Start(Tag::Item) => {
-                    // 新しいアイテムをスタックにプッシュ
-                    let indent = list_depth.saturating_sub(1);
-                    list_item_stack.push((String::new(), None, indent));
-                }
-                Event::End(TagEnd::Item) => {
-                    if in_list {
-                        if let Some((content, checked, indent)) = list_item_stack.pop() {
-                            // 内容が空でない場合のみ出力（既に出力済みの場合は空）
-                            if !content.trim().is_empty() {
-                                parsed_lines.push(ParsedLine::ListItem {
-                                    indent,
-                                    content: content.trim().to_string(),
-                                    checked,
-                                });
-                            }
-                        }
-                    }
+                    tree.flush_text(&mut current_text);
+                    let number = list_numbers.last_mut().and_then(|next| {
+                        let number = *next;
+                        *next = next.map(|n| n + 1);
+                        number
+                    });
+                    let marker = ListMarker::from_source(&content[range.clone()], number);
+                    tree.open(
+                        ContainerKind::ListItem {
+                            marker,
+                            checked: None,
+                        },
+                        range.start,
+                    );
                 }
                 Event::TaskListMarker(checked) => {
                     // 現在のアイテムのcheckedフラグを設定
-                    if let Some(item) = list_item_stack.last_mut() {
-                        item.1 = Some(checked);
+                    if let Some(OpenContainer {
+                        kind: ContainerKind::ListItem { checked: state, .. },
+                        ..
+                    }) = tree.stack.last_mut()
+                    {
+                        *state = Some(checked);
                     }
                 }
                 Event::Start(Tag::BlockQuote(_)) => {
-                    if !current_text.is_empty() {
-                        parsed_lines.push(ParsedLine::Text {
-                            content: std::mem::take(&mut current_text),
-                        });
-                    }
-                    in_blockquote = true;
-                    blockquote_content.clear();
-                }
-                Event::End(TagEnd::BlockQuote(_)) => {
-                    in_blockquote = false;
-
-                    // GitHub Alerts パターンを検出
-                    let content = std::mem::take(&mut blockquote_content);
-                    let trimmed = content.trim();
-
-                    #[allow(clippy::manual_map)]
-                    let alert_type = if let Some(stripped) = trimmed.strip_prefix("[!NOTE]") {
-                        Some((AlertType::Note, stripped))
-                    } else if let Some(stripped) = trimmed.strip_prefix("[!TIP]") {
-                        Some((AlertType::Tip, stripped))
-                    } else if let Some(stripped) = trimmed.strip_prefix("[!IMPORTANT]") {
-                        Some((AlertType::Important, stripped))
-                    } else if let Some(stripped) = trimmed.strip_prefix("[!WARNING]") {
-                        Some((AlertType::Warning, stripped))
-                    } else if let Some(stripped) = trimmed.strip_prefix("[!CAUTION]") {
-                        Some((AlertType::Caution, stripped))
-                    } else {
-                        None
-                    };
-
-                    if let Some((alert_type, alert_content)) = alert_type {
-                        parsed_lines.push(ParsedLine::Alert {
-                            alert_type,
-                            content: alert_content.trim().to_string(),
-                        });
-                    } else {
-                        parsed_lines.push(ParsedLine::BlockQuote { content });
+                    tree.flush_text(&mut current_text);
+                    tree.open(ContainerKind::BlockQuote, range.start);
+                }
+                Event::Start(Tag::FootnoteDefinition(label)) => {
+                    tree.flush_text(&mut current_text);
+                    tree.open(
+                        ContainerKind::FootnoteDefinition {
+                            number: 0,
+                            label: label.to_string(),
+                        },
+                        range.start,
+                    );
+                }
+                Event::End(
+                    TagEnd::List(_)
+                    | TagEnd::Item
+                    | TagEnd::BlockQuote(_)
+                    | TagEnd::FootnoteDefinition,
+                ) => {
+                    tree.flush_text(&mut current_text);
+                    tree.close_html_containers(range.end);
+                    if let Some(OpenContainer {
+                        mut kind,
+                        mut children,
+                        ..
+                    }) = tree.close()
+                    {
+                        match &kind {
+                            ContainerKind::List { .. } => {
+                                list_numbers.pop();
+                            }
+                            ContainerKind::BlockQuote => {
+                                // GitHub Alerts パターンを検出
+                                if let Some(alert_type) = detect_alert(&mut children) {
+                                    kind = ContainerKind::Alert(alert_type);
+                                }
+                            }
+                            ContainerKind::FootnoteDefinition { label, .. } => {
+                                // 番号は参照順に決まるので文末でまとめて配置する
+                                footnote_bodies.insert(label.clone(), (children, range));
+                                continue;
+                            }
+                            _ => {}
+                        }
+                        tree.push_container(kind, children, range);
                     }
                 }
+                Event::Start(Tag::Emphasis) => inline.emphasis += 1,
+                Event::End(TagEnd::Emphasis) => {
+                    inline.emphasis = inline.emphasis.saturating_sub(1);
+                }
+                Event::Start(Tag::Strong) => inline.strong += 1,
+                Event::End(TagEnd::Strong) => inline.strong = inline.strong.saturating_sub(1),
+                Event::Start(Tag::Strikethrough) => inline.strikethrough += 1,
+                Event::End(TagEnd::Strikethrough) => {
+                    inline.strikethrough = inline.strikethrough.saturating_sub(1);
+                }
+                Event::Start(Tag::Link { dest_url, .. }) => {
+                    inline.link = Some(Link {
+                        index: inline.link_count,
+                        url: dest_url.to_string(),
+                    });
+                    inline.link_count += 1;
+                }
+                Event::End(TagEnd::Link) => inline.link = None,
+                Event::FootnoteReference(label) => {
+                    let next = footnote_numbers.len() + 1;
+                    let number = *footnote_numbers.entry(label.to_string()).or_insert(next);
+                    push_span(inline_target, InlineSpan::footnote_ref(number));
+                }
                 Event::Text(text) => {
-                    if in_heading {
-                        heading_text.push_str(&text);
-                    } else if in_code_block {
+                    if in_code_block {
                         code_content.push_str(&text);
-                    } else if in_image {
+                    } else if in_image && !in_heading {
                         image_alt_text.push_str(&text);
-                    } else if in_table {
-                        current_cell.push_str(&text);
-                    } else if in_list {
-                        // スタックの一番上のアイテムにテキストを追加
-                        if let Some(item) = list_item_stack.last_mut() {
-                            item.0.push_str(&text);
-                        }
-                    } else if in_blockquote {
-                        blockquote_content.push_str(&text);
                     } else {
-                        current_text.push_str(&text);
+                        push_span(inline_target, inline.span(&text, false));
                     }
                 }
                 Event::Code(code) => {
-                    let code_str = format!("⟨INLINE_CODE⟩{code}⟨/INLINE_CODE⟩");
-                    if in_heading {
-                        heading_text.push_str(&code_str);
-                    } else if in_table {
-                        current_cell.push_str(&code_str);
-                    } else if in_list {
-                        // スタックの一番上のアイテムにコードを追加
-                        if let Some(item) = list_item_stack.last_mut() {
-                            item.0.push_str(&code_str);
-                        }
-                    } else if in_blockquote {
-                        blockquote_content.push_str(&code_str);
-                    } else {
-                        current_text.push_str(&code_str);
-                    }
+                    push_span(inline_target, inline.span(&code, true));
+                }
+                Event::InlineMath(source) => {
+                    push_span(inline_target, math_span(&inline, &source, "$"));
+                }
+                // 段落の中の `$$...$$` は独立したブロックにする
+                Event::DisplayMath(source) if !in_heading && !in_table => {
+                    tree.flush_text(&mut current_text);
+                    let source = source.trim().to_string();
+                    tree.push_line(
+                        ParsedLine::Math {
+                            highlighted: highlighter.highlight(&source, Some("tex")),
+                            source,
+                        },
+                        range,
+                    );
+                }
+                Event::DisplayMath(source) => {
+                    push_span(inline_target, math_span(&inline, &source, "$$"));
                 }
                 Event::SoftBreak | Event::HardBreak => {
-                    if in_heading {
-                        heading_text.push(' ');
-                    } else if in_code_block {
+                    if in_code_block {
                         code_content.push('\n');
-                    } else if in_table {
-                        current_cell.push(' ');
-                    } else if in_list {
-                        // スタックの一番上のアイテムにスペースを追加
-                        if let Some(item) = list_item_stack.last_mut() {
-                            item.0.push(' ');
-                        }
-                    } else if in_blockquote {
-                        blockquote_content.push('\n');
                     } else {
-                        current_text.push(' ');
+                        // 引用内の改行は保持し、それ以外は空白として扱う
+                        let in_quote = matches!(tree.innermost(), Some(ContainerKind::BlockQuote));
+                        let separator = if in_quote && !in_heading && !in_table {
+                            "\n"
+                        } else {
+                            " "
+                        };
+                        push_span(inline_target, inline.span(separator, false));
                     }
                 }
                 Event::Rule => {
-                    if !current_text.is_empty() {
-                        parsed_lines.push(ParsedLine::Text {
-                            content: std::mem::take(&mut current_text),
-                        });
-                    }
-                    parsed_lines.push(ParsedLine::HorizontalRule);
+                    tree.flush_text(&mut current_text);
+                    tree.push_line(ParsedLine::HorizontalRule, range);
                 }
                 Event::Start(Tag::Image { dest_url, .. }) => {
-                    if !current_text.is_empty() {
-                        parsed_lines.push(ParsedLine::Text {
-                            content: std::mem::take(&mut current_text),
-                        });
-                    }
+                    tree.flush_text(&mut current_text);
                     // 画像のURL（パス）を記録
                     // alt_textは後のEvent::Textで取得
                     in_image = true;
@@ -351,17 +832,16 @@ impl MarkdownDocument {
                 }
                 Event::End(TagEnd::Image) => {
                     in_image = false;
-                    parsed_lines.push(ParsedLine::Image {
-                        alt_text: std::mem::take(&mut image_alt_text),
-                        url: std::mem::take(&mut image_url),
-                    });
+                    tree.push_line(
+                        ParsedLine::Image {
+                            alt_text: std::mem::take(&mut image_alt_text),
+                            url: std::mem::take(&mut image_url),
+                        },
+                        range,
+                    );
                 }
                 Event::Start(Tag::Table(alignments)) => {
-                    if !current_text.is_empty() {
-                        parsed_lines.push(ParsedLine::Text {
-                            content: std::mem::take(&mut current_text),
-                        });
-                    }
+                    tree.flush_text(&mut current_text);
                     in_table = true;
                     table_headers.clear();
                     table_rows.clear();
@@ -378,11 +858,14 @@ impl MarkdownDocument {
                 }
                 Event::End(TagEnd::Table) => {
                     in_table = false;
-                    parsed_lines.push(ParsedLine::Table {
-                        headers: std::mem::take(&mut table_headers),
-                        rows: std::mem::take(&mut table_rows),
-                        alignments: std::mem::take(&mut table_alignments),
-                    });
+                    tree.push_line(
+                        ParsedLine::Table {
+                            headers: std::mem::take(&mut table_headers),
+                            rows: std::mem::take(&mut table_rows),
+                            alignments: std::mem::take(&mut table_alignments),
+                        },
+                        range,
+                    );
                 }
                 Event::Start(Tag::TableHead) => {
                     in_table_head = true;
@@ -392,15 +875,11 @@ impl MarkdownDocument {
                     in_table_head = false;
                     table_headers = std::mem::take(&mut current_row);
                 }
-                Event::Start(Tag::TableRow) => {
-                    if !in_table_head {
-                        current_row.clear();
-                    }
+                Event::Start(Tag::TableRow) if !in_table_head => {
+                    current_row.clear();
                 }
-                Event::End(TagEnd::TableRow) => {
-                    if !in_table_head {
-                        table_rows.push(std::mem::take(&mut current_row));
-                    }
+                Event::End(TagEnd::TableRow) if !in_table_head => {
+                    table_rows.push(std::mem::take(&mut current_row));
                 }
                 Event::Start(Tag::TableCell) => {
                     current_cell.clear();
@@ -408,21 +887,465 @@ impl MarkdownDocument {
                 Event::End(TagEnd::TableCell) => {
                     current_row.push(std::mem::take(&mut current_cell));
                 }
+                Event::Start(Tag::HtmlBlock) => {
+                    tree.flush_text(&mut current_text);
+                    html_block.clear();
+                }
+                Event::Html(text) => html_block.push_str(&text),
+                Event::End(TagEnd::HtmlBlock) => {
+                    html = Some((std::mem::take(&mut html_block), true));
+                }
+                Event::InlineHtml(text) => html = Some((text.to_string(), false)),
                 _ => {}
             }
+
+            for token in html.iter().flat_map(|(source, _)| html::tokenize(source)) {
+                let block = html.as_ref().is_some_and(|(_, block)| *block);
+                let target = if in_heading {
+                    &mut heading_text
+                } else if in_summary {
+                    &mut summary_text
+                } else if in_table {
+                    &mut current_cell
+                } else {
+                    &mut current_text
+                };
+
+                match &token {
+                    HtmlToken::Comment => {}
+                    HtmlToken::Text(text) => {
+                        // HTML ブロックの中では改行やインデントは空白1つとして扱う
+                        let text = if block {
+                            collapse_whitespace(text)
+                        } else {
+                            text.clone()
+                        };
+                        if block && !in_heading && !in_summary && !text.trim().is_empty() {
+                            tree.extend_text(html_range.clone());
+                        }
+                        push_span(target, inline.span(&text, false));
+                    }
+                    HtmlToken::Open { name, raw, .. } => match name.as_str() {
+                        "br" => push_span(target, inline.span("\n", false)),
+                        "img" if in_heading || in_summary || in_table => {
+                            let alt = token.attr("alt").unwrap_or_default();
+                            push_span(target, inline.span(alt, false));
+                        }
+                        "img" => {
+                            tree.flush_text(&mut current_text);
+                            tree.push_line(
+                                ParsedLine::Image {
+                                    alt_text: token.attr("alt").unwrap_or_default().to_string(),
+                                    url: token.attr("src").unwrap_or_default().to_string(),
+                                },
+                                html_range.clone(),
+                            );
+                        }
+                        "hr" => {
+                            tree.flush_text(&mut current_text);
+                            tree.push_line(ParsedLine::HorizontalRule, html_range.clone());
+                        }
+                        "details" => {
+                            tree.flush_text(&mut current_text);
+                            tree.open(
+                                ContainerKind::Details {
+                                    index: details_count,
+                                    open: token.attr("open").is_some(),
+                                },
+                                html_range.start,
+                            );
+                            details_count += 1;
+                        }
+                        "summary" => {
+                            in_summary = true;
+                            summary_text.clear();
+                        }
+                        "p" | "div" | "center" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                            tree.flush_text(&mut current_text);
+                            let alignment = if name == "center" {
+                                Some(Alignment::Center)
+                            } else {
+                                token.attr("align").and_then(parse_align)
+                            };
+                            if let Some(alignment) = alignment {
+                                tree.open(ContainerKind::Aligned(alignment), html_range.start);
+                            }
+                            html_elements.push((name.clone(), alignment.is_some()));
+
+                            if let Some(level) = name.strip_prefix('h') {
+                                in_heading = true;
+                                heading_level = level.parse().unwrap_or(1);
+                                heading_text.clear();
+                                heading_id = token.attr("id").map(str::to_string);
+                            }
+                        }
+                        name => {
+                            if !inline.apply_html(name, true, token.attr("href")) {
+                                push_span(target, inline.html_tag(raw));
+                            }
+                        }
+                    },
+                    HtmlToken::Close { name, raw } => match name.as_str() {
+                        "br" | "img" | "hr" => {}
+                        "summary" => {
+                            in_summary = false;
+                            if let Some(ContainerKind::Details { index, open }) =
+                                tree.innermost().cloned()
+                            {
+                                tree.push_line(
+                                    ParsedLine::Details {
+                                        index,
+                                        summary: trim_spans(std::mem::take(&mut summary_text)),
+                                        open,
+                                    },
+                                    html_range.clone(),
+                                );
+                            } else {
+                                // `<details>` の外の `<summary>` は本文として残す
+                                for span in std::mem::take(&mut summary_text) {
+                                    push_span(&mut current_text, span);
+                                }
+                            }
+                        }
+                        "details" => {
+                            tree.flush_text(&mut current_text);
+                            if matches!(tree.innermost(), Some(ContainerKind::Details { .. })) {
+                                tree.close_html(html_range.end);
+                            }
+                        }
+                        "p" | "div" | "center" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                            if in_heading && name.starts_with('h') {
+                                in_heading = false;
+                                push_heading(
+                                    &mut tree,
+                                    &mut toc,
+                                    &mut slugs,
+                                    heading_level,
+                                    std::mem::take(&mut heading_text),
+                                    heading_id.take(),
+                                    html_range.clone(),
+                                );
+                            } else {
+                                tree.flush_text(&mut current_text);
+                            }
+                            if let Some(position) =
+                                html_elements.iter().rposition(|(open, _)| open == name)
+                            {
+                                let (_, aligned) = html_elements.remove(position);
+                                if aligned
+                                    && matches!(tree.innermost(), Some(ContainerKind::Aligned(_)))
+                                {
+                                    tree.close_html(html_range.end);
+                                }
+                            }
+                        }
+                        name => {
+                            if !inline.apply_html(name, false, None) {
+                                push_span(target, inline.html_tag(raw));
+                            }
+                        }
+                    },
+                }
+            }
+            if html.as_ref().is_some_and(|(_, block)| *block) {
+                tree.flush_text(&mut current_text);
+            }
+
+            if let Some(range) = text_range {
+                tree.extend_text(range);
+            }
         }
 
-        if !current_text.is_empty() {
-            parsed_lines.push(ParsedLine::Text {
-                content: current_text,
+        tree.flush_text(&mut current_text);
+        let mut blocks = tree.finish();
+
+        // 参照された脚注を番号順に文末へ並べる（参照されない定義は表示しない）
+        let mut footnotes: Vec<(usize, String)> = footnote_numbers
+            .into_iter()
+            .map(|(label, number)| (number, label))
+            .collect();
+        footnotes.sort();
+        if !footnotes.is_empty() {
+            // 区切り線はソースにないので文末の位置とする
+            let end = index.range(content.len()..content.len());
+            blocks.push(Block::leaf(ParsedLine::HorizontalRule, end));
+        }
+        for (number, label) in footnotes {
+            let (children, range) = footnote_bodies.remove(&label).unwrap_or_default();
+            blocks.push(Block::Container {
+                kind: ContainerKind::FootnoteDefinition { number, label },
+                children,
+                source: index.range(range),
             });
         }
 
+        // 脚注の移動でリンクの出現順が変わるため振り直す
+        renumber_links(&mut blocks);
+        let Flattened {
+            lines: parsed_lines,
+            nesting,
+            sources,
+        } = flatten(&blocks);
+        let links = collect_links(&parsed_lines);
+        let folded_away = vec![false; parsed_lines.len()];
+
         Ok(MarkdownDocument {
             path,
             content,
+            blocks,
             parsed_lines,
+            nesting,
+            sources,
+            metadata,
             toc,
+            links,
+            folded: BTreeSet::new(),
+            folded_away,
+        })
+    }
+
+    /// フロントマターの `title`
+    pub fn title(&self) -> Option<&str> {
+        self.metadata.as_ref().and_then(Metadata::title)
+    }
+
+    /// 標準入力から読んだ文書か（読み直しや位置の記録はできない）
+    pub fn is_stdin(&self) -> bool {
+        self.path == Path::new(STDIN_PATH)
+    }
+
+    /// 画面に表示する名前（`title` がなければパス）
+    pub fn display_title(&self) -> String {
+        match self.title() {
+            Some(title) => title.to_string(),
+            None => self.path.display().to_string(),
+        }
+    }
+
+    /// ソースの行数
+    pub fn line_count(&self) -> usize {
+        self.content.lines().count().max(1)
+    }
+
+    /// `parsed_lines[block]` が始まるソースの行（1始まり）
+    pub fn source_line(&self, block: usize) -> usize {
+        self.sources
+            .get(block)
+            .map_or(1, |source| source.start_line.max(1))
+    }
+
+    /// ソースの `line` 行目（1始まり）を表示するブロック
+    ///
+    /// 行を含むブロックがなければ（空行など）その後に続く最初のブロックを返す。
+    pub fn block_at_line(&self, line: usize) -> usize {
+        self.sources
+            .iter()
+            .position(|source| source.contains_line(line))
+            .or_else(|| {
+                self.sources
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, source)| source.start_line > line)
+                    .min_by_key(|(_, source)| source.start_line)
+                    .map(|(block, _)| block)
+            })
+            .unwrap_or(self.parsed_lines.len().saturating_sub(1))
+    }
+
+    /// 閉じた `<details>` か折りたたんだセクションの中にあって表示しないブロックか
+    pub fn is_hidden(&self, block: usize) -> bool {
+        self.folded_away.get(block).copied().unwrap_or(false)
+            || self.nesting.get(block).is_some_and(|nesting| {
+                nesting
+                    .iter()
+                    .any(|entry| matches!(entry.kind, ContainerKind::Details { open: false, .. }))
+            })
+    }
+
+    /// 折りたためるブロックか（中身のある見出し、コードブロック、テーブル）
+    pub fn is_foldable(&self, block: usize) -> bool {
+        match self.parsed_lines.get(block) {
+            Some(ParsedLine::Heading { .. }) => self.section_end(block) > block + 1,
+            Some(ParsedLine::Code { .. } | ParsedLine::Table { .. }) => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_folded(&self, block: usize) -> bool {
+        self.folded.contains(&block)
+    }
+
+    /// 見出し行だけを残して中身を表示しないコードブロック・テーブルか
+    pub fn is_folded_content(&self, block: usize) -> bool {
+        self.is_folded(block)
+            && matches!(
+                self.parsed_lines[block],
+                ParsedLine::Code { .. } | ParsedLine::Table { .. }
+            )
+    }
+
+    pub fn folded_blocks(&self) -> impl Iterator<Item = usize> + '_ {
+        self.folded.iter().copied()
+    }
+
+    /// ブロックを折りたたむ（`folded` が false なら展開する）
+    pub fn set_folded(&mut self, block: usize, folded: bool) {
+        if folded && self.is_foldable(block) {
+            self.folded.insert(block);
+        } else if !folded {
+            self.folded.remove(&block);
+        }
+
+        self.folded_away = vec![false; self.parsed_lines.len()];
+        let headings: Vec<usize> = self
+            .folded
+            .iter()
+            .copied()
+            .filter(|&block| matches!(self.parsed_lines[block], ParsedLine::Heading { .. }))
+            .collect();
+        for heading in headings {
+            let end = self.section_end(heading);
+            self.folded_away[heading + 1..end].fill(true);
+        }
+    }
+
+    /// 見出し `block` のセクションの終わり（次の同じか浅い見出し、または文末の脚注）
+    pub fn section_end(&self, block: usize) -> usize {
+        let Some(ParsedLine::Heading { level, .. }) = self.parsed_lines.get(block) else {
+            return block + 1;
+        };
+        (block + 1..self.parsed_lines.len())
+            .find(|&next| match &self.parsed_lines[next] {
+                ParsedLine::Heading {
+                    level: next_level, ..
+                } => next_level <= level,
+                _ => self.is_footnote_section(next),
+            })
+            .unwrap_or(self.parsed_lines.len())
+    }
+
+    /// 文末にまとめた脚注（とその前の区切り線）か
+    fn is_footnote_section(&self, block: usize) -> bool {
+        let in_footnote = self.nesting[block]
+            .iter()
+            .any(|entry| matches!(entry.kind, ContainerKind::FootnoteDefinition { .. }));
+        // 脚注の前の区切り線はソースにないので文末の位置を持つ
+        let separator = matches!(self.parsed_lines[block], ParsedLine::HorizontalRule)
+            && self.sources[block].bytes.start >= self.content.len();
+        in_footnote || separator
+    }
+
+    /// 折りたたんだときに隠れるソースの行数（テーブルは行数）
+    pub fn folded_line_count(&self, block: usize) -> usize {
+        match &self.parsed_lines[block] {
+            ParsedLine::Heading { .. } => {
+                let end = self.section_end(block);
+                self.sources[end - 1]
+                    .end_line
+                    .saturating_sub(self.sources[block].end_line)
+            }
+            ParsedLine::Code { content, .. } => content.lines().count(),
+            ParsedLine::Table { rows, .. } => rows.len(),
+            _ => 0,
+        }
+    }
+
+    /// ブロックを含むセクションの見出し
+    fn section_heading(&self, block: usize) -> Option<usize> {
+        self.parsed_lines[..block]
+            .iter()
+            .rposition(|line| matches!(line, ParsedLine::Heading { .. }))
+    }
+
+    fn heading_anchor(&self, block: usize) -> Option<String> {
+        match &self.parsed_lines[block] {
+            ParsedLine::Heading { anchor, .. } => Some(anchor.clone()),
+            _ => None,
+        }
+    }
+
+    /// 折りたためるブロックの、再読み込み後も使える位置
+    pub fn fold_key(&self, block: usize) -> Option<FoldKey> {
+        match self.parsed_lines.get(block)? {
+            ParsedLine::Heading { anchor, .. } => Some(FoldKey::Section(anchor.clone())),
+            ParsedLine::Code { .. } | ParsedLine::Table { .. } => {
+                let heading = self.section_heading(block);
+                let start = heading.map_or(0, |heading| heading + 1);
+                let nth = self.parsed_lines[start..block]
+                    .iter()
+                    .filter(|line| {
+                        matches!(line, ParsedLine::Code { .. } | ParsedLine::Table { .. })
+                    })
+                    .count();
+                Some(FoldKey::Block {
+                    section: heading.and_then(|heading| self.heading_anchor(heading)),
+                    nth,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// `fold_key` の位置にあるブロック
+    pub fn fold_block(&self, key: &FoldKey) -> Option<usize> {
+        let mut section: Option<&str> = None;
+        let mut nth = 0;
+        for (block, line) in self.parsed_lines.iter().enumerate() {
+            match (line, key) {
+                (ParsedLine::Heading { anchor, .. }, FoldKey::Section(wanted))
+                    if anchor == wanted =>
+                {
+                    return Some(block);
+                }
+                (ParsedLine::Heading { anchor, .. }, _) => {
+                    section = Some(anchor);
+                    nth = 0;
+                }
+                (ParsedLine::Code { .. } | ParsedLine::Table { .. }, _) => {
+                    if *key
+                        == (FoldKey::Block {
+                            section: section.map(str::to_string),
+                            nth,
+                        })
+                    {
+                        return Some(block);
+                    }
+                    nth += 1;
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// `index` 番目の `<details>` を開閉する
+    pub fn set_details_open(&mut self, index: usize, open: bool) {
+        for line in &mut self.parsed_lines {
+            if let ParsedLine::Details {
+                index: i, open: o, ..
+            } = line
+            {
+                if *i == index {
+                    *o = open;
+                }
+            }
+        }
+        for entry in self.nesting.iter_mut().flatten() {
+            if let ContainerKind::Details { index: i, open: o } = &mut entry.kind {
+                if *i == index {
+                    *o = open;
+                }
+            }
+        }
+    }
+
+    /// 番号 `number` の脚注の本文
+    pub fn footnote(&self, number: usize) -> Option<&[InlineSpan]> {
+        self.parsed_lines.iter().find_map(|line| match line {
+            ParsedLine::FootnoteDefinition {
+                number: n, content, ..
+            } if *n == number => Some(content.as_slice()),
+            _ => None,
         })
     }
 }
@@ -1,6 +1,9 @@
 #[cfg(test)]
 mod tests {
-    use crate::markdown::{highlighter::CodeHighlighter, parser::MarkdownDocument, ParsedLine};
+    use crate::markdown::{
+        highlighter::CodeHighlighter, parser::MarkdownDocument, plain_text, FoldKey, InlineSpan,
+        ParsedLine,
+    };
     use std::path::PathBuf;
 
     #[test]
@@ -19,7 +22,7 @@ mod tests {
                 .unwrap();
 
         // Extract list items
-        let list_items: Vec<(&usize, &String)> = doc
+        let list_items: Vec<(&usize, String)> = doc
             .parsed_lines
             .iter()
             .filter_map(|line| {
@@ -27,7 +30,7 @@ mod tests {
                     indent, content, ..
                 } = line
                 {
-                    Some((indent, content))
+                    Some((indent, plain_text(content)))
                 } else {
                     None
                 }
@@ -78,24 +81,54 @@ mod tests {
             })
             .expect("Should have text line");
 
-        // Should contain markers, not backticks
-        assert!(
-            text_line.contains("⟨INLINE_CODE⟩"),
-            "Should contain opening marker"
-        );
-        assert!(
-            text_line.contains("⟨/INLINE_CODE⟩"),
-            "Should contain closing marker"
-        );
-        assert!(
-            text_line.contains("inline code"),
-            "Should contain the code content"
+        // Inline code should become its own span, without backticks
+        assert_eq!(text_line.len(), 3);
+        assert_eq!(text_line[0], InlineSpan::plain("Text with "));
+        assert_eq!(text_line[1], InlineSpan::code("inline code"));
+        assert_eq!(text_line[2], InlineSpan::plain(" here"));
+        assert_eq!(plain_text(text_line), "Text with inline code here");
+    }
+
+    #[test]
+    fn test_emphasis_strong_strike_and_link_parsing() {
+        let markdown = "*em* **strong** ~~gone~~ [link](https://example.com)";
+        let highlighter = CodeHighlighter::new("base16-ocean.dark".to_string());
+        let doc =
+            MarkdownDocument::parse(PathBuf::from("test.md"), markdown.to_string(), &highlighter)
+                .unwrap();
+
+        let ParsedLine::Text { content } = &doc.parsed_lines[0] else {
+            panic!("Should have text line");
+        };
+
+        assert_eq!(plain_text(content), "em strong gone link");
+        assert!(content[0].style.emphasis);
+        assert!(content[2].style.strong);
+        assert!(content[4].style.strikethrough);
+        assert_eq!(content[6].text, "link");
+        assert_eq!(
+            content[6].link.as_ref().map(|l| l.url.as_str()),
+            Some("https://example.com")
         );
+        assert_eq!(doc.links.len(), 1);
+        assert_eq!(doc.links[0].text, "link");
+        assert_eq!(doc.links[0].line_index, 0);
+    }
 
-        // Should NOT have standalone backticks anymore
-        // Note: the markers contain the content between them, backticks should not be separate
-        let expected = "Text with ⟨INLINE_CODE⟩inline code⟨/INLINE_CODE⟩ here";
-        assert_eq!(text_line, expected);
+    #[test]
+    fn test_heading_inline_code_toc_title() {
+        let markdown = "# The `mdv` **viewer**";
+        let highlighter = CodeHighlighter::new("base16-ocean.dark".to_string());
+        let doc =
+            MarkdownDocument::parse(PathBuf::from("test.md"), markdown.to_string(), &highlighter)
+                .unwrap();
+
+        assert_eq!(doc.toc[0].title, "The mdv viewer");
+        let ParsedLine::Heading { text, .. } = &doc.parsed_lines[0] else {
+            panic!("Should have heading");
+        };
+        assert!(text[1].style.code);
+        assert!(text[3].style.strong);
     }
 
     #[test]
@@ -128,57 +161,53 @@ mod tests {
             })
             .expect("Should have table");
 
-        // Check that table cells contain markers
+        // Table cells should carry inline code spans
         let (headers, rows, _) = table;
         assert_eq!(headers.len(), 2);
         assert_eq!(rows.len(), 2);
 
-        // First row should have inline code markers
-        assert!(
-            rows[0][0].contains("⟨INLINE_CODE⟩"),
-            "First cell should contain inline code marker"
-        );
-        assert_eq!(rows[0][0], "⟨INLINE_CODE⟩j⟨/INLINE_CODE⟩");
-
-        // Second row should also have inline code markers
-        assert!(
-            rows[1][0].contains("⟨INLINE_CODE⟩"),
-            "Second cell should contain inline code marker"
-        );
-        assert_eq!(rows[1][0], "⟨INLINE_CODE⟩k⟨/INLINE_CODE⟩");
+        assert_eq!(rows[0][0], vec![InlineSpan::code("j")]);
+        assert_eq!(rows[1][0], vec![InlineSpan::code("k")]);
+        assert_eq!(plain_text(&rows[0][1]), "Scroll down");
     }
 
     #[test]
-    fn test_truncate_with_markers() {
-        use crate::tui::ui::{truncate_with_markers, visible_text_len};
+    fn test_truncate_spans() {
+        use crate::tui::ui::{truncate_spans, visible_text_len};
 
-        let text = "⟨INLINE_CODE⟩PageDown/PageUp⟨/INLINE_CODE⟩";
+        let code = vec![InlineSpan::code("PageDown/PageUp")];
         // Width 15 is exact length of visible text
-        let truncated = truncate_with_markers(text, 15);
-        assert_eq!(truncated, "⟨INLINE_CODE⟩PageDown/PageUp⟨/INLINE_CODE⟩");
+        assert_eq!(truncate_spans(&code, 15), code);
 
-        // Width 10 cuts it short -> should still have closing marker
-        let truncated_short = truncate_with_markers(text, 10);
-        assert_eq!(truncated_short, "⟨INLINE_CODE⟩PageDown/P⟨/INLINE_CODE⟩");
+        // Width 10 cuts it short but keeps the code style
+        assert_eq!(
+            truncate_spans(&code, 10),
+            vec![InlineSpan::code("PageDown/P")]
+        );
 
         // Check normal text mix
-        let mixed = "Start ⟨INLINE_CODE⟩code⟨/INLINE_CODE⟩ end";
-        let mixed_trunc = truncate_with_markers(mixed, 8); // "Start co"
-        assert_eq!(mixed_trunc, "Start ⟨INLINE_CODE⟩co⟨/INLINE_CODE⟩");
+        let mixed = vec![
+            InlineSpan::plain("Start "),
+            InlineSpan::code("code"),
+            InlineSpan::plain(" end"),
+        ];
+        assert_eq!(
+            truncate_spans(&mixed, 8), // "Start co"
+            vec![InlineSpan::plain("Start "), InlineSpan::code("co")]
+        );
 
         // Test CJK characters
         let cjk_text = "日本語テスト";
         assert_eq!(visible_text_len(cjk_text), 12); // 6 chars * 2 width = 12
 
         // Truncate CJK (width 12 -> 8) "日本語テ"
-        let cjk_trunc = truncate_with_markers(cjk_text, 8);
-        assert_eq!(cjk_trunc, "日本語テ");
+        let cjk = vec![InlineSpan::plain(cjk_text)];
+        assert_eq!(plain_text(&truncate_spans(&cjk, 8)), "日本語テ");
 
         // Truncate CJK odd width (width 12 -> 7)
         // "日本語" is width 6. "日本語テ" is width 8.
         // Width 7 should allow "日本語" (6) but not "テ" (adds 2 -> 8).
-        let cjk_odd = truncate_with_markers(cjk_text, 7);
-        assert_eq!(cjk_odd, "日本語");
+        assert_eq!(plain_text(&truncate_spans(&cjk, 7)), "日本語");
 
         // Test ambiguous width characters (arrows)
         // With width() (not cjk), arrows should be width 1
@@ -186,8 +215,8 @@ mod tests {
         // j(1) + space(1) + /(1) + space(1) + ↓(1) = 5
         assert_eq!(visible_text_len(arrow_text), 5);
 
-        let arrow_trunc = truncate_with_markers(arrow_text, 4);
-        assert_eq!(arrow_trunc, "j / ");
+        let arrow = vec![InlineSpan::plain(arrow_text)];
+        assert_eq!(plain_text(&truncate_spans(&arrow, 4)), "j / ");
     }
 
     #[test]
@@ -268,35 +297,28 @@ mod tests {
 
     #[test]
     fn test_truncate_edge_cases() {
-        use crate::tui::ui::truncate_with_markers;
+        use crate::tui::ui::truncate_spans;
 
-        // Empty string
-        let result = truncate_with_markers("", 10);
-        assert_eq!(result, "");
+        // Empty input
+        assert!(truncate_spans(&[], 10).is_empty());
 
         // Zero max_visible
-        let result = truncate_with_markers("text", 0);
-        assert_eq!(result, "");
+        assert!(truncate_spans(&[InlineSpan::plain("text")], 0).is_empty());
 
         // Very large max_visible (larger than text)
-        let result = truncate_with_markers("short", 100);
-        assert_eq!(result, "short");
+        let short = vec![InlineSpan::plain("short")];
+        assert_eq!(truncate_spans(&short, 100), short);
 
-        // Only markers
-        let result = truncate_with_markers("⟨INLINE_CODE⟩⟨/INLINE_CODE⟩", 10);
-        assert_eq!(result, "⟨INLINE_CODE⟩⟨/INLINE_CODE⟩");
-
-        // Marker at the end (truncated mid-marker should add closing tag)
-        let result = truncate_with_markers("text⟨INLINE_CODE⟩", 10);
-        assert_eq!(result, "text⟨INLINE_CODE⟩⟨/INLINE_CODE⟩");
+        // Empty code span disappears instead of leaving a dangling style
+        assert!(truncate_spans(&[InlineSpan::code("")], 10).is_empty());
 
         // Only whitespace
-        let result = truncate_with_markers("     ", 5);
-        assert_eq!(result, "     ");
+        let spaces = vec![InlineSpan::plain("     ")];
+        assert_eq!(truncate_spans(&spaces, 5), spaces);
 
         // Control characters
-        let result = truncate_with_markers("text\t\n", 10);
-        assert!(result.contains("text"));
+        let result = truncate_spans(&[InlineSpan::plain("text\t\n")], 10);
+        assert!(plain_text(&result).contains("text"));
     }
 
     #[test]
@@ -340,4 +362,241 @@ mod tests {
 
         assert!(!doc.parsed_lines.is_empty());
     }
+
+    #[test]
+    fn test_footnotes_collected_at_end() {
+        let markdown = r#"First[^b] then[^a] and [site](https://a.example).
+
+[^a]: Note A with [inner](https://b.example).
+
+[^b]: Note B.
+
+    Second paragraph.
+
+[^unused]: Never referenced.
+
+Again[^b].
+"#;
+
+        let highlighter = CodeHighlighter::new("base16-ocean.dark".to_string());
+        let doc =
+            MarkdownDocument::parse(PathBuf::from("test.md"), markdown.to_string(), &highlighter)
+                .unwrap();
+
+        // 参照マーカーは最初に参照された順に番号が振られる
+        let ParsedLine::Text { content } = &doc.parsed_lines[0] else {
+            panic!("expected paragraph, got {:?}", doc.parsed_lines[0]);
+        };
+        assert_eq!(plain_text(content), "First¹ then² and site.");
+        assert_eq!(content[1].footnote, Some(1));
+        assert_eq!(content[3].footnote, Some(2));
+
+        let footnotes: Vec<_> = doc
+            .parsed_lines
+            .iter()
+            .filter_map(|line| match line {
+                ParsedLine::FootnoteDefinition {
+                    number,
+                    label,
+                    content,
+                } => Some((*number, label.as_str(), plain_text(content))),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            footnotes,
+            [
+                (1, "b", "Note B.\nSecond paragraph.".to_string()),
+                (2, "a", "Note A with inner.".to_string()),
+            ]
+        );
+        assert!(matches!(
+            doc.parsed_lines.last(),
+            Some(ParsedLine::FootnoteDefinition { number: 2, .. })
+        ));
+        assert_eq!(
+            doc.footnote(1).map(plain_text),
+            Some("Note B.\nSecond paragraph.".to_string())
+        );
+
+        // 脚注内のリンクは文末に移動した位置の順で番号が振られる
+        let urls: Vec<_> = doc.links.iter().map(|l| l.url.as_str()).collect();
+        assert_eq!(urls, ["https://a.example", "https://b.example"]);
+    }
+
+    #[test]
+    fn test_math_parsing() {
+        let markdown = r#"Energy $E = mc^2$ and $\unknown{x}$ here.
+
+$$
+\frac{a}{b}
+$$
+"#;
+
+        let highlighter = CodeHighlighter::new("base16-ocean.dark".to_string());
+        let doc =
+            MarkdownDocument::parse(PathBuf::from("test.md"), markdown.to_string(), &highlighter)
+                .unwrap();
+
+        let ParsedLine::Text { content } = &doc.parsed_lines[0] else {
+            panic!("expected paragraph, got {:?}", doc.parsed_lines[0]);
+        };
+        assert_eq!(
+            plain_text(content),
+            r"Energy E = mc² and $\unknown{x}$ here."
+        );
+        assert!(content[1].style.math);
+        // 組版できないインライン数式はソースをコードとして表示する
+        assert!(content[3].style.code);
+
+        assert!(matches!(
+            &doc.parsed_lines[1],
+            ParsedLine::Math { source, .. } if source == r"\frac{a}{b}"
+        ));
+    }
+
+    #[test]
+    fn test_nested_blocks_keep_their_containers() {
+        use crate::markdown::{Block, ContainerKind};
+
+        let markdown = r#"- Item
+
+  ```rust
+  fn main() {}
+  ```
+
+> Quote
+> - in quote
+
+> [!WARNING]
+> Careful.
+>
+> | a |
+> |---|
+> | 1 |
+
+First paragraph.
+
+Second paragraph.
+"#;
+
+        let highlighter = CodeHighlighter::new("base16-ocean.dark".to_string());
+        let doc =
+            MarkdownDocument::parse(PathBuf::from("test.md"), markdown.to_string(), &highlighter)
+                .unwrap();
+
+        assert!(matches!(
+            &doc.blocks[0],
+            Block::Container {
+                kind: ContainerKind::List { .. },
+                ..
+            }
+        ));
+        assert_eq!(doc.parsed_lines.len(), doc.nesting.len());
+
+        let kinds = |i: usize| -> Vec<&ContainerKind> {
+            doc.nesting[i].iter().map(|entry| &entry.kind).collect()
+        };
+
+        // コードブロックはリスト項目の中に残る
+        assert!(matches!(&doc.parsed_lines[1], ParsedLine::Code { .. }));
+        assert!(matches!(
+            kinds(1)[..],
+            [ContainerKind::List { .. }, ContainerKind::ListItem { .. }]
+        ));
+
+        assert!(matches!(
+            &doc.parsed_lines[2],
+            ParsedLine::BlockQuote { .. }
+        ));
+        assert!(matches!(&doc.parsed_lines[3], ParsedLine::ListItem { .. }));
+        assert!(matches!(
+            kinds(3)[..],
+            [ContainerKind::BlockQuote, ContainerKind::List { .. }]
+        ));
+
+        // 段落以外を含むアラートは中身を入れ子で持つ
+        assert!(matches!(&doc.parsed_lines[5], ParsedLine::Table { .. }));
+        assert!(matches!(kinds(5)[..], [ContainerKind::Alert(_)]));
+        assert!(doc.nesting[4][0].first && doc.nesting[5][0].last);
+
+        // 連続する段落は別々の行になる
+        let texts: Vec<String> = doc.parsed_lines[6..]
+            .iter()
+            .filter_map(|line| match line {
+                ParsedLine::Text { content } => Some(plain_text(content)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(texts, ["First paragraph.", "Second paragraph."]);
+    }
+
+    #[test]
+    fn test_alert_without_body_is_kept() {
+        use crate::markdown::parser::AlertType;
+
+        let markdown = "> [!WARNING]\n\nafter\n";
+
+        let highlighter = CodeHighlighter::new("base16-ocean.dark".to_string());
+        let doc =
+            MarkdownDocument::parse(PathBuf::from("test.md"), markdown.to_string(), &highlighter)
+                .unwrap();
+
+        assert!(matches!(
+            &doc.parsed_lines[0],
+            ParsedLine::Alert {
+                alert_type: AlertType::Warning,
+                content,
+            } if content.is_empty()
+        ));
+        assert!(matches!(&doc.parsed_lines[1], ParsedLine::Text { .. }));
+    }
+
+    #[test]
+    fn test_front_matter_is_stripped() {
+        let markdown = "---\ntitle: Front\n---\n# Heading\n";
+
+        let highlighter = CodeHighlighter::new("base16-ocean.dark".to_string());
+        let doc =
+            MarkdownDocument::parse(PathBuf::from("test.md"), markdown.to_string(), &highlighter)
+                .unwrap();
+
+        assert_eq!(doc.title(), Some("Front"));
+        assert_eq!(doc.display_title(), "Front");
+        assert_eq!(doc.parsed_lines.len(), 1);
+        // 行番号はフロントマターを含むファイル全体で数える
+        assert!(matches!(
+            doc.parsed_lines[0],
+            ParsedLine::Heading { line_num: 4, .. }
+        ));
+    }
+
+    #[test]
+    fn test_fold_section_and_keys() {
+        let markdown = "# A\n\n```sh\nls\n```\n\n## B\n\ntext[^1]\n\n| x |\n|---|\n| 1 |\n\n# C\n\n[^1]: note\n";
+
+        let highlighter = CodeHighlighter::new("base16-ocean.dark".to_string());
+        let mut doc =
+            MarkdownDocument::parse(PathBuf::from("test.md"), markdown.to_string(), &highlighter)
+                .unwrap();
+
+        // A, code, B, text, table, C, 区切り線, 脚注
+        assert_eq!(doc.section_end(0), 5);
+        assert_eq!(doc.section_end(2), 5);
+        // 最後のセクションに文末の脚注は含めない
+        assert!(!doc.is_foldable(5));
+
+        doc.set_folded(0, true);
+        assert!((1..5).all(|block| doc.is_hidden(block)));
+        assert!(!doc.is_hidden(5));
+        assert_eq!(doc.folded_line_count(0), 12);
+
+        assert_eq!(doc.fold_key(0), Some(FoldKey::Section("a".to_string())));
+        let table = FoldKey::Block {
+            section: Some("b".to_string()),
+            nth: 0,
+        };
+        assert_eq!(doc.fold_key(4), Some(table.clone()));
+        assert_eq!(doc.fold_block(&table), Some(4));
+    }
 }
@@ -0,0 +1,77 @@
+use std::ops::Range;
+
+/// ブロックに対応するソース上の位置
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SourceRange {
+    /// バイト範囲
+    pub bytes: Range<usize>,
+    /// 開始行（1始まり）
+    pub start_line: usize,
+    /// 終了行（1始まり、この行を含む）
+    pub end_line: usize,
+}
+
+impl SourceRange {
+    /// `self` の先頭から `other` の末尾までの範囲
+    pub fn join(&self, other: &SourceRange) -> SourceRange {
+        SourceRange {
+            bytes: self.bytes.start..other.bytes.end.max(self.bytes.start),
+            start_line: self.start_line,
+            end_line: other.end_line.max(self.start_line),
+        }
+    }
+
+    pub fn contains_line(&self, line: usize) -> bool {
+        (self.start_line..=self.end_line).contains(&line)
+    }
+}
+
+/// バイト位置から行番号を引くための索引
+#[derive(Debug, Clone)]
+pub struct LineIndex {
+    /// 各行の先頭のバイト位置
+    line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+    pub fn new(content: &str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { line_starts }
+    }
+
+    /// `offset` を含む行（1始まり）
+    pub fn line_of(&self, offset: usize) -> usize {
+        self.line_starts.partition_point(|&start| start <= offset)
+    }
+
+    pub fn range(&self, bytes: Range<usize>) -> SourceRange {
+        // 末尾の改行は次の行に数えない
+        let last = bytes.end.saturating_sub(1).max(bytes.start);
+        SourceRange {
+            start_line: self.line_of(bytes.start),
+            end_line: self.line_of(last),
+            bytes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_index() {
+        let index = LineIndex::new("# Title\n\ntext\nmore\n");
+
+        assert_eq!(index.line_of(0), 1);
+        assert_eq!(index.line_of(8), 2);
+        assert_eq!(index.line_of(9), 3);
+
+        let range = index.range(9..19);
+        assert_eq!((range.start_line, range.end_line), (3, 4));
+        assert!(range.contains_line(4));
+        assert!(!range.contains_line(5));
+    }
+}
@@ -1,6 +1,89 @@
+use std::collections::HashMap;
+
 #[derive(Debug, Clone)]
 pub struct TocEntry {
     pub level: usize,
     pub title: String,
     pub line_number: usize,
+    /// GitHub互換のアンカー名（`#` なし）
+    pub anchor: String,
+}
+
+/// 見出しテキストからGitHub形式のアンカー名を作る
+///
+/// 小文字化し、英数字・`-`・`_` 以外を取り除いて空白を `-` に置き換える。
+pub fn slugify(title: &str) -> String {
+    title
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                Some(c)
+            } else if c.is_whitespace() {
+                Some('-')
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// ドキュメント内で一意なアンカー名を払い出す（github-slugger と同じ規則）
+#[derive(Debug, Default)]
+pub struct SlugCounter {
+    occurrences: HashMap<String, usize>,
+}
+
+impl SlugCounter {
+    /// 見出しテキストからアンカー名を作る。重複時は `-1`, `-2` を付ける
+    pub fn slug(&mut self, title: &str) -> String {
+        let original = slugify(title);
+        let mut slug = original.clone();
+
+        while self.occurrences.contains_key(&slug) {
+            let count = self.occurrences.entry(original.clone()).or_insert(0);
+            *count += 1;
+            slug = format!("{original}-{count}");
+        }
+
+        self.occurrences.insert(slug.clone(), 0);
+        slug
+    }
+
+    /// `{#custom-id}` で明示されたアンカー名を予約する
+    pub fn reserve(&mut self, id: &str) -> String {
+        self.occurrences.entry(id.to_string()).or_insert(0);
+        id.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slugify_github_rules() {
+        assert_eq!(slugify("Installation"), "installation");
+        assert_eq!(slugify("What's new in v2.0?"), "whats-new-in-v20");
+        assert_eq!(slugify("foo_bar  baz"), "foo_bar--baz");
+        assert_eq!(slugify("インストール方法"), "インストール方法");
+        assert_eq!(slugify("🚀 Quick start"), "-quick-start");
+    }
+
+    #[test]
+    fn test_duplicate_slugs_get_suffixes() {
+        let mut counter = SlugCounter::default();
+        assert_eq!(counter.slug("Usage"), "usage");
+        assert_eq!(counter.slug("Usage"), "usage-1");
+        assert_eq!(counter.slug("Usage 1"), "usage-1-1");
+        assert_eq!(counter.slug("Usage"), "usage-2");
+    }
+
+    #[test]
+    fn test_reserved_ids_are_not_reused() {
+        let mut counter = SlugCounter::default();
+        assert_eq!(counter.reserve("setup"), "setup");
+        assert_eq!(counter.slug("Setup"), "setup-1");
+    }
 }
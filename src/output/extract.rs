@@ -0,0 +1,137 @@
+//! コードブロックを取り出す（`mdv extract`）
+//!
+//! info string に `file=` / `title=` があるブロックは、同じ名前のものをつなげて一つのファイルにできる。
+
+use crate::error::MdError;
+use crate::markdown::{CodeInfo, MarkdownDocument, ParsedLine};
+use std::path::{Component, Path, PathBuf};
+
+/// 取り出すブロックの条件（指定しないものは絞り込まない）
+#[derive(Debug, Clone, Default)]
+pub struct CodeFilter {
+    /// info string の言語（大文字小文字を区別しない）
+    pub lang: Option<String>,
+    /// 名前にこの文字列を含む最初の見出しのセクション
+    pub heading: Option<String>,
+    /// 条件に合うブロックのうち何番目か（1始まり）
+    pub index: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CodeBlock {
+    pub info: CodeInfo,
+    pub content: String,
+    /// ソースの行番号（1始まり）
+    pub line: usize,
+}
+
+impl CodeBlock {
+    /// 書き出すファイルの名前（`file=`、なければ `title=`）
+    pub fn file_name(&self) -> Option<&str> {
+        self.info.file_name()
+    }
+}
+
+/// 条件に合うコードブロックを文書の順に返す。一つもなければエラー
+pub fn extract(
+    document: &MarkdownDocument,
+    filter: &CodeFilter,
+) -> Result<Vec<CodeBlock>, MdError> {
+    let range = match &filter.heading {
+        Some(heading) => {
+            let block = heading_block(document, heading)
+                .ok_or_else(|| MdError::HeadingNotFound(heading.clone()))?;
+            block..document.section_end(block)
+        }
+        None => 0..document.parsed_lines.len(),
+    };
+
+    let mut blocks: Vec<CodeBlock> = range
+        .filter_map(|block| match &document.parsed_lines[block] {
+            ParsedLine::Code { lang, content, .. } => Some(CodeBlock {
+                info: lang.as_deref().map(CodeInfo::parse).unwrap_or_default(),
+                content: content.clone(),
+                line: document.source_line(block),
+            }),
+            _ => None,
+        })
+        .filter(|block| match &filter.lang {
+            Some(lang) => block
+                .info
+                .language
+                .as_ref()
+                .is_some_and(|language| language.eq_ignore_ascii_case(lang)),
+            None => true,
+        })
+        .collect();
+
+    if let Some(index) = filter.index {
+        blocks = blocks.into_iter().skip(index - 1).take(1).collect();
+    }
+    if blocks.is_empty() {
+        return Err(MdError::NoCodeBlocks);
+    }
+    Ok(blocks)
+}
+
+/// 名前に `heading` を含む最初の見出しのブロック（ビューアーの `--heading` と同じ探し方）
+fn heading_block(document: &MarkdownDocument, heading: &str) -> Option<usize> {
+    let heading = heading.to_lowercase();
+    let toc_index = document
+        .toc
+        .iter()
+        .position(|entry| entry.title.to_lowercase().contains(&heading))?;
+    document
+        .parsed_lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| matches!(line, ParsedLine::Heading { .. }))
+        .nth(toc_index)
+        .map(|(block, _)| block)
+}
+
+/// ブロックの内容をつなげる（どのブロックも改行で終わるようにする）
+pub fn concat<'a>(blocks: impl IntoIterator<Item = &'a CodeBlock>) -> String {
+    let mut text = String::new();
+    for block in blocks {
+        text.push_str(&block.content);
+        if !text.is_empty() && !text.ends_with('\n') {
+            text.push('\n');
+        }
+    }
+    text
+}
+
+/// ファイル名ごとに内容をつなげる（最初に現れた順。名前のないブロックは `None` にまとめる）
+pub fn group_by_file(blocks: &[CodeBlock]) -> Vec<(Option<String>, String)> {
+    let mut names: Vec<Option<&str>> = Vec::new();
+    for block in blocks {
+        if !names.contains(&block.file_name()) {
+            names.push(block.file_name());
+        }
+    }
+    names
+        .into_iter()
+        .map(|name| {
+            let content = concat(blocks.iter().filter(|block| block.file_name() == name));
+            (name.map(str::to_string), content)
+        })
+        .collect()
+}
+
+/// `dir` 以下の `name` に書く。絶対パスや `..` で `dir` の外を指す名前はエラー
+pub fn write_file(dir: &Path, name: &str, content: &str) -> Result<PathBuf, MdError> {
+    let relative = Path::new(name);
+    let inside = relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    if !inside || name.is_empty() {
+        return Err(MdError::UnsafeOutputPath(name.to_string()));
+    }
+    let path = dir.join(relative);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, content)?;
+    Ok(path)
+}
@@ -0,0 +1,656 @@
+//! 文書をひとつの HTML ファイルに書き出す（`mdv export --html`）
+//!
+//! CSS・ハイライト済みのコード・ローカルの画像をすべて埋め込むので、ファイルひとつで配布できる。
+//! 色は UI テーマから取る。
+
+use crate::markdown::parser::AlertType;
+use crate::markdown::{
+    math, Alignment, Block, CodeHighlighter, CodeInfo, ContainerKind, InlineSpan, ListMarker,
+    MarkdownDocument, ParsedLine,
+};
+use crate::tui::ui::alert_decoration;
+use crate::tui::UiTheme;
+use base64::Engine;
+use ratatui::style::Color;
+use std::fmt::Write;
+use std::path::Path;
+
+/// `document` を CSS と画像を埋め込んだ HTML にする
+pub fn export_html(
+    document: &MarkdownDocument,
+    theme: &UiTheme,
+    highlighter: &CodeHighlighter,
+) -> String {
+    let title = document.title().map(str::to_string).unwrap_or_else(|| {
+        document
+            .path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    });
+    let base_dir = document.path.parent().unwrap_or(Path::new(""));
+    let mut writer = HtmlWriter {
+        theme,
+        base_dir,
+        html: String::new(),
+    };
+    writer.blocks(&document.blocks);
+
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{}</title>\n<style>\n{}</style>\n</head>\n<body>\n",
+        escape(&title),
+        stylesheet(theme, highlighter)
+    );
+    if !document.toc.is_empty() {
+        html.push_str("<nav class=\"toc\">\n<ul>\n");
+        for entry in &document.toc {
+            let _ = writeln!(
+                html,
+                "<li class=\"toc-{}\"><a href=\"#{}\">{}</a></li>",
+                entry.level.clamp(1, 6),
+                escape(&entry.anchor),
+                escape(&entry.title)
+            );
+        }
+        html.push_str("</ul>\n</nav>\n");
+    }
+    html.push_str("<main>\n");
+    html.push_str(&writer.html);
+    html.push_str("</main>\n</body>\n</html>\n");
+    html
+}
+
+struct HtmlWriter<'a> {
+    theme: &'a UiTheme,
+    /// 相対パスの画像を探すディレクトリ
+    base_dir: &'a Path,
+    html: String,
+}
+
+impl HtmlWriter<'_> {
+    fn blocks(&mut self, blocks: &[Block]) {
+        for block in blocks {
+            match block {
+                Block::Leaf { line, .. } => self.leaf(line),
+                Block::Container { kind, children, .. } => self.container(kind, children),
+            }
+        }
+    }
+
+    fn container(&mut self, kind: &ContainerKind, children: &[Block]) {
+        match kind {
+            ContainerKind::BlockQuote => {
+                self.html.push_str("<blockquote>\n");
+                self.blocks(children);
+                self.html.push_str("</blockquote>\n");
+            }
+            ContainerKind::Alert(alert_type) => {
+                let (icon, label, _, _) = alert_decoration(*alert_type, self.theme);
+                let _ = writeln!(
+                    self.html,
+                    "<div class=\"alert alert-{}\">\n<p class=\"alert-title\">{icon} {label}</p>",
+                    alert_type.name()
+                );
+                self.blocks(children);
+                self.html.push_str("</div>\n");
+            }
+            ContainerKind::List { start } => {
+                let ordered = children.iter().any(|child| {
+                    matches!(
+                        child,
+                        Block::Container {
+                            kind: ContainerKind::ListItem {
+                                marker: ListMarker::Ordered { .. },
+                                ..
+                            },
+                            ..
+                        }
+                    )
+                });
+                match (ordered, start) {
+                    (true, Some(start)) if *start != 1 => {
+                        let _ = writeln!(self.html, "<ol start=\"{start}\">");
+                    }
+                    (true, _) => self.html.push_str("<ol>\n"),
+                    (false, _) => self.html.push_str("<ul>\n"),
+                }
+                self.blocks(children);
+                self.html
+                    .push_str(if ordered { "</ol>\n" } else { "</ul>\n" });
+            }
+            ContainerKind::ListItem { checked, .. } => {
+                match checked {
+                    Some(checked) => {
+                        let _ = write!(
+                            self.html,
+                            "<li class=\"task\"><input type=\"checkbox\" disabled{}> ",
+                            if *checked { " checked" } else { "" }
+                        );
+                    }
+                    None => self.html.push_str("<li>"),
+                }
+                self.blocks(children);
+                self.html.push_str("</li>\n");
+            }
+            ContainerKind::FootnoteDefinition { number, .. } => {
+                let _ = writeln!(
+                    self.html,
+                    "<div class=\"footnote\" id=\"fn-{number}\">\n<span class=\"footnote-number\">{number}.</span>"
+                );
+                self.blocks(children);
+                self.html.push_str("</div>\n");
+            }
+            ContainerKind::Details { open, .. } => {
+                let _ = writeln!(self.html, "<details{}>", if *open { " open" } else { "" });
+                // `<summary>` がなければ最初の行の代わりに "Details" と表示する
+                match children.first() {
+                    Some(Block::Leaf {
+                        line: ParsedLine::Details { summary, .. },
+                        ..
+                    }) => {
+                        let _ = writeln!(self.html, "<summary>{}</summary>", inline(summary));
+                        self.blocks(&children[1..]);
+                    }
+                    _ => {
+                        self.html.push_str("<summary>Details</summary>\n");
+                        self.blocks(children);
+                    }
+                }
+                self.html.push_str("</details>\n");
+            }
+            ContainerKind::Aligned(alignment) => {
+                let _ = writeln!(self.html, "<div{}>", align_attribute(*alignment));
+                self.blocks(children);
+                self.html.push_str("</div>\n");
+            }
+        }
+    }
+
+    fn leaf(&mut self, line: &ParsedLine) {
+        match line {
+            ParsedLine::Heading {
+                level,
+                text,
+                anchor,
+                ..
+            } => {
+                let level = (*level).clamp(1, 6);
+                let _ = writeln!(
+                    self.html,
+                    "<h{level} id=\"{}\">{}</h{level}>",
+                    escape(anchor),
+                    inline(text)
+                );
+            }
+            ParsedLine::Code {
+                lang, highlighted, ..
+            } => {
+                self.html.push_str("<div class=\"code\">");
+                // 言語と `file=` / `title=` の名前だけを出す（ほかの属性は出さない）
+                let info = lang.as_deref().map(CodeInfo::parse).unwrap_or_default();
+                let label: Vec<&str> = info
+                    .language
+                    .as_deref()
+                    .into_iter()
+                    .chain(info.file_name())
+                    .collect();
+                if !label.is_empty() {
+                    let _ = write!(
+                        self.html,
+                        "<span class=\"code-lang\">{}</span>",
+                        escape(&label.join(" · "))
+                    );
+                }
+                self.html.push_str("<pre><code>");
+                for (index, spans) in highlighted.iter().enumerate() {
+                    if index > 0 {
+                        self.html.push('\n');
+                    }
+                    for span in spans {
+                        let color = span.style.foreground;
+                        let _ = write!(
+                            self.html,
+                            "<span style=\"color:#{:02x}{:02x}{:02x}\">{}</span>",
+                            color.r,
+                            color.g,
+                            color.b,
+                            escape(&span.text)
+                        );
+                    }
+                }
+                self.html.push_str("</code></pre></div>\n");
+            }
+            ParsedLine::Text { content } => {
+                let _ = writeln!(self.html, "<p>{}</p>", inline(content));
+            }
+            ParsedLine::ListItem {
+                content, checked, ..
+            } => {
+                let checkbox = match checked {
+                    Some(true) => "<input type=\"checkbox\" disabled checked> ",
+                    Some(false) => "<input type=\"checkbox\" disabled> ",
+                    None => "",
+                };
+                let _ = writeln!(self.html, "<li>{checkbox}{}</li>", inline(content));
+            }
+            ParsedLine::Table {
+                headers,
+                rows,
+                alignments,
+            } => {
+                let align = |column: usize| {
+                    align_attribute(alignments.get(column).copied().unwrap_or(Alignment::None))
+                };
+                self.html.push_str("<table>\n<thead>\n<tr>");
+                for (column, cell) in headers.iter().enumerate() {
+                    let _ = write!(self.html, "<th{}>{}</th>", align(column), inline(cell));
+                }
+                self.html.push_str("</tr>\n</thead>\n<tbody>\n");
+                for row in rows {
+                    self.html.push_str("<tr>");
+                    for (column, cell) in row.iter().enumerate() {
+                        let _ = write!(self.html, "<td{}>{}</td>", align(column), inline(cell));
+                    }
+                    self.html.push_str("</tr>\n");
+                }
+                self.html.push_str("</tbody>\n</table>\n");
+            }
+            ParsedLine::BlockQuote { content } => {
+                let _ = writeln!(
+                    self.html,
+                    "<blockquote><p>{}</p></blockquote>",
+                    inline(content)
+                );
+            }
+            ParsedLine::Alert {
+                alert_type,
+                content,
+            } => {
+                let (icon, label, _, _) = alert_decoration(*alert_type, self.theme);
+                let _ = writeln!(
+                    self.html,
+                    "<div class=\"alert alert-{}\">\n<p class=\"alert-title\">{icon} {label}</p>\n<p>{}</p>\n</div>",
+                    alert_type.name(),
+                    inline(content)
+                );
+            }
+            ParsedLine::Image { alt_text, url } => {
+                let source = image_source(url, self.base_dir);
+                let src = if is_safe_url(&source, true) {
+                    format!(" src=\"{}\"", escape(&source))
+                } else {
+                    String::new()
+                };
+                let _ = writeln!(
+                    self.html,
+                    "<p class=\"image\"><img{src} alt=\"{}\"></p>",
+                    escape(alt_text)
+                );
+            }
+            ParsedLine::Math { source, .. } => {
+                let rendered = math::render_display(source)
+                    .map(|rows| rows.join("\n"))
+                    .unwrap_or_else(|_| source.clone());
+                let _ = writeln!(self.html, "<pre class=\"math\">{}</pre>", escape(&rendered));
+            }
+            ParsedLine::FootnoteDefinition {
+                number, content, ..
+            } => {
+                let _ = writeln!(
+                    self.html,
+                    "<div class=\"footnote\" id=\"fn-{number}\"><span class=\"footnote-number\">{number}.</span> {}</div>",
+                    inline(content)
+                );
+            }
+            ParsedLine::Details { summary, open, .. } => {
+                let _ = writeln!(
+                    self.html,
+                    "<details{}><summary>{}</summary></details>",
+                    if *open { " open" } else { "" },
+                    inline(summary)
+                );
+            }
+            ParsedLine::HorizontalRule => self.html.push_str("<hr>\n"),
+            ParsedLine::Empty => {}
+        }
+    }
+}
+
+/// インラインの装飾を HTML のタグにする
+fn inline(spans: &[InlineSpan]) -> String {
+    let mut html = String::new();
+    for span in spans {
+        let mut text = escape(&span.text).replace('\n', "<br>");
+        let style = span.style;
+        if let Some(number) = span.footnote {
+            let _ = write!(
+                html,
+                "<sup class=\"footnote-ref\"><a href=\"#fn-{number}\">{number}</a></sup>"
+            );
+            continue;
+        }
+        for (enabled, tag, class) in [
+            (style.code, "code", ""),
+            (style.kbd, "kbd", ""),
+            (style.math, "span", " class=\"math\""),
+            (style.html, "span", " class=\"html\""),
+            (style.emphasis, "em", ""),
+            (style.strong, "strong", ""),
+            (style.strikethrough, "del", ""),
+        ] {
+            if enabled {
+                text = format!("<{tag}{class}>{text}</{tag}>");
+            }
+        }
+        if let Some(link) = span
+            .link
+            .as_ref()
+            .filter(|link| is_safe_url(&link.url, false))
+        {
+            text = format!("<a href=\"{}\">{text}</a>", escape(&link.url));
+        }
+        html.push_str(&text);
+    }
+    html
+}
+
+/// 共有する HTML に書いてよい URL か
+///
+/// `http` / `https` / `mailto`、相対パスと `#` だけを通す。`javascript:` などは通さない。
+/// `data:image/*` は画像の `src`（`image` が true）だけ許す。
+fn is_safe_url(url: &str, image: bool) -> bool {
+    // ブラウザは URL 中の空白や制御文字を無視するので、取り除いてからスキームを見る
+    let url: String = url
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace() && !c.is_control())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    let scheme = match url.split_once(':') {
+        Some((scheme, _)) if !scheme.contains(['/', '?', '#']) => scheme,
+        // `:` がないか、パスやクエリの中にしかなければ相対 URL
+        _ => return true,
+    };
+    match scheme {
+        "http" | "https" | "mailto" => true,
+        "data" => image && url.starts_with("data:image/"),
+        _ => false,
+    }
+}
+
+/// ローカルの画像は data URI にして埋め込む。読めなければ元の URL のまま
+fn image_source(url: &str, base_dir: &Path) -> String {
+    if url.contains("://") || url.starts_with("data:") {
+        return url.to_string();
+    }
+    let path = base_dir.join(url.split(['?', '#']).next().unwrap_or(url));
+    let mime = match path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase)
+        .as_deref()
+    {
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("svg") => "image/svg+xml",
+        Some("webp") => "image/webp",
+        Some("bmp") => "image/bmp",
+        Some("ico") => "image/x-icon",
+        _ => return url.to_string(),
+    };
+    match std::fs::read(&path) {
+        Ok(bytes) => format!(
+            "data:{mime};base64,{}",
+            base64::engine::general_purpose::STANDARD.encode(bytes)
+        ),
+        Err(_) => url.to_string(),
+    }
+}
+
+fn align_attribute(alignment: Alignment) -> &'static str {
+    match alignment {
+        Alignment::None => "",
+        Alignment::Left => " style=\"text-align:left\"",
+        Alignment::Center => " style=\"text-align:center\"",
+        Alignment::Right => " style=\"text-align:right\"",
+    }
+}
+
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// UI テーマの色を使ったスタイルシート
+fn stylesheet(theme: &UiTheme, highlighter: &CodeHighlighter) -> String {
+    let text = css_color(theme.text.primary());
+    // 端末の背景色はわからないので、文字色が明るければ暗い背景にする
+    let background = if luminance(theme.text.primary()) > 0.5 {
+        "#1e1e1e"
+    } else {
+        "#ffffff"
+    };
+    let code_background = highlighter
+        .background()
+        .map(|color| format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b))
+        .unwrap_or_else(|| "#2b303b".to_string());
+
+    let mut css = format!(
+        "body {{ margin: 0; background: {background}; color: {text}; \
+         font-family: -apple-system, BlinkMacSystemFont, \"Segoe UI\", Helvetica, Arial, sans-serif; line-height: 1.6; }}\n\
+         main {{ max-width: 860px; margin: 0 auto; padding: 2rem; }}\n\
+         nav.toc {{ position: fixed; top: 0; left: 0; bottom: 0; width: 260px; overflow-y: auto; \
+         padding: 1rem; box-sizing: border-box; border-right: 1px solid {toc_border}; font-size: 0.9rem; }}\n\
+         nav.toc ul {{ list-style: none; margin: 0; padding: 0; }}\n\
+         nav.toc a {{ color: {toc}; text-decoration: none; }}\n\
+         nav.toc a:hover {{ color: {toc_selected}; }}\n\
+         nav.toc + main {{ margin-left: 260px; }}\n\
+         @media (max-width: 900px) {{ nav.toc {{ position: static; width: auto; border-right: none; }} nav.toc + main {{ margin-left: auto; }} }}\n\
+         a {{ color: {link}; }}\n\
+         p, li {{ color: {text}; }}\n\
+         li > p {{ margin: 0; }}\n\
+         li.task {{ list-style: none; }}\n\
+         li.task > p {{ display: inline; }}\n\
+         li::marker {{ color: {bullet}; }}\n\
+         input[type=checkbox] {{ accent-color: {checked}; }}\n\
+         code {{ color: {inline_code}; background: {inline_code_bg}; padding: 0.1em 0.3em; border-radius: 3px; }}\n\
+         kbd {{ color: {kbd}; background: {kbd_bg}; padding: 0.1em 0.4em; border-radius: 3px; font-family: monospace; }}\n\
+         .math {{ color: {math}; }}\n\
+         pre.math {{ text-align: center; font-family: monospace; }}\n\
+         .html {{ color: {muted}; }}\n\
+         div.code {{ position: relative; border: 1px solid {code_border}; border-radius: 4px; background: {code_background}; margin: 1em 0; }}\n\
+         div.code pre {{ margin: 0; padding: 1em; overflow-x: auto; }}\n\
+         div.code code {{ background: none; padding: 0; color: inherit; }}\n\
+         .code-lang {{ position: absolute; top: 0.2em; right: 0.5em; font-size: 0.8em; color: {lang_label}; }}\n\
+         blockquote {{ margin: 1em 0; padding: 0 1em; border-left: 4px solid {quote_border}; }}\n\
+         blockquote p {{ color: {quote}; }}\n\
+         table {{ border-collapse: collapse; margin: 1em 0; }}\n\
+         th, td {{ border: 1px solid {table_border}; padding: 0.3em 0.8em; }}\n\
+         th {{ color: {table_header}; }}\n\
+         td {{ color: {table_cell}; }}\n\
+         img {{ max-width: 100%; }}\n\
+         hr {{ border: none; border-top: 1px solid {border}; }}\n\
+         .alert {{ margin: 1em 0; padding: 0 1em; border-left: 4px solid; }}\n\
+         .alert-title {{ font-weight: bold; }}\n\
+         .footnote {{ font-size: 0.9em; }}\n\
+         .footnote-number, .footnote-ref a {{ color: {footnote}; }}\n",
+        toc = css_color(theme.toc.normal()),
+        toc_selected = css_color(theme.toc.selected()),
+        toc_border = css_color(theme.border.secondary()),
+        link = css_color(theme.link.text()),
+        bullet = css_color(theme.list.bullet()),
+        checked = css_color(theme.list.checked()),
+        inline_code = css_color(theme.inline_code.foreground()),
+        inline_code_bg = css_color(theme.inline_code.background()),
+        kbd = css_color(theme.kbd.foreground()),
+        kbd_bg = css_color(theme.kbd.background()),
+        math = css_color(theme.math.text()),
+        muted = css_color(theme.text.muted()),
+        code_border = css_color(theme.code.border()),
+        lang_label = css_color(theme.code.lang_label()),
+        quote_border = css_color(theme.blockquote.border()),
+        quote = css_color(theme.blockquote.text()),
+        table_border = css_color(theme.table.border()),
+        table_header = css_color(theme.table.header()),
+        table_cell = css_color(theme.table.cell()),
+        border = css_color(theme.border.primary()),
+        footnote = css_color(theme.footnote.marker()),
+    );
+    for (level, color) in [
+        theme.heading.h1(),
+        theme.heading.h2(),
+        theme.heading.h3(),
+        theme.heading.h4(),
+        theme.heading.h5(),
+        theme.heading.h6(),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let color = css_color(color);
+        let _ = writeln!(
+            css,
+            "h{level} {{ color: {color}; }}\nnav.toc .toc-{level} {{ padding-left: {indent}em; }}",
+            level = level + 1,
+            indent = level
+        );
+    }
+    for alert_type in [
+        AlertType::Note,
+        AlertType::Tip,
+        AlertType::Important,
+        AlertType::Warning,
+        AlertType::Caution,
+    ] {
+        let (_, _, border, text) = alert_decoration(alert_type, theme);
+        let _ = writeln!(
+            css,
+            ".alert-{class} {{ border-color: {border}; }}\n.alert-{class} .alert-title {{ color: {border}; }}\n.alert-{class} p {{ color: {text}; }}",
+            class = alert_type.name(),
+            border = css_color(border),
+            text = css_color(text)
+        );
+    }
+    css
+}
+
+/// 端末の色を CSS の色にする（名前付きの色は一般的な端末の配色で近似する）
+pub fn css_color(color: Color) -> String {
+    let (r, g, b) = rgb(color);
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+fn rgb(color: Color) -> (u8, u8, u8) {
+    match color {
+        Color::Reset | Color::White => (0xff, 0xff, 0xff),
+        Color::Black => (0x00, 0x00, 0x00),
+        Color::Red => (0xcd, 0x31, 0x31),
+        Color::Green => (0x0d, 0xbc, 0x79),
+        Color::Yellow => (0xe5, 0xe5, 0x10),
+        Color::Blue => (0x24, 0x72, 0xc8),
+        Color::Magenta => (0xbc, 0x3f, 0xbc),
+        Color::Cyan => (0x11, 0xa8, 0xcd),
+        Color::Gray => (0xe5, 0xe5, 0xe5),
+        Color::DarkGray => (0x66, 0x66, 0x66),
+        Color::LightRed => (0xf1, 0x4c, 0x4c),
+        Color::LightGreen => (0x23, 0xd1, 0x8b),
+        Color::LightYellow => (0xf5, 0xf5, 0x43),
+        Color::LightBlue => (0x3b, 0x8e, 0xea),
+        Color::LightMagenta => (0xd6, 0x70, 0xd6),
+        Color::LightCyan => (0x29, 0xb8, 0xdb),
+        Color::Rgb(r, g, b) => (r, g, b),
+        Color::Indexed(index) => indexed_rgb(index),
+    }
+}
+
+/// 256色パレットの色（0〜15 は名前付きの色と同じ）
+fn indexed_rgb(index: u8) -> (u8, u8, u8) {
+    const NAMED: [Color; 16] = [
+        Color::Black,
+        Color::Red,
+        Color::Green,
+        Color::Yellow,
+        Color::Blue,
+        Color::Magenta,
+        Color::Cyan,
+        Color::Gray,
+        Color::DarkGray,
+        Color::LightRed,
+        Color::LightGreen,
+        Color::LightYellow,
+        Color::LightBlue,
+        Color::LightMagenta,
+        Color::LightCyan,
+        Color::White,
+    ];
+    match index {
+        0..=15 => rgb(NAMED[index as usize]),
+        16..=231 => {
+            let level = |value: u8| if value == 0 { 0 } else { 55 + value * 40 };
+            let index = index - 16;
+            (level(index / 36), level(index / 6 % 6), level(index % 6))
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            (gray, gray, gray)
+        }
+    }
+}
+
+/// 0.0（黒）〜 1.0（白）の明るさ
+fn luminance(color: Color) -> f32 {
+    let (r, g, b) = rgb(color);
+    (0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32) / 255.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_css_color() {
+        assert_eq!(css_color(Color::Rgb(1, 2, 255)), "#0102ff");
+        assert_eq!(css_color(Color::Indexed(196)), "#ff0000");
+        assert_eq!(css_color(Color::Indexed(232)), "#080808");
+        assert_eq!(css_color(Color::Indexed(4)), css_color(Color::Blue));
+    }
+
+    #[test]
+    fn test_is_safe_url() {
+        for url in [
+            "https://example.com",
+            "mailto:me@example.com",
+            "#usage",
+            "docs/a.md",
+            "a.md?x=1:2",
+        ] {
+            assert!(is_safe_url(url, false), "{url}");
+        }
+        for url in [
+            "javascript:alert(1)",
+            " JavaScript:alert(1)",
+            "java\tscript:alert(1)",
+            "vbscript:msgbox",
+            "file:///etc/passwd",
+            "data:image/png;base64,AA==",
+        ] {
+            assert!(!is_safe_url(url, false), "{url}");
+        }
+        assert!(is_safe_url("data:image/png;base64,AA==", true));
+        assert!(!is_safe_url("data:text/html,<script>", true));
+    }
+}
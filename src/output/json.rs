@@ -0,0 +1,452 @@
+//! 解析した文書を JSON で出力する（`--dump-json` / `--dump-toc`）
+//!
+//! 内部の型をそのまま出すと実装の都合で形が変わるので、出力用の型に写してから書き出す。
+//! ファイルの数によらず `{"version": 1, "documents": [...]}` の形にする。
+//! 形を変えるときは `SCHEMA_VERSION` を上げる。
+
+use crate::markdown::{
+    plain_text, Alignment, CodeInfo, ContainerKind, InlineSpan, ListMarker, MarkdownDocument,
+    MetadataValue, Nesting, ParsedLine, TocEntry,
+};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// 出力する JSON の形の版
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// `--dump-toc` の出力形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum TocFormat {
+    Json,
+    /// 入れ子の箇条書きにした見出しへのリンク
+    Markdown,
+}
+
+/// 出力全体（ファイルごとの結果を `documents` に入れる）
+#[derive(Debug, Serialize)]
+pub struct JsonOutput<T> {
+    pub version: u32,
+    pub documents: Vec<T>,
+}
+
+impl<T> JsonOutput<T> {
+    pub fn new(documents: Vec<T>) -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+            documents,
+        }
+    }
+
+    /// 整形した JSON（末尾に改行を付ける）
+    pub fn to_json(&self) -> String
+    where
+        T: Serialize,
+    {
+        // 文字列と数値だけなので失敗しない
+        serde_json::to_string_pretty(self).unwrap_or_default() + "\n"
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct JsonDocument {
+    pub path: String,
+    /// フロントマターの `title`
+    pub title: Option<String>,
+    /// フロントマター（記述順）
+    pub metadata: Option<Value>,
+    pub toc: Vec<JsonTocEntry>,
+    /// 表示の単位になるブロック（`parsed_lines` と、本文が複数のブロックのアラートの見出し）
+    pub blocks: Vec<JsonBlock>,
+    pub links: Vec<JsonLink>,
+}
+
+/// `--dump-toc` のファイルごとの結果
+#[derive(Debug, Serialize)]
+pub struct JsonToc {
+    pub path: String,
+    pub toc: Vec<JsonTocEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JsonTocEntry {
+    pub level: usize,
+    pub title: String,
+    pub anchor: String,
+    /// ソースの行番号（1始まり）
+    pub line: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JsonBlock {
+    /// ソースの行番号（1始まり）
+    pub line: usize,
+    /// 囲んでいるコンテナ（外側から）
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub containers: Vec<String>,
+    #[serde(flatten)]
+    pub kind: JsonBlockKind,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JsonBlockKind {
+    Heading {
+        level: usize,
+        text: String,
+        anchor: String,
+    },
+    Code {
+        /// info string の最初の語
+        lang: Option<String>,
+        /// info string の `file=` や `title=` などの属性（記述順）
+        #[serde(skip_serializing_if = "Map::is_empty")]
+        attributes: Map<String, Value>,
+        content: String,
+    },
+    Paragraph {
+        text: String,
+    },
+    ListItem {
+        /// 入れ子の深さ（0始まり）
+        indent: usize,
+        /// `-` や `1.` など
+        marker: String,
+        checked: Option<bool>,
+        text: String,
+    },
+    Table {
+        headers: Vec<String>,
+        rows: Vec<Vec<String>>,
+        /// 列ごとの `none` / `left` / `center` / `right`
+        alignments: Vec<&'static str>,
+    },
+    Blockquote {
+        text: String,
+    },
+    Alert {
+        /// `note` / `tip` / `important` / `warning` / `caution`
+        kind: &'static str,
+        text: String,
+    },
+    Image {
+        alt: String,
+        url: String,
+    },
+    Math {
+        source: String,
+    },
+    Footnote {
+        number: usize,
+        label: String,
+        text: String,
+    },
+    Details {
+        summary: String,
+        open: bool,
+    },
+    HorizontalRule,
+    Empty,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JsonLink {
+    pub url: String,
+    pub text: String,
+    /// リンクを含む `blocks` のインデックス
+    pub block: usize,
+}
+
+impl JsonDocument {
+    pub fn new(document: &MarkdownDocument) -> Self {
+        let (blocks, block_indices) = blocks(document);
+        Self {
+            path: document.path.display().to_string(),
+            title: document.title().map(str::to_string),
+            metadata: document.metadata.as_ref().map(|metadata| {
+                Value::Object(
+                    metadata
+                        .entries
+                        .iter()
+                        .map(|(key, value)| (key.clone(), metadata_value(value)))
+                        .collect(),
+                )
+            }),
+            toc: toc(&document.toc),
+            blocks,
+            links: document
+                .links
+                .iter()
+                .map(|link| JsonLink {
+                    url: link.url.clone(),
+                    text: link.text.clone(),
+                    block: block_indices[link.line_index],
+                })
+                .collect(),
+        }
+    }
+}
+
+/// `parsed_lines` をブロックにする。`parsed_lines` の添字ごとのブロックの添字も返す
+///
+/// アラートはどれも `alert` ブロックを一つ持つ（`text` は先頭の段落）。
+/// 2つ目以降の段落やコードブロックなどは `containers` に `alert:*` を持つ後続のブロックになる。
+fn blocks(document: &MarkdownDocument) -> (Vec<JsonBlock>, Vec<usize>) {
+    let mut blocks = Vec::with_capacity(document.parsed_lines.len());
+    let mut block_indices = Vec::with_capacity(document.parsed_lines.len());
+    for (index, (line, nesting)) in document
+        .parsed_lines
+        .iter()
+        .zip(&document.nesting)
+        .enumerate()
+    {
+        let containers: Vec<String> = nesting.iter().map(container_name).collect();
+        let mut kind = Some(block_kind(line));
+        for (depth, entry) in nesting.iter().enumerate() {
+            let ContainerKind::Alert(alert_type) = &entry.kind else {
+                continue;
+            };
+            if !entry.first {
+                continue;
+            }
+            // 先頭の段落はアラートのブロックにする
+            let text = match (depth + 1 == nesting.len(), line) {
+                (true, ParsedLine::Text { content }) => {
+                    kind = None;
+                    plain_text(content)
+                }
+                _ => String::new(),
+            };
+            blocks.push(JsonBlock {
+                line: document.source_line(index),
+                containers: containers[..depth].to_vec(),
+                kind: JsonBlockKind::Alert {
+                    kind: alert_type.name(),
+                    text,
+                },
+            });
+        }
+        if let Some(kind) = kind {
+            blocks.push(JsonBlock {
+                line: document.source_line(index),
+                containers,
+                kind,
+            });
+        }
+        block_indices.push(blocks.len() - 1);
+    }
+    (blocks, block_indices)
+}
+
+fn toc(entries: &[TocEntry]) -> Vec<JsonTocEntry> {
+    entries
+        .iter()
+        .map(|entry| JsonTocEntry {
+            level: entry.level,
+            title: entry.title.clone(),
+            anchor: entry.anchor.clone(),
+            line: entry.line_number,
+        })
+        .collect()
+}
+
+/// 文書を整形した JSON にする
+pub fn dump_documents(documents: &[MarkdownDocument]) -> String {
+    JsonOutput::new(documents.iter().map(JsonDocument::new).collect()).to_json()
+}
+
+/// 目次を JSON か入れ子の箇条書きにする
+pub fn dump_toc(documents: &[MarkdownDocument], format: TocFormat) -> String {
+    match format {
+        TocFormat::Json => JsonOutput::new(
+            documents
+                .iter()
+                .map(|document| JsonToc {
+                    path: document.path.display().to_string(),
+                    toc: toc(&document.toc),
+                })
+                .collect(),
+        )
+        .to_json(),
+        TocFormat::Markdown => documents
+            .iter()
+            .map(|document| markdown_toc(&document.toc))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }
+}
+
+/// 最も浅い見出しを一段目にした `- [見出し](#anchor)` の入れ子のリスト
+pub fn markdown_toc(entries: &[TocEntry]) -> String {
+    let top = entries.iter().map(|entry| entry.level).min().unwrap_or(1);
+    // 見出しのレベルが飛んでも一段ずつしか深くしない
+    let mut depths: Vec<usize> = Vec::new();
+    let mut markdown = String::new();
+    for entry in entries {
+        while depths.last().is_some_and(|&level| level >= entry.level) {
+            depths.pop();
+        }
+        let depth = depths.len().min(entry.level - top);
+        depths.push(entry.level);
+        let title = entry.title.replace('[', "\\[").replace(']', "\\]");
+        markdown.push_str(&format!(
+            "{}- [{title}](#{})\n",
+            "  ".repeat(depth),
+            entry.anchor
+        ));
+    }
+    markdown
+}
+
+fn block_kind(line: &ParsedLine) -> JsonBlockKind {
+    let text = |spans: &[InlineSpan]| plain_text(spans);
+    match line {
+        ParsedLine::Heading {
+            level,
+            text: spans,
+            anchor,
+            ..
+        } => JsonBlockKind::Heading {
+            level: *level,
+            text: text(spans),
+            anchor: anchor.clone(),
+        },
+        ParsedLine::Code { lang, content, .. } => {
+            let info = lang.as_deref().map(CodeInfo::parse).unwrap_or_default();
+            JsonBlockKind::Code {
+                lang: info.language,
+                attributes: info
+                    .attributes
+                    .into_iter()
+                    .map(|(key, value)| (key, Value::String(value)))
+                    .collect(),
+                content: content.clone(),
+            }
+        }
+        ParsedLine::Text { content } => JsonBlockKind::Paragraph {
+            text: text(content),
+        },
+        ParsedLine::ListItem {
+            indent,
+            content,
+            checked,
+            marker,
+        } => JsonBlockKind::ListItem {
+            indent: *indent,
+            marker: match marker {
+                ListMarker::Bullet(bullet) => bullet.to_string(),
+                ListMarker::Ordered { number, delimiter } => format!("{number}{delimiter}"),
+            },
+            checked: *checked,
+            text: text(content),
+        },
+        ParsedLine::Table {
+            headers,
+            rows,
+            alignments,
+        } => JsonBlockKind::Table {
+            headers: headers.iter().map(|cell| text(cell)).collect(),
+            rows: rows
+                .iter()
+                .map(|row| row.iter().map(|cell| text(cell)).collect())
+                .collect(),
+            alignments: alignments
+                .iter()
+                .map(|alignment| match alignment {
+                    Alignment::None => "none",
+                    Alignment::Left => "left",
+                    Alignment::Center => "center",
+                    Alignment::Right => "right",
+                })
+                .collect(),
+        },
+        ParsedLine::BlockQuote { content } => JsonBlockKind::Blockquote {
+            text: text(content),
+        },
+        ParsedLine::Alert {
+            alert_type,
+            content,
+        } => JsonBlockKind::Alert {
+            kind: alert_type.name(),
+            text: text(content),
+        },
+        ParsedLine::Image { alt_text, url } => JsonBlockKind::Image {
+            alt: alt_text.clone(),
+            url: url.clone(),
+        },
+        ParsedLine::Math { source, .. } => JsonBlockKind::Math {
+            source: source.clone(),
+        },
+        ParsedLine::FootnoteDefinition {
+            number,
+            label,
+            content,
+        } => JsonBlockKind::Footnote {
+            number: *number,
+            label: label.clone(),
+            text: text(content),
+        },
+        ParsedLine::Details { summary, open, .. } => JsonBlockKind::Details {
+            summary: text(summary),
+            open: *open,
+        },
+        ParsedLine::HorizontalRule => JsonBlockKind::HorizontalRule,
+        ParsedLine::Empty => JsonBlockKind::Empty,
+    }
+}
+
+fn container_name(nesting: &Nesting) -> String {
+    match &nesting.kind {
+        ContainerKind::BlockQuote => "blockquote".to_string(),
+        ContainerKind::Alert(alert_type) => format!("alert:{}", alert_type.name()),
+        ContainerKind::List { .. } => "list".to_string(),
+        ContainerKind::ListItem { .. } => "list_item".to_string(),
+        ContainerKind::FootnoteDefinition { .. } => "footnote".to_string(),
+        ContainerKind::Details { .. } => "details".to_string(),
+        ContainerKind::Aligned(_) => "aligned".to_string(),
+    }
+}
+
+fn metadata_value(value: &MetadataValue) -> Value {
+    match value {
+        MetadataValue::Null => Value::Null,
+        MetadataValue::Bool(value) => Value::Bool(*value),
+        MetadataValue::Integer(value) => Value::from(*value),
+        MetadataValue::Float(value) => Value::from(*value),
+        MetadataValue::String(value) => Value::String(value.clone()),
+        MetadataValue::List(values) => Value::Array(values.iter().map(metadata_value).collect()),
+        MetadataValue::Map(entries) => Value::Object(
+            entries
+                .iter()
+                .map(|(key, value)| (key.clone(), metadata_value(value)))
+                .collect::<Map<_, _>>(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(level: usize, title: &str) -> TocEntry {
+        TocEntry {
+            level,
+            title: title.to_string(),
+            line_number: 1,
+            anchor: crate::markdown::slugify(title),
+        }
+    }
+
+    #[test]
+    fn test_markdown_toc_nests_by_level() {
+        let entries = [
+            entry(2, "Install"),
+            entry(4, "From source"),
+            entry(3, "Homebrew"),
+            entry(2, "Usage [advanced]"),
+        ];
+        assert_eq!(
+            markdown_toc(&entries),
+            "- [Install](#install)\n  - [From source](#from-source)\n  - [Homebrew](#homebrew)\n- [Usage \\[advanced\\]](#usage-advanced)\n"
+        );
+    }
+}
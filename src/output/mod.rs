@@ -0,0 +1,7 @@
+pub mod extract;
+pub mod html;
+pub mod json;
+pub mod print;
+
+pub use json::TocFormat;
+pub use print::{ColorMode, PrintOptions};
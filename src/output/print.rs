@@ -0,0 +1,453 @@
+//! 端末の画面を使わずに、描画した文書を標準出力に書き出す（`--print`）
+//!
+//! TUI と同じ `DocumentLayout` の行を、色は ANSI エスケープシーケンスにして出力する。
+
+use crate::markdown::link::for_each_inline_mut;
+use crate::markdown::{math, MarkdownDocument, ParsedLine};
+use crate::tui::layout::DocumentLayout;
+use crate::tui::UiTheme;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use std::fmt::Write;
+use std::io::IsTerminal;
+use unicode_width::UnicodeWidthChar;
+
+/// 幅を指定されず、端末の幅もわからないときの幅
+pub const DEFAULT_WIDTH: u16 = 80;
+
+/// 色を付けて出力するかどうか
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ColorMode {
+    /// 標準出力が端末で、`NO_COLOR` が設定されていなければ色を付ける
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    pub fn enabled(self) -> bool {
+        match self {
+            ColorMode::Auto => {
+                std::io::stdout().is_terminal()
+                    && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+            }
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrintOptions {
+    /// 1行の桁数
+    pub width: u16,
+    pub color: bool,
+    /// 罫線や記号を ASCII 文字で描く
+    pub ascii: bool,
+}
+
+impl PrintOptions {
+    /// 指定がなければ端末の幅、`$COLUMNS`、`DEFAULT_WIDTH` の順に使う
+    pub fn default_width() -> u16 {
+        if std::io::stdout().is_terminal() {
+            if let Ok((width, _)) = crossterm::terminal::size() {
+                return width;
+            }
+        }
+        std::env::var("COLUMNS")
+            .ok()
+            .and_then(|columns| columns.parse().ok())
+            .filter(|&columns| columns > 0)
+            .unwrap_or(DEFAULT_WIDTH)
+    }
+}
+
+/// `document` を描画したテキスト（各行は改行で終わる）
+pub fn render_document(
+    document: &MarkdownDocument,
+    theme: &UiTheme,
+    options: PrintOptions,
+) -> String {
+    // 本文は組む前に ASCII にする（置き換えで幅が変わっても枠や表の罫線が揃う）
+    let ascii_document;
+    let document = if options.ascii {
+        ascii_document = transliterate_document(document);
+        &ascii_document
+    } else {
+        document
+    };
+    // レイアウトの幅は本文の枠を含む
+    let layout = DocumentLayout::build(document, theme, "", options.width.saturating_add(2));
+    let mut output = String::new();
+    for line in layout.rows(0..layout.row_count()) {
+        write_line(&mut output, line, options);
+        output.push('\n');
+    }
+    output
+}
+
+fn write_line(output: &mut String, line: &Line, options: PrintOptions) {
+    let start = output.len();
+    for span in &line.spans {
+        let text = if options.ascii {
+            to_ascii(&span.content)
+        } else {
+            span.content.to_string()
+        };
+        let style = line.style.patch(span.style);
+        let codes = if options.color {
+            sgr_codes(style)
+        } else {
+            Vec::new()
+        };
+        if codes.is_empty() {
+            output.push_str(&text);
+        } else {
+            let _ = write!(output, "\x1b[{}m{text}\x1b[0m", codes.join(";"));
+        }
+    }
+    // 色がなければ背景を塗るための空白は要らない
+    if !options.color {
+        let trimmed = output[start..].trim_end().len();
+        output.truncate(start + trimmed);
+    }
+}
+
+/// スタイルを表す SGR のパラメータ
+fn sgr_codes(style: Style) -> Vec<String> {
+    const MODIFIERS: [(Modifier, &str); 7] = [
+        (Modifier::BOLD, "1"),
+        (Modifier::DIM, "2"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::SLOW_BLINK, "5"),
+        (Modifier::REVERSED, "7"),
+        (Modifier::CROSSED_OUT, "9"),
+    ];
+    let mut codes: Vec<String> = MODIFIERS
+        .iter()
+        .filter(|(modifier, _)| style.add_modifier.contains(*modifier))
+        .map(|(_, code)| code.to_string())
+        .collect();
+    codes.extend(style.fg.and_then(|color| color_code(color, 30)));
+    codes.extend(style.bg.and_then(|color| color_code(color, 40)));
+    codes
+}
+
+/// `base` は前景色なら 30、背景色なら 40
+fn color_code(color: Color, base: u8) -> Option<String> {
+    let code = match color {
+        Color::Reset => return None,
+        Color::Black => base,
+        Color::Red => base + 1,
+        Color::Green => base + 2,
+        Color::Yellow => base + 3,
+        Color::Blue => base + 4,
+        Color::Magenta => base + 5,
+        Color::Cyan => base + 6,
+        Color::Gray => base + 7,
+        Color::DarkGray => base + 60,
+        Color::LightRed => base + 61,
+        Color::LightGreen => base + 62,
+        Color::LightYellow => base + 63,
+        Color::LightBlue => base + 64,
+        Color::LightMagenta => base + 65,
+        Color::LightCyan => base + 66,
+        Color::White => base + 67,
+        Color::Rgb(r, g, b) => return Some(format!("{};2;{r};{g};{b}", base + 8)),
+        Color::Indexed(index) => return Some(format!("{};5;{index}", base + 8)),
+    };
+    Some(code.to_string())
+}
+
+/// 本文の文字を ASCII にした文書
+///
+/// 脚注の参照は `[1]`、ディスプレイ数式は組版せずに TeX のソースをコードブロックとして表示する。
+fn transliterate_document(document: &MarkdownDocument) -> MarkdownDocument {
+    let mut document = document.clone();
+    for line in &mut document.parsed_lines {
+        for_each_inline_mut(line, |spans| {
+            for span in spans {
+                span.text = match span.footnote {
+                    Some(number) => format!("[{number}]"),
+                    None => transliterate(&span.text),
+                };
+            }
+        });
+        match line {
+            ParsedLine::Code {
+                lang,
+                content,
+                highlighted,
+            } => {
+                *lang = lang.as_deref().map(transliterate);
+                *content = transliterate(content);
+                for span in highlighted.iter_mut().flatten() {
+                    span.text = transliterate(&span.text);
+                }
+            }
+            ParsedLine::Math {
+                source,
+                highlighted,
+            } => {
+                let mut highlighted = std::mem::take(highlighted);
+                for span in highlighted.iter_mut().flatten() {
+                    span.text = transliterate(&span.text);
+                }
+                let content = transliterate(source);
+                *line = ParsedLine::Code {
+                    lang: Some("math".to_string()),
+                    content,
+                    highlighted,
+                };
+            }
+            ParsedLine::Image { alt_text, url } => {
+                *alt_text = transliterate(alt_text);
+                *url = transliterate(url);
+            }
+            _ => {}
+        }
+    }
+    document
+}
+
+/// 本文の文字を ASCII にする（幅は変わってもよい）
+///
+/// 上付き・下付き文字は `^2` / `_i`（2文字以上なら `^(10)`）、ギリシャ文字と数式の記号は名前にする。
+/// 置き換えられない文字は幅の数だけ `?` にする。
+pub fn transliterate(text: &str) -> String {
+    let mut ascii = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_ascii() {
+            ascii.push(c);
+            continue;
+        }
+        let script = [
+            ('^', math::from_superscript as fn(char) -> Option<char>),
+            ('_', math::from_subscript),
+        ]
+        .into_iter()
+        .find_map(|(marker, from)| from(c).map(|base| (marker, from, base)));
+        if let Some((marker, from, base)) = script {
+            let mut bases = String::from(base);
+            while let Some(base) = chars.peek().and_then(|&next| from(next)) {
+                bases.push(base);
+                chars.next();
+            }
+            let bases = transliterate(&bases);
+            if bases.chars().count() == 1 {
+                let _ = write!(ascii, "{marker}{bases}");
+            } else {
+                let _ = write!(ascii, "{marker}({bases})");
+            }
+            continue;
+        }
+        match ascii_name(c) {
+            Some(name) => ascii.push_str(name),
+            None => ascii.push_str(&"?".repeat(c.width().unwrap_or(0))),
+        }
+    }
+    ascii
+}
+
+/// 本文によく出る記号・アクセント付きの文字・ギリシャ文字・数式の記号の ASCII 表記
+fn ascii_name(c: char) -> Option<&'static str> {
+    const LATIN: [(&str, &str); 16] = [
+        ("ÀÁÂÃÄÅ", "A"),
+        ("àáâãäå", "a"),
+        ("Ç", "C"),
+        ("ç", "c"),
+        ("ÈÉÊË", "E"),
+        ("èéêë", "e"),
+        ("ÌÍÎÏ", "I"),
+        ("ìíîï", "i"),
+        ("Ñ", "N"),
+        ("ñ", "n"),
+        ("ÒÓÔÕÖØ", "O"),
+        ("òóôõöø", "o"),
+        ("ÙÚÛÜ", "U"),
+        ("ùúûü", "u"),
+        ("Ýý", "Y"),
+        ("ß", "ss"),
+    ];
+    if let Some((_, ascii)) = LATIN.iter().find(|(letters, _)| letters.contains(c)) {
+        return Some(ascii);
+    }
+    Some(match c {
+        // 約物
+        '\u{a0}' | '\u{2002}'..='\u{200a}' | '\u{3000}' => " ",
+        '\u{200b}'..='\u{200d}' | '\u{fe0f}' => "",
+        '‘' | '’' | '′' => "'",
+        '“' | '”' | '″' => "\"",
+        '–' | '−' => "-",
+        '—' => "--",
+        '…' | '⋯' => "...",
+        '•' | '●' | '∙' => "*",
+        '©' => "(c)",
+        '®' => "(R)",
+        '™' => "(TM)",
+        '°' => "deg",
+        '✓' | '✔' => "v",
+        '✗' | '✘' => "x",
+        // ギリシャ文字
+        'α' => "alpha",
+        'β' => "beta",
+        'γ' => "gamma",
+        'δ' => "delta",
+        'ε' | 'ϵ' => "epsilon",
+        'ζ' => "zeta",
+        'η' => "eta",
+        'θ' | 'ϑ' => "theta",
+        'ι' => "iota",
+        'κ' => "kappa",
+        'λ' => "lambda",
+        'μ' | 'µ' => "mu",
+        'ν' => "nu",
+        'ξ' => "xi",
+        'π' | 'ϖ' => "pi",
+        'ρ' | 'ϱ' => "rho",
+        'σ' | 'ς' => "sigma",
+        'τ' => "tau",
+        'υ' => "upsilon",
+        'φ' | 'ϕ' => "phi",
+        'χ' => "chi",
+        'ψ' => "psi",
+        'ω' => "omega",
+        'Γ' => "Gamma",
+        'Δ' => "Delta",
+        'Θ' => "Theta",
+        'Λ' => "Lambda",
+        'Ξ' => "Xi",
+        'Π' => "Pi",
+        'Σ' => "Sigma",
+        'Υ' => "Upsilon",
+        'Φ' => "Phi",
+        'Ψ' => "Psi",
+        'Ω' => "Omega",
+        // 数式の記号
+        '∑' => "sum",
+        '∏' => "prod",
+        '∫' => "int",
+        '∮' => "oint",
+        '√' => "sqrt",
+        '∞' => "inf",
+        '∂' => "d",
+        '∇' => "nabla",
+        '∀' => "forall",
+        '∃' => "exists",
+        '∅' => "{}",
+        '¬' => "!",
+        '±' => "+-",
+        '∓' => "-+",
+        '×' => "x",
+        '÷' => "/",
+        '⋅' | '·' | '∗' | '⋆' => "*",
+        '∘' => "o",
+        '≤' => "<=",
+        '≥' => ">=",
+        '≠' => "!=",
+        '≈' | '≃' | '≅' => "~=",
+        '≡' => "==",
+        '∼' => "~",
+        '∝' => "prop",
+        '≪' => "<<",
+        '≫' => ">>",
+        '→' | '⟶' => "->",
+        '←' | '⟵' => "<-",
+        '↔' => "<->",
+        '⇒' | '⟹' => "=>",
+        '⇐' => "<=",
+        '⇔' => "<=>",
+        '↦' => "|->",
+        '∈' => "in",
+        '∉' => "notin",
+        '∋' => "ni",
+        '⊂' | '⊆' => "subset",
+        '⊃' | '⊇' => "supset",
+        '∪' => "cup",
+        '∩' => "cap",
+        '∖' => "\\",
+        '∧' => "&",
+        '∨' => "|",
+        '⊥' => "_|_",
+        '∥' | '‖' => "||",
+        '∣' => "|",
+        '⟨' => "<",
+        '⟩' => ">",
+        'ℓ' => "l",
+        'ℏ' => "hbar",
+        'ℝ' => "R",
+        'ℕ' => "N",
+        'ℤ' => "Z",
+        'ℚ' => "Q",
+        'ℂ' => "C",
+        _ => return None,
+    })
+}
+
+/// 罫線や記号を同じ幅の ASCII 文字に置き換える（組んだ後の行の枠がずれないようにする）
+///
+/// 置き換えられない文字は幅の数だけ `?` にする。
+pub fn to_ascii(text: &str) -> String {
+    let mut ascii = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_ascii() {
+            ascii.push(c);
+            continue;
+        }
+        // 異体字セレクタの付いた絵文字は2桁で表示する
+        let width = if chars.next_if_eq(&'\u{fe0f}').is_some() {
+            2
+        } else {
+            c.width().unwrap_or(0)
+        };
+        let replacement = match c {
+            '─' | '━' => "-",
+            '═' => "=",
+            '│' | '┃' | '║' | '▌' | '▐' => "|",
+            '\u{2500}'..='\u{257f}' => "+",
+            '•' | '●' => "*",
+            '◦' => "-",
+            '▸' | '▶' | '›' => ">",
+            '▾' | '▼' => "v",
+            '✓' => "x",
+            '…' => "~",
+            'ℹ' => "i",
+            '💡' => "*",
+            '❗' | '⚠' => "!",
+            '🛑' => "X",
+            _ => "?",
+        };
+        let replacement = &replacement[..replacement.len().min(width)];
+        ascii.push_str(replacement);
+        ascii.push_str(&" ".repeat(width - replacement.len()));
+    }
+    ascii
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sgr_codes() {
+        let style = Style::default()
+            .fg(Color::Rgb(1, 2, 3))
+            .bg(Color::DarkGray)
+            .add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+        assert_eq!(sgr_codes(style), vec!["1", "4", "38;2;1;2;3", "100"]);
+        assert!(sgr_codes(Style::default().fg(Color::Reset)).is_empty());
+    }
+
+    #[test]
+    fn test_transliterate_and_to_ascii() {
+        assert_eq!(transliterate("x²⁺ⁱ + aᵢⱼ"), "x^(2+i) + a_(ij)");
+        assert_eq!(transliterate("∑ αβ ≤ ∞"), "sum alphabeta <= inf");
+        assert_eq!(transliterate("日本"), "????");
+        // 組んだ後の置き換えは幅を変えない
+        assert_eq!(to_ascii("│ 💡 …"), "| *  ~");
+        assert_eq!(to_ascii("⚠\u{fe0f}x"), "! x");
+    }
+}
@@ -0,0 +1,3 @@
+pub mod reading_position;
+
+pub use reading_position::{ReadingPosition, StateStore};
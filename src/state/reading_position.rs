@@ -0,0 +1,114 @@
+//! ファイルごとに読んでいた位置をセッションをまたいで残す
+
+use crate::error::MdError;
+use crate::markdown::FoldKey;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// 覚えておくファイルの数（超えたら最も長く開いていないファイルから忘れる）
+pub const MAX_FILES: usize = 500;
+
+/// 読んでいた位置と表示の状態
+///
+/// 位置は画面上端のセクションの見出しからの距離で持つので、見出しより前を編集してもずれない。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReadingPosition {
+    /// 画面上端のセクションの見出しのアンカー（最初の見出しより前なら `None`）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heading: Option<String>,
+    /// 見出し（`heading` が `None` なら文書の先頭）から数えた画面上端のブロック
+    pub blocks: usize,
+    /// そのブロックの中で画面上端にある行
+    pub row: usize,
+    /// 見出しが見つからないときに使うソースの行番号（1始まり）
+    pub line: usize,
+    pub show_toc: bool,
+    pub folds: Vec<FoldKey>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FileState {
+    /// 正規化したファイルのパス
+    path: PathBuf,
+    position: ReadingPosition,
+}
+
+/// 状態ファイルの内容
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StateStore {
+    /// 最後に開いたファイルが末尾
+    #[serde(default)]
+    files: Vec<FileState>,
+}
+
+impl StateStore {
+    /// 状態ファイルの場所（`$XDG_STATE_HOME/mdv/state.toml`、既定は `~/.local/state/mdv/state.toml`）
+    pub fn default_path() -> Option<PathBuf> {
+        let state_home = std::env::var_os("XDG_STATE_HOME")
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+            .or_else(|| {
+                if cfg!(windows) {
+                    std::env::var_os("LOCALAPPDATA").map(PathBuf::from)
+                } else {
+                    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/state"))
+                }
+            })?;
+        Some(state_home.join("mdv").join("state.toml"))
+    }
+
+    /// 状態ファイルを読み込む。ファイルがないか壊れていれば空にする
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|content| toml::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), MdError> {
+        let error =
+            |e: &dyn std::fmt::Display| MdError::StateSaveError(format!("{}: {e}", path.display()));
+        let content = toml::to_string(self).map_err(|e| error(&e))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| error(&e))?;
+        }
+        // 他の mdv が書きかけのファイルを読まないように、別名で書いてから置き換える
+        let temp = path.with_extension(format!("toml.{}", std::process::id()));
+        std::fs::write(&temp, content).map_err(|e| error(&e))?;
+        std::fs::rename(&temp, path).map_err(|e| error(&e))
+    }
+
+    pub fn get(&self, file: &Path) -> Option<&ReadingPosition> {
+        let file = canonical(file);
+        self.files
+            .iter()
+            .find(|entry| entry.path == file)
+            .map(|entry| &entry.position)
+    }
+
+    /// 位置を記録し、最後に開いたファイルにする。`MAX_FILES` を超えたら古いものから忘れる
+    pub fn set(&mut self, file: &Path, position: ReadingPosition) {
+        let file = canonical(file);
+        self.files.retain(|entry| entry.path != file);
+        self.files.push(FileState {
+            path: file,
+            position,
+        });
+        let excess = self.files.len().saturating_sub(MAX_FILES);
+        self.files.drain(..excess);
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+/// シンボリックリンクや相対パスの違いで別のファイルとして覚えないようにする
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
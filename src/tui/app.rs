@@ -1,12 +1,72 @@
-use crate::markdown::MarkdownDocument;
-use crate::tui::ui::calculate_toc_width;
+use crate::markdown::link::for_each_inline;
+use crate::markdown::{ContainerKind, FoldKey, LinkTarget, MarkdownDocument, ParsedLine};
+use crate::state::ReadingPosition;
+use crate::tui::file_tree::FileTree;
+use crate::tui::layout::DocumentLayout;
+use crate::tui::navigation::{Jump, JumpList};
+use crate::tui::search::{SearchDirection, SearchMatch, SearchPrompt, SearchQuery, SearchState};
+use crate::tui::toc::{self, TocRow, TocView};
+use crate::tui::ui::{calculate_toc_width, ScreenAreas, ScreenLink};
 use crate::tui::ThemeManager;
-use crossterm::event::{KeyCode, KeyModifiers};
+use crossterm::event::{KeyCode, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::{Position, Rect};
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// マウスホイール1回でスクロールする行数
+const WHEEL_STEP: usize = 3;
+
+/// ステータスメッセージを表示しておく時間
+const STATUS_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// App の外（ファイル読み込みや外部コマンド）で処理する要求
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppAction {
+    OpenFile {
+        path: PathBuf,
+        anchor: Option<String>,
+    },
+    OpenExternal(String),
+    CopyToClipboard(String),
+}
+
+/// 切り替えて表示していない文書とその表示状態
+struct Buffer {
+    document: MarkdownDocument,
+    scroll_offset: usize,
+    scroll_anchor: Option<(usize, usize)>,
+    layout: Option<DocumentLayout>,
+    toc_selected: usize,
+    toc_offset: usize,
+    toc_view: TocView,
+    toc_synced_offset: Option<usize>,
+    toggled_details: HashSet<usize>,
+    selected_link: Option<usize>,
+}
+
+impl Buffer {
+    fn new(document: MarkdownDocument) -> Self {
+        Self {
+            document,
+            scroll_offset: 0,
+            scroll_anchor: None,
+            layout: None,
+            toc_selected: 0,
+            toc_offset: 0,
+            toc_view: TocView::default(),
+            toc_synced_offset: None,
+            toggled_details: HashSet::new(),
+            selected_link: None,
+        }
+    }
+}
 
 pub struct App<'a> {
     pub document: MarkdownDocument,
+    /// 画面上端に表示するレイアウト上の行
     pub scroll_offset: usize,
-    pub current_line: usize,
     pub show_toc: bool,
     pub toc_selected: usize,
     pub should_quit: bool,
@@ -14,6 +74,56 @@ pub struct App<'a> {
     pub theme_manager: &'a ThemeManager,
     pub toc_width_cache: Option<u16>,
     pub show_help: bool,
+    /// リンクヒントモードで選択中のリンク (`Link::index`)
+    pub selected_link: Option<usize>,
+    /// 直前の描画で画面に表示されたリンク
+    pub link_regions: Vec<ScreenLink>,
+    /// 直前の描画での本文・スクロールバー・目次の位置（マウスの当たり判定に使う）
+    pub screen_areas: ScreenAreas,
+    /// 目次の表示を始める行
+    pub toc_offset: usize,
+    /// 目次の折りたたみと絞り込み
+    pub toc_view: TocView,
+    /// 目次の選択を表示位置に合わせたときのスクロール位置
+    toc_synced_offset: Option<usize>,
+    /// スクロールバーをドラッグ中か
+    dragging_scrollbar: bool,
+    /// 現在の幅で展開した文書（幅・テーマ・文書が変わると作り直す）
+    pub layout: Option<DocumentLayout>,
+    /// レイアウトを作り直した後に表示するブロックとその中の行
+    scroll_anchor: Option<(usize, usize)>,
+    pub pending_action: Option<AppAction>,
+    pub status_message: Option<String>,
+    /// ポップアップで表示中の脚注番号（空なら非表示）
+    pub footnote_popup: Vec<usize>,
+    /// フロントマターのパネルを表示するか
+    pub show_metadata: bool,
+    /// `open` 属性の初期状態から開閉を切り替えた `<details>` の番号
+    pub toggled_details: HashSet<usize>,
+    /// 入力中または直前に確定した検索
+    pub search: Option<SearchState>,
+    /// 検索語の入力中なら `Some`
+    pub search_prompt: Option<SearchPrompt>,
+    /// 表示中のステータスメッセージとその表示を始めた時刻
+    status_since: Option<(String, Instant)>,
+    /// 2文字のコマンド（`za` など）の1文字目
+    pending_key: Option<char>,
+    /// 開いている文書（表示中の文書の位置は `None`）
+    buffers: Vec<Option<Buffer>>,
+    /// 表示中の文書の `buffers` 上の位置
+    current_buffer: usize,
+    /// `:` で入力中のコマンド
+    pub command_line: Option<String>,
+    /// ディレクトリを開いたときのファイルツリー
+    pub file_tree: Option<FileTree>,
+    /// ファイルツリーを表示するか
+    pub show_file_tree: bool,
+    /// 見出しやリンクへのジャンプの履歴
+    jumps: JumpList,
+    /// 履歴で戻る先のファイルを読み込んだら移る位置
+    pending_jump: Option<Jump>,
+    /// リンクで移って閉じた文書の読んでいた位置（終了時に状態ファイルに書く）
+    closed_positions: Vec<(PathBuf, ReadingPosition)>,
 }
 
 impl<'a> App<'a> {
@@ -23,11 +133,11 @@ impl<'a> App<'a> {
         initial_line: Option<usize>,
         theme_manager: &'a ThemeManager,
     ) -> Self {
-        let scroll_offset = initial_line.unwrap_or(0);
+        // `initial_line` はソースの行番号（1始まり）
+        let scroll_anchor = initial_line.map(|line| (document.block_at_line(line), 0));
         Self {
             document,
-            scroll_offset,
-            current_line: scroll_offset,
+            scroll_offset: 0,
             show_toc,
             toc_selected: 0,
             should_quit: false,
@@ -35,29 +145,323 @@ impl<'a> App<'a> {
             theme_manager,
             toc_width_cache: None,
             show_help: false,
+            selected_link: None,
+            link_regions: Vec::new(),
+            screen_areas: ScreenAreas::default(),
+            toc_offset: 0,
+            toc_view: TocView::default(),
+            toc_synced_offset: None,
+            dragging_scrollbar: false,
+            layout: None,
+            scroll_anchor,
+            pending_action: None,
+            status_message: None,
+            footnote_popup: Vec::new(),
+            show_metadata: false,
+            toggled_details: HashSet::new(),
+            search: None,
+            search_prompt: None,
+            status_since: None,
+            pending_key: None,
+            buffers: vec![None],
+            current_buffer: 0,
+            command_line: None,
+            file_tree: None,
+            show_file_tree: false,
+            jumps: JumpList::default(),
+            pending_jump: None,
+            closed_positions: Vec::new(),
         }
     }
 
     pub fn update_document(&mut self, document: MarkdownDocument) {
+        // 表示中のブロックを新しい文書でも表示する
+        self.invalidate_layout();
+        if let Some((block, _)) = &mut self.scroll_anchor {
+            *block = (*block).min(document.parsed_lines.len().saturating_sub(1));
+        }
+        // 折りたたみは見出しのアンカーで引き継ぐ
+        let folds: Vec<FoldKey> = self
+            .document
+            .folded_blocks()
+            .filter_map(|block| self.document.fold_key(block))
+            .collect();
         self.document = document;
         self.invalidate_toc_cache();
-
-        // Adjust scroll_offset if it exceeds the new document length
-        if self.scroll_offset >= self.document.parsed_lines.len() {
-            self.scroll_offset = self.document.parsed_lines.len().saturating_sub(1);
+        self.apply_toggled_details();
+        for key in &folds {
+            if let Some(block) = self.document.fold_block(key) {
+                self.document.set_folded(block, true);
+            }
         }
 
         // Adjust toc_selected if it exceeds the new TOC length
         if self.toc_selected >= self.document.toc.len() {
             self.toc_selected = self.document.toc.len().saturating_sub(1);
         }
+        self.toc_synced_offset = None;
+
+        if self
+            .selected_link
+            .is_some_and(|index| index >= self.document.links.len())
+        {
+            self.selected_link = None;
+        }
+
+        let document = &self.document;
+        self.footnote_popup
+            .retain(|&number| document.footnote(number).is_some());
+    }
+
+    /// 別のドキュメントを開き、先頭（またはアンカー）から表示する
+    pub fn open_document(&mut self, document: MarkdownDocument, anchor: Option<&str>) {
+        // 閉じる文書の位置を残す（標準入力は次に開き直せないので残さない）
+        if !self.document.is_stdin() {
+            let position = self.reading_position();
+            self.closed_positions
+                .push((self.document.path.clone(), position));
+        }
+        self.document = document;
+        self.invalidate_toc_cache();
+        self.layout = None;
+        self.scroll_anchor = None;
+        self.scroll_offset = 0;
+        self.toc_selected = 0;
+        self.toc_view = TocView::default();
+        self.toc_synced_offset = None;
+        self.toggled_details.clear();
+        self.selected_link = None;
+        self.footnote_popup.clear();
+        if let Some(search) = &mut self.search {
+            search.current = None;
+        }
+
+        self.sync_tree_selection();
+
+        // 履歴で戻ってきたファイルなら記録した位置に移る
+        if let Some(jump) = self.pending_jump.take() {
+            if jump.path == self.document.path {
+                self.go_to_jump(&jump);
+            }
+        }
+
+        if let Some(anchor) = anchor {
+            if !self.jump_to_anchor(anchor) {
+                self.status_message = Some(format!("Anchor not found: #{anchor}"));
+            }
+        }
+    }
+
+    /// 文書を新しいバッファとして末尾に加える（表示は切り替えない）
+    pub fn add_buffer(&mut self, document: MarkdownDocument) {
+        self.buffers.push(Some(Buffer::new(document)));
+    }
+
+    pub fn buffer_count(&self) -> usize {
+        self.buffers.len()
+    }
+
+    pub fn current_buffer(&self) -> usize {
+        self.current_buffer
+    }
+
+    /// 開いている文書（バッファの順）
+    pub fn documents(&self) -> impl Iterator<Item = &MarkdownDocument> {
+        self.buffers.iter().map(|buffer| match buffer {
+            Some(buffer) => &buffer.document,
+            None => &self.document,
+        })
+    }
+
+    /// `path` のファイルを開いているバッファ
+    pub fn find_buffer(&self, path: &Path) -> Option<usize> {
+        self.documents()
+            .position(|document| same_file(&document.path, path))
+    }
+
+    /// 表示するバッファを切り替える。スクロール位置や目次の状態はバッファごとに保つ
+    pub fn switch_buffer(&mut self, index: usize) {
+        if index == self.current_buffer || index >= self.buffers.len() {
+            return;
+        }
+        self.swap_buffer(index);
+        self.footnote_popup.clear();
+        self.link_regions.clear();
+        self.dragging_scrollbar = false;
+        // 検索語はバッファをまたいで使う
+        if let Some(search) = &mut self.search {
+            search.current = None;
+        }
+        self.refresh_search();
+        self.sync_tree_selection();
+    }
+
+    /// バッファの文書を読み直した内容に置き換える（表示中のバッファは変えない）
+    pub fn update_buffer(&mut self, index: usize, document: MarkdownDocument) {
+        if index >= self.buffers.len() {
+            return;
+        }
+        let current = self.current_buffer;
+        self.swap_buffer(index);
+        self.update_document(document);
+        self.swap_buffer(current);
+    }
+
+    fn next_buffer(&mut self) {
+        if self.buffers.len() < 2 {
+            self.status_message = Some("Only one file open".to_string());
+            return;
+        }
+        self.switch_buffer((self.current_buffer + 1) % self.buffers.len());
+    }
+
+    fn prev_buffer(&mut self) {
+        if self.buffers.len() < 2 {
+            self.status_message = Some("Only one file open".to_string());
+            return;
+        }
+        let count = self.buffers.len();
+        self.switch_buffer((self.current_buffer + count - 1) % count);
+    }
+
+    /// 表示中の状態を `buffers` に戻し、`index` のバッファの状態を取り出す
+    fn swap_buffer(&mut self, index: usize) {
+        let Some(incoming) = self.buffers.get_mut(index).and_then(Option::take) else {
+            return;
+        };
+        let outgoing = Buffer {
+            document: std::mem::replace(&mut self.document, incoming.document),
+            scroll_offset: std::mem::replace(&mut self.scroll_offset, incoming.scroll_offset),
+            scroll_anchor: std::mem::replace(&mut self.scroll_anchor, incoming.scroll_anchor),
+            layout: std::mem::replace(&mut self.layout, incoming.layout),
+            toc_selected: std::mem::replace(&mut self.toc_selected, incoming.toc_selected),
+            toc_offset: std::mem::replace(&mut self.toc_offset, incoming.toc_offset),
+            toc_view: std::mem::replace(&mut self.toc_view, incoming.toc_view),
+            toc_synced_offset: std::mem::replace(
+                &mut self.toc_synced_offset,
+                incoming.toc_synced_offset,
+            ),
+            toggled_details: std::mem::replace(&mut self.toggled_details, incoming.toggled_details),
+            selected_link: std::mem::replace(&mut self.selected_link, incoming.selected_link),
+        };
+        self.buffers[self.current_buffer] = Some(outgoing);
+        self.current_buffer = index;
+        self.invalidate_toc_cache();
+    }
+
+    /// `:b` の引数（番号か、パスの一部）でバッファを選ぶ。引数がなければ一覧を表示する
+    fn select_buffer(&mut self, argument: &str) {
+        if argument.is_empty() {
+            self.status_message = Some(self.buffer_list());
+            return;
+        }
+        if let Ok(number) = argument.parse::<usize>() {
+            match number
+                .checked_sub(1)
+                .filter(|&index| index < self.buffers.len())
+            {
+                Some(index) => self.switch_buffer(index),
+                None => self.status_message = Some(format!("No such buffer: {number}")),
+            }
+            return;
+        }
+
+        let wanted = argument.to_lowercase();
+        let found: Vec<usize> = self
+            .documents()
+            .enumerate()
+            .filter(|(_, document)| {
+                document
+                    .path
+                    .to_string_lossy()
+                    .to_lowercase()
+                    .contains(&wanted)
+            })
+            .map(|(index, _)| index)
+            .collect();
+        match found.as_slice() {
+            [index] => self.switch_buffer(*index),
+            [] => self.status_message = Some(format!("No matching buffer for {argument}")),
+            _ => self.status_message = Some(format!("More than one match for {argument}")),
+        }
+    }
+
+    /// `1:a.md 2:b.md` 形式のバッファの一覧（表示中のバッファに `*`）
+    fn buffer_list(&self) -> String {
+        self.documents()
+            .enumerate()
+            .map(|(index, document)| {
+                let current = if index == self.current_buffer {
+                    "*"
+                } else {
+                    ""
+                };
+                format!("{}:{}{current}", index + 1, document.path.display())
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn handle_command_key(&mut self, key: KeyCode, modifiers: KeyModifiers) {
+        let Some(command) = &mut self.command_line else {
+            return;
+        };
+        match (key, modifiers) {
+            (KeyCode::Esc, _) => self.command_line = None,
+            (KeyCode::Enter, _) => {
+                if let Some(command) = self.command_line.take() {
+                    self.execute_command(&command);
+                }
+            }
+            (KeyCode::Backspace, _) if command.pop().is_none() => self.command_line = None,
+            (KeyCode::Char(c), modifiers) if !modifiers.contains(KeyModifiers::CONTROL) => {
+                command.push(c);
+            }
+            _ => {}
+        }
+    }
+
+    /// `:` で入力したコマンドを実行する（`:b N` / `:b name` / `:bn` / `:bp` / `:ls` / `:q`）
+    fn execute_command(&mut self, command: &str) {
+        let command = command.trim();
+        // `:b2` のように名前と引数の間の空白は省略できる
+        let name_end = command
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(command.len());
+        let (name, argument) = command.split_at(name_end);
+        match name {
+            "" => {}
+            "b" | "buffer" => self.select_buffer(argument.trim()),
+            "bn" | "bnext" => self.next_buffer(),
+            "bp" | "bprevious" | "bN" | "bNext" => self.prev_buffer(),
+            "ls" | "buffers" | "files" => self.status_message = Some(self.buffer_list()),
+            "q" | "quit" => self.should_quit = true,
+            _ => self.status_message = Some(format!("Not a command: {name}")),
+        }
     }
 
     pub fn handle_key(&mut self, key: KeyCode, modifiers: KeyModifiers) {
-        // ヘルプ表示中は ? と Esc と q のみ受け付ける
+        self.status_message = None;
+
+        if self.search_prompt.is_some() {
+            self.handle_search_key(key, modifiers);
+            return;
+        }
+
+        if self.command_line.is_some() {
+            self.handle_command_key(key, modifiers);
+            return;
+        }
+
+        if self.toc_view.filtering {
+            self.handle_toc_filter_key(key, modifiers);
+            return;
+        }
+
+        // ヘルプ表示中は F1 と Esc と q のみ受け付ける
         if self.show_help {
             match (key, modifiers) {
-                (KeyCode::Char('?'), _) | (KeyCode::Esc, _) | (KeyCode::Char('q'), _) => {
+                (KeyCode::F(1), _) | (KeyCode::Esc, _) | (KeyCode::Char('q'), _) => {
                     self.show_help = false;
                 }
                 _ => {}
@@ -65,10 +469,55 @@ impl<'a> App<'a> {
             return;
         }
 
+        // 脚注ポップアップはスクロール位置を変えずに閉じる
+        if !self.footnote_popup.is_empty() {
+            if matches!(
+                key,
+                KeyCode::Char('f') | KeyCode::Esc | KeyCode::Char('q') | KeyCode::Enter
+            ) {
+                self.footnote_popup.clear();
+            }
+            return;
+        }
+
+        match (self.pending_key.take(), key) {
+            (Some('z'), key) => {
+                self.handle_fold_key(key);
+                return;
+            }
+            (Some('g'), KeyCode::Char('g')) => {
+                self.scroll_to_top();
+                return;
+            }
+            (Some('g'), KeyCode::Char('t')) => {
+                self.next_buffer();
+                return;
+            }
+            (Some('g'), KeyCode::Char('T')) => {
+                self.prev_buffer();
+                return;
+            }
+            // `g` に続かないキーはそのまま受け付ける
+            _ => {}
+        }
+
         match (key, modifiers) {
             (KeyCode::Char('q'), _) | (KeyCode::Char('c'), KeyModifiers::CONTROL) => {
                 self.should_quit = true
             }
+            (KeyCode::Down, _) | (KeyCode::Char('j'), _) if self.file_tree_focused() => {
+                self.move_tree_selection(1);
+            }
+            (KeyCode::Up, _) | (KeyCode::Char('k'), _) if self.file_tree_focused() => {
+                self.move_tree_selection(-1);
+            }
+            (KeyCode::Left, _) | (KeyCode::Char('h'), _) if self.file_tree_focused() => {
+                self.tree_collapse();
+            }
+            (KeyCode::Right, _) | (KeyCode::Char('l'), _) if self.file_tree_focused() => {
+                self.tree_expand();
+            }
+            (KeyCode::Enter, _) if self.file_tree_focused() => self.tree_enter(),
             (KeyCode::Down, _) | (KeyCode::Char('j'), _) => {
                 if self.show_toc {
                     self.toc_down();
@@ -83,10 +532,44 @@ impl<'a> App<'a> {
                     self.scroll_up();
                 }
             }
-            (KeyCode::Char('?'), _) => self.show_help = true,
+            (KeyCode::Left, _) | (KeyCode::Char('h'), _) if self.show_toc => self.toc_collapse(),
+            (KeyCode::Right, _) | (KeyCode::Char('l'), _) if self.show_toc => self.toc_expand(),
+            (KeyCode::F(1), _) => self.show_help = true,
+            (KeyCode::Char('/'), _) if self.show_toc => self.start_toc_filter(),
+            (KeyCode::Esc, _) if self.show_toc && self.toc_view.filter.is_some() => {
+                self.clear_toc_filter();
+            }
+            (KeyCode::Char('/'), _) => self.start_search(SearchDirection::Forward),
+            (KeyCode::Char('?'), _) => self.start_search(SearchDirection::Backward),
+            (KeyCode::Char('n'), _) => self.search_next(false),
+            (KeyCode::Char('N'), KeyModifiers::SHIFT) => self.search_next(true),
             (KeyCode::Char('t'), _) => self.toggle_toc(),
-            (KeyCode::Enter, _) if self.show_toc => self.jump_to_heading(),
-            (KeyCode::Char('g'), _) => self.scroll_to_top(),
+            (KeyCode::Char('e'), _) => self.toggle_file_tree(),
+            (KeyCode::Enter, _) if self.show_toc => {
+                self.record_jump();
+                self.jump_to_heading();
+            }
+            (KeyCode::Tab, _) => self.next_link(),
+            (KeyCode::BackTab, _) => self.prev_link(),
+            (KeyCode::Enter, _) if self.selected_link.is_some() => self.follow_link(),
+            (KeyCode::Esc, _) => {
+                self.selected_link = None;
+                self.search = None;
+            }
+            (KeyCode::Char('o'), KeyModifiers::CONTROL) | (KeyCode::Backspace, _) => {
+                self.jump_back();
+            }
+            // `Ctrl+I` は端末が区別して送れるときだけ届く（多くは `Tab` になる）
+            (KeyCode::Char('i'), KeyModifiers::CONTROL) | (KeyCode::Char(']'), _) => {
+                self.jump_forward();
+            }
+            (KeyCode::Char('y'), _) => self.copy_heading_anchor(),
+            (KeyCode::Char('f'), _) => self.show_footnotes(),
+            (KeyCode::Char('m'), _) => self.toggle_metadata(),
+            (KeyCode::Char('o'), _) => self.toggle_details(),
+            (KeyCode::Char('z'), _) => self.pending_key = Some('z'),
+            (KeyCode::Char('g'), _) => self.pending_key = Some('g'),
+            (KeyCode::Char(':'), _) => self.command_line = Some(String::new()),
             (KeyCode::Char('G'), KeyModifiers::SHIFT) => self.scroll_to_bottom(),
             (KeyCode::PageDown, _) => self.page_down(),
             (KeyCode::PageUp, _) => self.page_up(),
@@ -94,10 +577,90 @@ impl<'a> App<'a> {
         }
     }
 
-    fn scroll_down(&mut self) {
-        if self.scroll_offset < self.document.parsed_lines.len().saturating_sub(1) {
-            self.scroll_offset += 1;
+    /// 現在の幅のレイアウトを用意する。幅かテーマが変わっていれば作り直す
+    pub fn ensure_layout(&mut self, area_width: u16) {
+        let theme_name = self.theme_manager.current_theme_name();
+        if self.layout.as_ref().is_some_and(|layout| {
+            layout.area_width == area_width && layout.theme_name == theme_name
+        }) {
+            return;
         }
+
+        self.invalidate_layout();
+        let layout = DocumentLayout::build(
+            &self.document,
+            &self.theme_manager.current_theme(),
+            theme_name,
+            area_width,
+        );
+        if let Some((block, offset)) = self.scroll_anchor.take() {
+            let rows = layout.block_rows(block);
+            self.scroll_offset =
+                (rows.start + offset).min(rows.end.saturating_sub(1).max(rows.start));
+        }
+        self.layout = Some(layout);
+        self.clamp_scroll();
+        self.refresh_search();
+    }
+
+    /// レイアウトを破棄する。表示中の位置はブロック単位で覚えておく
+    pub fn invalidate_layout(&mut self) {
+        if let Some(layout) = self.layout.take() {
+            if self.scroll_anchor.is_none() {
+                let block = layout.block_at_row(self.scroll_offset);
+                let start = layout.block_rows(block).start;
+                self.scroll_anchor = Some((block, self.scroll_offset.saturating_sub(start)));
+            }
+        }
+    }
+
+    /// 画面上端のブロック
+    pub fn top_block(&self) -> usize {
+        match (&self.scroll_anchor, &self.layout) {
+            (Some((block, _)), _) => *block,
+            (None, Some(layout)) => layout.block_at_row(self.scroll_offset),
+            (None, None) => 0,
+        }
+    }
+
+    /// 一部でも画面に表示されているブロック
+    pub fn visible_blocks(&self) -> Range<usize> {
+        match (&self.scroll_anchor, &self.layout) {
+            (None, Some(layout)) => layout
+                .blocks_in(self.scroll_offset..self.scroll_offset + self.viewport_height.max(1)),
+            _ => {
+                let top = self.top_block();
+                top..(top + 1).min(self.document.parsed_lines.len())
+            }
+        }
+    }
+
+    /// ブロックの先頭を画面上端に表示する
+    pub fn scroll_to_block(&mut self, block: usize) {
+        match &self.layout {
+            Some(layout) => {
+                self.scroll_offset = layout.block_rows(block).start;
+                self.clamp_scroll();
+            }
+            None => self.scroll_anchor = Some((block, 0)),
+        }
+    }
+
+    /// 最後のページが画面いっぱいに表示される位置
+    pub fn max_scroll(&self) -> usize {
+        self.layout.as_ref().map_or(0, |layout| {
+            layout
+                .row_count()
+                .saturating_sub(self.viewport_height.max(1))
+        })
+    }
+
+    fn clamp_scroll(&mut self) {
+        self.scroll_offset = self.scroll_offset.min(self.max_scroll());
+    }
+
+    fn scroll_down(&mut self) {
+        self.scroll_offset = (self.scroll_offset + 1).min(self.max_scroll());
     }
 
     fn scroll_up(&mut self) {
@@ -105,8 +668,7 @@ impl<'a> App<'a> {
     }
 
     fn page_down(&mut self) {
-        self.scroll_offset = (self.scroll_offset + self.viewport_height)
-            .min(self.document.parsed_lines.len().saturating_sub(1));
+        self.scroll_offset = (self.scroll_offset + self.viewport_height).min(self.max_scroll());
     }
 
     fn page_up(&mut self) {
@@ -114,32 +676,1269 @@ impl<'a> App<'a> {
     }
 
     fn scroll_to_top(&mut self) {
+        self.record_jump();
+        self.scroll_anchor = None;
         self.scroll_offset = 0;
     }
 
     fn scroll_to_bottom(&mut self) {
-        self.scroll_offset = self.document.parsed_lines.len().saturating_sub(1);
+        self.record_jump();
+        self.scroll_offset = self.max_scroll();
+    }
+
+    /// レイアウト上の `offset` 行目を画面上端にしたときの位置
+    fn jump_at(&self, offset: usize) -> Jump {
+        let (block, row) = match (&self.scroll_anchor, &self.layout) {
+            (Some(anchor), _) => *anchor,
+            (None, Some(layout)) => {
+                let block = layout.block_at_row(offset);
+                (block, offset.saturating_sub(layout.block_rows(block).start))
+            }
+            (None, None) => (0, 0),
+        };
+        let label = match self
+            .heading_at_block(block)
+            .and_then(|index| self.document.toc.get(index))
+        {
+            Some(entry) => entry.title.clone(),
+            None => "Top".to_string(),
+        };
+        Jump {
+            path: self.document.path.clone(),
+            block,
+            row,
+            label,
+        }
+    }
+
+    /// ジャンプする前に今の位置を履歴に残す
+    fn record_jump(&mut self) {
+        let jump = self.jump_at(self.scroll_offset);
+        self.jumps.push(jump);
+    }
+
+    fn jump_back(&mut self) {
+        let current = self.jump_at(self.scroll_offset);
+        match self.jumps.back(current) {
+            Some(jump) => self.restore_jump(jump),
+            None => self.status_message = Some("Already at oldest position".to_string()),
+        }
+    }
+
+    fn jump_forward(&mut self) {
+        let current = self.jump_at(self.scroll_offset);
+        match self.jumps.forward(current) {
+            Some(jump) => self.restore_jump(jump),
+            None => self.status_message = Some("Already at newest position".to_string()),
+        }
+    }
+
+    /// 履歴の位置に戻る。閉じたファイルなら読み込んでから移る
+    fn restore_jump(&mut self, jump: Jump) {
+        if jump.path == self.document.path {
+            self.go_to_jump(&jump);
+        } else if let Some(index) = self.find_buffer(&jump.path) {
+            self.switch_buffer(index);
+            self.go_to_jump(&jump);
+        } else {
+            self.pending_action = Some(AppAction::OpenFile {
+                path: jump.path.clone(),
+                anchor: None,
+            });
+            self.pending_jump = Some(jump);
+        }
+    }
+
+    /// 表示中の文書で履歴の位置に移る（折りたたまれていれば開く）
+    fn go_to_jump(&mut self, jump: &Jump) {
+        let Some(last) = self.document.parsed_lines.len().checked_sub(1) else {
+            return;
+        };
+        let block = jump.block.min(last);
+        self.reveal_block(block);
+        match &self.layout {
+            Some(layout) => {
+                let rows = layout.block_rows(block);
+                self.scroll_offset = (rows.start + jump.row).min(rows.end.max(rows.start + 1) - 1);
+                self.clamp_scroll();
+            }
+            None => self.scroll_anchor = Some((block, jump.row)),
+        }
+    }
+
+    /// 表示中の文書で読んでいる位置（次に開いたときに戻れるように残す）
+    pub fn reading_position(&self) -> ReadingPosition {
+        let Jump { block, row, .. } = self.jump_at(self.scroll_offset);
+        let heading = self.heading_at_block(block).and_then(|index| {
+            let start = self.heading_block(index)?;
+            Some((self.document.toc.get(index)?.anchor.clone(), start))
+        });
+        let (heading, start) = match heading {
+            Some((anchor, start)) => (Some(anchor), start),
+            None => (None, 0),
+        };
+        ReadingPosition {
+            heading,
+            blocks: block - start,
+            row,
+            line: self.document.source_line(block),
+            show_toc: self.show_toc,
+            folds: self
+                .document
+                .folded_blocks()
+                .filter_map(|block| self.document.fold_key(block))
+                .collect(),
+        }
+    }
+
+    /// リンクで移って閉じた文書の位置（閉じた順）を取り出す
+    pub fn take_closed_positions(&mut self) -> Vec<(PathBuf, ReadingPosition)> {
+        std::mem::take(&mut self.closed_positions)
+    }
+
+    /// 前回読んでいた位置と折りたたみを戻す（目次の表示は呼び出し側で決める）
+    pub fn restore_reading_position(&mut self, position: &ReadingPosition) {
+        let Some(last) = self.document.parsed_lines.len().checked_sub(1) else {
+            return;
+        };
+        self.invalidate_layout();
+        for key in &position.folds {
+            if let Some(block) = self.document.fold_block(key) {
+                self.document.set_folded(block, true);
+            }
+        }
+
+        // 見出しがなくなっていればソースの行番号で探す
+        let start = match &position.heading {
+            Some(anchor) => self
+                .document
+                .toc
+                .iter()
+                .position(|entry| &entry.anchor == anchor)
+                .and_then(|index| self.heading_block(index)),
+            None => Some(0),
+        };
+        let block = match start {
+            Some(start) => start + position.blocks,
+            None => self.document.block_at_line(position.line),
+        };
+        self.scroll_anchor = Some((block.min(last), position.row));
+    }
+
+    /// パンくず（直前にいた位置から今の位置まで、古い順）
+    pub fn breadcrumbs(&self) -> Vec<String> {
+        let label = |jump: &Jump| {
+            if jump.path == self.document.path {
+                jump.label.clone()
+            } else {
+                let file = jump
+                    .path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                format!("{file}: {}", jump.label)
+            }
+        };
+        let history = self.jumps.history();
+        if history.is_empty() {
+            return Vec::new();
+        }
+        history
+            .iter()
+            .map(label)
+            .chain([label(&self.jump_at(self.scroll_offset))])
+            .collect()
+    }
+
+    fn start_search(&mut self, direction: SearchDirection) {
+        let mut query = SearchQuery::new(direction);
+        // 正規表現モードは前回の検索から引き継ぐ
+        query.regex = self
+            .search
+            .as_ref()
+            .is_some_and(|search| search.query.regex);
+        self.search_prompt = Some(SearchPrompt {
+            origin: self.scroll_offset,
+            previous: self.search.take(),
+        });
+        self.search = Some(SearchState::new(query));
+        self.selected_link = None;
+    }
+
+    fn handle_search_key(&mut self, key: KeyCode, modifiers: KeyModifiers) {
+        let Some(search) = &mut self.search else {
+            return;
+        };
+        let query = &mut search.query;
+        match (key, modifiers) {
+            (KeyCode::Esc, _) => self.cancel_search(),
+            (KeyCode::Enter, _) => self.confirm_search(),
+            (KeyCode::Char('r'), KeyModifiers::CONTROL) => {
+                query.regex = !query.regex;
+                self.update_search();
+            }
+            (KeyCode::Backspace, _) => {
+                if query.pattern.pop().is_some() {
+                    self.update_search();
+                } else {
+                    self.cancel_search();
+                }
+            }
+            (KeyCode::Char(c), modifiers) if !modifiers.contains(KeyModifiers::CONTROL) => {
+                query.pattern.push(c);
+                self.update_search();
+            }
+            _ => {}
+        }
+    }
+
+    /// 入力をやめて、入力を始める前の検索と位置に戻す
+    fn cancel_search(&mut self) {
+        if let Some(prompt) = self.search_prompt.take() {
+            self.search = prompt.previous;
+            self.scroll_offset = prompt.origin;
+            self.clamp_scroll();
+        }
+    }
+
+    fn confirm_search(&mut self) {
+        let Some(prompt) = self.search_prompt.take() else {
+            return;
+        };
+        let Some(search) = &self.search else {
+            return;
+        };
+        // 入力を始めた位置から移っていれば履歴に残す
+        if prompt.origin != self.scroll_offset {
+            let jump = self.jump_at(prompt.origin);
+            self.jumps.push(jump);
+        }
+
+        if search.query.pattern.is_empty() {
+            // 空のまま確定したら前回の検索語をこの向きで探す
+            let direction = search.query.direction;
+            self.search = prompt.previous.map(|mut previous| {
+                previous.query.direction = direction;
+                previous.current = None;
+                previous
+            });
+            self.search_next(false);
+        } else if let Some(error) = &search.error {
+            self.status_message = Some(format!("Invalid regex: {error}"));
+        } else if search.matches.is_empty() {
+            self.status_message = Some(format!("Pattern not found: {}", search.query.pattern));
+        }
+    }
+
+    /// 入力が変わるたびに、入力を始めた位置から探し直す
+    fn update_search(&mut self) {
+        let origin = self
+            .search_prompt
+            .as_ref()
+            .map_or(self.scroll_offset, |prompt| prompt.origin);
+        self.refresh_search();
+
+        let Some(search) = &self.search else {
+            return;
+        };
+        match self.match_from(origin, search.query.direction) {
+            Some(index) => self.show_match(index),
+            None => {
+                self.scroll_offset = origin;
+                self.clamp_scroll();
+            }
+        }
+    }
+
+    /// 現在のレイアウトでマッチを探し直す
+    fn refresh_search(&mut self) {
+        if let (Some(search), Some(layout)) = (&mut self.search, &self.layout) {
+            search.update(&self.document, layout, &self.theme_manager.current_theme());
+        }
+    }
+
+    /// `n`（`reverse` なら `N`）で次のマッチに移る
+    fn search_next(&mut self, reverse: bool) {
+        let Some(search) = &self.search else {
+            self.status_message = Some("No previous search".to_string());
+            return;
+        };
+        if search.matches.is_empty() {
+            self.status_message = Some(format!("Pattern not found: {}", search.query.pattern));
+            return;
+        }
+
+        let forward = (search.query.direction == SearchDirection::Forward) != reverse;
+        let direction = if forward {
+            SearchDirection::Forward
+        } else {
+            SearchDirection::Backward
+        };
+        let count = search.matches.len();
+        // 選択中のマッチが画面外なら表示位置から探す
+        let next = match search
+            .current
+            .filter(|&current| self.is_match_visible(current))
+        {
+            Some(current) if forward => (current + 1) % count,
+            Some(current) => (current + count - 1) % count,
+            None => {
+                let from = match direction {
+                    SearchDirection::Forward => self.scroll_offset,
+                    SearchDirection::Backward => self.scroll_offset + self.viewport_height,
+                };
+                let Some(next) = self.match_from(from, direction) else {
+                    return;
+                };
+                next
+            }
+        };
+
+        let previous_row = self.match_row(search.current.unwrap_or(next));
+        self.record_jump();
+        self.show_match(next);
+        let next_row = self.match_row(next);
+        if (forward && next_row < previous_row) || (!forward && next_row > previous_row) {
+            self.status_message = Some(if forward {
+                "Search wrapped to top".to_string()
+            } else {
+                "Search wrapped to bottom".to_string()
+            });
+        }
+    }
+
+    /// レイアウト上の行 `row` から `direction` の向きで最初のマッチ（端まで行けば反対側から）
+    fn match_from(&self, row: usize, direction: SearchDirection) -> Option<usize> {
+        let count = self.search.as_ref()?.matches.len();
+        let rows: Vec<usize> = (0..count).map(|index| self.match_row(index)).collect();
+        match direction {
+            SearchDirection::Forward => rows
+                .iter()
+                .position(|&match_row| match_row >= row)
+                .or((count > 0).then_some(0)),
+            SearchDirection::Backward => rows
+                .iter()
+                .rposition(|&match_row| match_row < row)
+                .or(count.checked_sub(1)),
+        }
+    }
+
+    /// マッチの行（閉じた `<details>` の中ならそのブロックの位置）
+    fn match_row(&self, index: usize) -> usize {
+        let found = self
+            .search
+            .as_ref()
+            .and_then(|search| search.matches.get(index));
+        match (found, &self.layout) {
+            (Some(SearchMatch { row: Some(row), .. }), _) => *row,
+            (Some(found), Some(layout)) => layout.block_rows(found.block).start,
+            _ => 0,
+        }
+    }
+
+    fn is_match_visible(&self, index: usize) -> bool {
+        let row = self.match_row(index);
+        (self.scroll_offset..self.scroll_offset + self.viewport_height.max(1)).contains(&row)
+    }
+
+    /// マッチを選択して画面内に表示する。閉じた `<details>` の中なら開く
+    fn show_match(&mut self, index: usize) {
+        let Some(search) = &self.search else {
+            return;
+        };
+        let Some(found) = search.matches.get(index).cloned() else {
+            return;
+        };
+        let mut index = index;
+
+        if found.row.is_none() {
+            // 開いた後のレイアウトでも同じブロックの何番目のマッチかで探す
+            let nth = search.matches[..index]
+                .iter()
+                .filter(|other| other.block == found.block)
+                .count();
+            let width = self.layout.as_ref().map(|layout| layout.area_width);
+            self.reveal_block(found.block);
+            if let Some(width) = width {
+                self.ensure_layout(width);
+            }
+            let Some(search) = &self.search else {
+                return;
+            };
+            index = search
+                .matches
+                .iter()
+                .enumerate()
+                .filter(|(_, other)| other.block == found.block)
+                .nth(nth)
+                .map_or(index, |(index, _)| index);
+        }
+
+        if let Some(search) = &mut self.search {
+            search.current = Some(index);
+        }
+        if !self.is_match_visible(index) {
+            // 前後が読めるように画面の上から 1/4 の位置に表示する
+            self.scroll_offset = self
+                .match_row(index)
+                .saturating_sub(self.viewport_height / 4);
+            self.clamp_scroll();
+        }
+    }
+
+    /// ブロックを囲む閉じた `<details>` を開き、ブロックを隠している折りたたみを展開する
+    fn reveal_block(&mut self, block: usize) {
+        let document = &self.document;
+        let folds: Vec<usize> = document
+            .folded_blocks()
+            .filter(|&folded| {
+                if folded == block {
+                    document.is_folded_content(block)
+                } else {
+                    folded < block && document.section_end(folded) > block
+                }
+            })
+            .collect();
+        if !folds.is_empty() {
+            self.invalidate_layout();
+            for folded in folds {
+                self.document.set_folded(folded, false);
+            }
+        }
+
+        let closed: Vec<usize> = self
+            .document
+            .nesting
+            .get(block)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| match entry.kind {
+                ContainerKind::Details { index, open: false } => Some(index),
+                _ => None,
+            })
+            .collect();
+        if closed.is_empty() {
+            return;
+        }
+
+        self.invalidate_layout();
+        for index in closed {
+            self.document.set_details_open(index, true);
+            if !self.toggled_details.remove(&index) {
+                self.toggled_details.insert(index);
+            }
+        }
     }
 
     fn toggle_toc(&mut self) {
         self.show_toc = !self.show_toc;
+        if self.show_toc {
+            self.reveal_toc_selection();
+        }
+    }
+
+    /// ディレクトリのファイルツリーを表示する
+    pub fn set_file_tree(&mut self, tree: FileTree) {
+        self.file_tree = Some(tree);
+        self.show_file_tree = true;
+        self.sync_tree_selection();
+    }
+
+    /// キー操作をファイルツリーで受け付けるか（目次を開いている間は目次が先）
+    pub fn file_tree_focused(&self) -> bool {
+        self.show_file_tree && self.file_tree.is_some() && !self.show_toc
+    }
+
+    /// ファイルツリーを開閉する。目次を開いていれば閉じてツリーを操作できるようにする
+    fn toggle_file_tree(&mut self) {
+        if self.file_tree.is_none() {
+            self.status_message = Some("Not viewing a directory".to_string());
+            return;
+        }
+        if self.file_tree_focused() {
+            self.show_file_tree = false;
+        } else {
+            self.show_file_tree = true;
+            self.show_toc = false;
+            self.reveal_tree_selection();
+        }
+    }
+
+    /// ディレクトリを読み直す。ファイルの一覧が変わったら true を返す
+    pub fn refresh_file_tree(&mut self) -> bool {
+        let changed = self.file_tree.as_mut().is_some_and(FileTree::rescan);
+        if changed {
+            self.reveal_tree_selection();
+        }
+        changed
+    }
+
+    /// 表示中のファイルをツリーで選ぶ
+    fn sync_tree_selection(&mut self) {
+        if let Some(tree) = &mut self.file_tree {
+            tree.select_path(&self.document.path);
+        }
+        self.reveal_tree_selection();
+    }
+
+    /// 選択中のエントリがツリーの表示範囲に入るようにする
+    fn reveal_tree_selection(&mut self) {
+        let height = self
+            .screen_areas
+            .file_tree
+            .map_or(0, |area| area.height as usize);
+        let Some(tree) = &mut self.file_tree else {
+            return;
+        };
+        let rows = tree.rows();
+        let row = rows
+            .iter()
+            .position(|&index| index == tree.selected)
+            .unwrap_or(0);
+        if row < tree.offset {
+            tree.offset = row;
+        } else if height > 0 && row >= tree.offset + height {
+            tree.offset = row + 1 - height;
+        }
+    }
+
+    /// ツリーの選択を動かし、ファイルならその内容を本文に表示する
+    fn move_tree_selection(&mut self, delta: isize) {
+        if let Some(tree) = &mut self.file_tree {
+            tree.move_selection(delta);
+        }
+        self.reveal_tree_selection();
+        self.preview_tree_selection();
+    }
+
+    fn preview_tree_selection(&mut self) {
+        let Some(entry) = self.file_tree.as_ref().and_then(FileTree::selected_entry) else {
+            return;
+        };
+        if !entry.is_dir && entry.path != self.document.path {
+            self.pending_action = Some(AppAction::OpenFile {
+                path: entry.path.clone(),
+                anchor: None,
+            });
+        }
+    }
+
+    /// ディレクトリを折りたたむ。折りたたみ済みかファイルなら親のディレクトリに移る
+    fn tree_collapse(&mut self) {
+        let Some(tree) = &mut self.file_tree else {
+            return;
+        };
+        let Some(entry) = tree.selected_entry() else {
+            return;
+        };
+        if entry.is_dir && !tree.is_collapsed(entry) {
+            tree.set_collapsed(tree.selected, true);
+        } else if let Some(parent) = tree.parent(tree.selected) {
+            tree.selected = parent;
+        }
+        self.reveal_tree_selection();
+    }
+
+    /// ディレクトリを展開する。展開済みなら最初の子に移る
+    fn tree_expand(&mut self) {
+        let Some(tree) = &mut self.file_tree else {
+            return;
+        };
+        let Some(entry) = tree.selected_entry().filter(|entry| entry.is_dir) else {
+            return;
+        };
+        if tree.is_collapsed(entry) {
+            tree.set_collapsed(tree.selected, false);
+        } else {
+            // 一覧に載るディレクトリは必ずファイルを含むので、次のエントリが最初の子
+            tree.selected += 1;
+        }
+        self.reveal_tree_selection();
+        self.preview_tree_selection();
+    }
+
+    /// ディレクトリなら開閉し、ファイルならツリーを閉じて本文を読めるようにする
+    fn tree_enter(&mut self) {
+        let Some(tree) = &mut self.file_tree else {
+            return;
+        };
+        let Some(entry) = tree.selected_entry() else {
+            return;
+        };
+        if entry.is_dir {
+            let collapsed = tree.is_collapsed(entry);
+            tree.set_collapsed(tree.selected, !collapsed);
+            self.reveal_tree_selection();
+        } else {
+            self.preview_tree_selection();
+            self.show_file_tree = false;
+        }
+    }
+
+    /// 画面内で最初の `<details>` を開閉する
+    fn toggle_details(&mut self) {
+        let document = &self.document;
+        let found = self
+            .visible_blocks()
+            .find_map(|block| match &document.parsed_lines[block] {
+                ParsedLine::Details { index, open, .. } if !document.is_hidden(block) => {
+                    Some((*index, *open))
+                }
+                _ => None,
+            });
+
+        match found {
+            Some((index, open)) => {
+                self.invalidate_layout();
+                self.document.set_details_open(index, !open);
+                if !self.toggled_details.remove(&index) {
+                    self.toggled_details.insert(index);
+                }
+            }
+            None => self.status_message = Some("No <details> on screen".to_string()),
+        }
+    }
+
+    /// 再読み込みした文書に `<details>` の開閉状態を引き継ぐ
+    fn apply_toggled_details(&mut self) {
+        let defaults: Vec<(usize, bool)> = self
+            .document
+            .parsed_lines
+            .iter()
+            .filter_map(|line| match line {
+                ParsedLine::Details { index, open, .. } if self.toggled_details.contains(index) => {
+                    Some((*index, *open))
+                }
+                _ => None,
+            })
+            .collect();
+        for (index, open) in defaults {
+            self.document.set_details_open(index, !open);
+        }
+    }
+
+    /// `z` に続くキーで折りたたむ（`za` 切り替え、`zc` 折りたたみ、`zo` 展開、`zM` / `zR` すべて）
+    fn handle_fold_key(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Char('a') => match self.fold_target(|_| true) {
+                Some(block) => self.change_folds(vec![(block, !self.document.is_folded(block))]),
+                None => self.status_message = Some("No fold on screen".to_string()),
+            },
+            KeyCode::Char('c') => match self.fold_target(|folded| !folded) {
+                Some(block) => self.change_folds(vec![(block, true)]),
+                None => self.status_message = Some("No fold on screen".to_string()),
+            },
+            KeyCode::Char('o') => match self.fold_target(|folded| folded) {
+                Some(block) => self.change_folds(vec![(block, false)]),
+                None => self.status_message = Some("No fold on screen".to_string()),
+            },
+            KeyCode::Char('M') => {
+                let headings = (0..self.document.parsed_lines.len())
+                    .filter(|&block| {
+                        matches!(
+                            self.document.parsed_lines[block],
+                            ParsedLine::Heading { .. }
+                        )
+                    })
+                    .map(|block| (block, true))
+                    .collect();
+                self.change_folds(headings);
+            }
+            KeyCode::Char('R') => {
+                let folded = self
+                    .document
+                    .folded_blocks()
+                    .map(|block| (block, false))
+                    .collect();
+                self.change_folds(folded);
+            }
+            _ => {}
+        }
+    }
+
+    /// 画面内で最初の折りたためるブロック。なければ表示位置を含むセクションの見出し
+    ///
+    /// `wanted` は折りたたみ済みかどうかで対象を絞る。
+    fn fold_target(&self, wanted: impl Fn(bool) -> bool) -> Option<usize> {
+        let document = &self.document;
+        let candidate = |block: &usize| {
+            !document.is_hidden(*block)
+                && document.is_foldable(*block)
+                && wanted(document.is_folded(*block))
+        };
+        if let Some(block) = self.visible_blocks().find(candidate) {
+            return Some(block);
+        }
+
+        let top = self.top_block();
+        (0..top)
+            .rev()
+            .filter(|&block| {
+                matches!(document.parsed_lines[block], ParsedLine::Heading { .. })
+                    && document.section_end(block) > top
+            })
+            .find(candidate)
+    }
+
+    /// 折りたたみを変えて、同じ幅でレイアウトを作り直す
+    fn change_folds(&mut self, folds: Vec<(usize, bool)>) {
+        let width = self.layout.as_ref().map(|layout| layout.area_width);
+        self.invalidate_layout();
+        for (block, folded) in folds {
+            self.document.set_folded(block, folded);
+        }
+        self.anchor_to_visible_block();
+        if let Some(width) = width {
+            self.ensure_layout(width);
+        }
+    }
+
+    /// 表示位置のブロックが折りたたみで隠れたら、それを隠している見出しから表示する
+    fn anchor_to_visible_block(&mut self) {
+        let Some((block, _)) = self.scroll_anchor else {
+            return;
+        };
+        if !self.document.is_hidden(block) {
+            return;
+        }
+        let heading = self
+            .document
+            .folded_blocks()
+            .filter(|&folded| folded < block && self.document.section_end(folded) > block)
+            .min();
+        if let Some(heading) = heading {
+            self.scroll_anchor = Some((heading, 0));
+        }
+    }
+
+    fn toggle_metadata(&mut self) {
+        if self.document.metadata.is_some() {
+            self.show_metadata = !self.show_metadata;
+        } else {
+            self.status_message = Some("No front matter".to_string());
+        }
+    }
+
+    /// 目次ペインに表示する行（折りたたみと絞り込みを反映）
+    pub fn toc_rows(&self) -> Vec<TocRow> {
+        self.toc_view.rows(&self.document.toc)
+    }
+
+    /// 選択中の見出しの目次ペイン上の行
+    ///
+    /// 折りたたまれて表示されていなければ、表示されている祖先の行。
+    pub fn toc_selected_row(&self, rows: &[TocRow]) -> Option<usize> {
+        let mut index = Some(self.toc_selected);
+        while let Some(current) = index {
+            if let Some(row) = rows.iter().position(|row| row.index == current) {
+                return Some(row);
+            }
+            index = toc::parent(&self.document.toc, current);
+        }
+        None
     }
 
     fn toc_up(&mut self) {
-        self.toc_selected = self.toc_selected.saturating_sub(1);
+        self.move_toc_selection(false);
     }
 
     fn toc_down(&mut self) {
-        if self.toc_selected < self.document.toc.len().saturating_sub(1) {
+        self.move_toc_selection(true);
+    }
+
+    /// 目次ペインで表示されている次（前）の行を選ぶ
+    fn move_toc_selection(&mut self, down: bool) {
+        let rows = self.toc_rows();
+        let next = match self.toc_selected_row(&rows) {
+            Some(row) if down => (row + 1).min(rows.len().saturating_sub(1)),
+            Some(row) => row.saturating_sub(1),
+            None => 0,
+        };
+        if let Some(row) = rows.get(next) {
+            self.toc_selected = row.index;
+        }
+        self.reveal_toc_selection();
+    }
+
+    /// 選択中の見出しを折りたたむ。折りたたみ済みか子がなければ親に移る
+    fn toc_collapse(&mut self) {
+        let toc = &self.document.toc;
+        let Some(entry) = toc.get(self.toc_selected) else {
+            return;
+        };
+        if toc::has_children(toc, self.toc_selected) && !self.toc_view.is_collapsed(entry) {
+            self.toc_view.set_collapsed(entry, true);
+        } else if let Some(parent) = toc::parent(toc, self.toc_selected) {
+            self.toc_selected = parent;
+        }
+        self.reveal_toc_selection();
+    }
+
+    /// 選択中の見出しを展開する。展開済みなら最初の子に移る
+    fn toc_expand(&mut self) {
+        let toc = &self.document.toc;
+        let Some(entry) = toc.get(self.toc_selected) else {
+            return;
+        };
+        if !toc::has_children(toc, self.toc_selected) {
+            return;
+        }
+        if self.toc_view.is_collapsed(entry) {
+            self.toc_view.set_collapsed(entry, false);
+        } else if self.toc_view.filter.is_none() {
             self.toc_selected += 1;
         }
+        self.reveal_toc_selection();
+    }
+
+    fn start_toc_filter(&mut self) {
+        self.toc_view.filter = Some(String::new());
+        self.toc_view.filtering = true;
+        self.toc_offset = 0;
+    }
+
+    fn clear_toc_filter(&mut self) {
+        self.toc_view.filter = None;
+        self.toc_view.filtering = false;
+        self.reveal_toc_selection();
+    }
+
+    fn handle_toc_filter_key(&mut self, key: KeyCode, modifiers: KeyModifiers) {
+        let filter = self.toc_view.filter.get_or_insert_with(String::new);
+        match (key, modifiers) {
+            (KeyCode::Esc, _) => self.clear_toc_filter(),
+            (KeyCode::Enter, _) => {
+                self.clear_toc_filter();
+                self.record_jump();
+                self.jump_to_heading();
+            }
+            (KeyCode::Up, _) | (KeyCode::Char('p'), KeyModifiers::CONTROL) => self.toc_up(),
+            (KeyCode::Down, _) | (KeyCode::Char('n'), KeyModifiers::CONTROL) => self.toc_down(),
+            (KeyCode::Backspace, _) => {
+                if filter.pop().is_some() {
+                    self.update_toc_filter();
+                } else {
+                    self.clear_toc_filter();
+                }
+            }
+            (KeyCode::Char(c), modifiers) if !modifiers.contains(KeyModifiers::CONTROL) => {
+                filter.push(c);
+                self.update_toc_filter();
+            }
+            _ => {}
+        }
+    }
+
+    /// 選択中の見出しが絞り込みで消えたら、最初に一致した見出しを選ぶ
+    fn update_toc_filter(&mut self) {
+        let rows = self.toc_rows();
+        if !rows.iter().any(|row| row.index == self.toc_selected) {
+            if let Some(first) = rows.first() {
+                self.toc_selected = first.index;
+            }
+        }
+        self.reveal_toc_selection();
+    }
+
+    /// 選択中の見出しが目次の表示範囲に入るようにする
+    fn reveal_toc_selection(&mut self) {
+        let height = self.screen_areas.toc.map_or(0, |toc| toc.height as usize);
+        let row = self.toc_selected_row(&self.toc_rows()).unwrap_or(0);
+        if row < self.toc_offset {
+            self.toc_offset = row;
+        } else if height > 0 && row >= self.toc_offset + height {
+            self.toc_offset = row + 1 - height;
+        }
+    }
+
+    /// 本文をスクロールしたら、表示位置を含むセクションの見出しを目次で選ぶ
+    ///
+    /// 目次で選択を動かしただけなら（スクロール位置が同じなら）何もしない。
+    pub fn sync_toc_selection(&mut self) {
+        if self.layout.is_none()
+            || self.toc_view.filter.is_some()
+            || self.toc_synced_offset == Some(self.scroll_offset)
+        {
+            return;
+        }
+        self.toc_synced_offset = Some(self.scroll_offset);
+        if let Some(heading) = self.heading_at_block(self.top_block()) {
+            self.toc_selected = heading;
+            self.reveal_toc_selection();
+        }
+    }
+
+    /// 端末の大きさが変わったら目次の幅とレイアウトを計算し直す
+    pub fn handle_resize(&mut self) {
+        self.invalidate_toc_cache();
+        self.invalidate_layout();
+    }
+
+    /// 貼り付けられた文字列は検索語の入力中だけ使う（改行は空白にする）
+    pub fn handle_paste(&mut self, text: &str) {
+        if self.search_prompt.is_none() {
+            return;
+        }
+        if let Some(search) = &mut self.search {
+            search.query.pattern.extend(text.chars().map(|c| {
+                if c == '\n' || c == '\r' {
+                    ' '
+                } else {
+                    c
+                }
+            }));
+            self.update_search();
+        }
+    }
+
+    /// 一定間隔で呼ばれる。表示が変わったら true を返す
+    pub fn tick(&mut self) -> bool {
+        self.expire_status(Instant::now())
+    }
+
+    /// 表示してから `STATUS_TIMEOUT` が経ったステータスメッセージを消す
+    pub fn expire_status(&mut self, now: Instant) -> bool {
+        let Some(message) = &self.status_message else {
+            self.status_since = None;
+            return false;
+        };
+        match &self.status_since {
+            Some((shown, since)) if shown == message => {
+                if now.saturating_duration_since(*since) < STATUS_TIMEOUT {
+                    return false;
+                }
+                self.status_message = None;
+                self.status_since = None;
+                true
+            }
+            _ => {
+                self.status_since = Some((message.clone(), now));
+                false
+            }
+        }
+    }
+
+    pub fn handle_mouse(&mut self, mouse: MouseEvent) {
+        // ポップアップや検索語の入力中はマウスを使わない
+        if self.show_help || !self.footnote_popup.is_empty() || self.search_prompt.is_some() {
+            return;
+        }
+
+        let position = Position::new(mouse.column, mouse.row);
+        let areas = self.screen_areas;
+        let toc = areas
+            .toc
+            .filter(|toc| self.show_toc && toc.contains(position));
+        let file_tree = areas
+            .file_tree
+            .filter(|tree| self.show_file_tree && tree.contains(position));
+
+        match mouse.kind {
+            MouseEventKind::ScrollDown if file_tree.is_some() => {
+                if let Some(tree) = &mut self.file_tree {
+                    tree.offset =
+                        (tree.offset + WHEEL_STEP).min(tree.rows().len().saturating_sub(1));
+                }
+            }
+            MouseEventKind::ScrollUp if file_tree.is_some() => {
+                if let Some(tree) = &mut self.file_tree {
+                    tree.offset = tree.offset.saturating_sub(WHEEL_STEP);
+                }
+            }
+            MouseEventKind::ScrollDown if toc.is_some() => {
+                self.toc_offset += WHEEL_STEP;
+            }
+            MouseEventKind::ScrollUp if toc.is_some() => {
+                self.toc_offset = self.toc_offset.saturating_sub(WHEEL_STEP);
+            }
+            MouseEventKind::ScrollDown => {
+                self.scroll_offset = (self.scroll_offset + WHEEL_STEP).min(self.max_scroll());
+            }
+            MouseEventKind::ScrollUp => {
+                self.scroll_offset = self.scroll_offset.saturating_sub(WHEEL_STEP);
+            }
+            MouseEventKind::Down(MouseButton::Left) => {
+                if let Some(scrollbar) = areas.scrollbar.filter(|bar| bar.contains(position)) {
+                    self.dragging_scrollbar = true;
+                    self.drag_scrollbar(scrollbar, mouse.row);
+                } else if let Some(toc) = toc {
+                    self.click_toc(toc, mouse.column, mouse.row);
+                } else if let Some(area) = file_tree {
+                    self.click_file_tree(area, mouse.row);
+                } else if let Some(link) = self
+                    .link_regions
+                    .iter()
+                    .find(|link| link.area.contains(position))
+                {
+                    self.selected_link = Some(link.index);
+                    self.follow_link();
+                }
+            }
+            MouseEventKind::Drag(MouseButton::Left) if self.dragging_scrollbar => {
+                if let Some(scrollbar) = areas.scrollbar {
+                    self.drag_scrollbar(scrollbar, mouse.row);
+                }
+            }
+            MouseEventKind::Up(MouseButton::Left) => self.dragging_scrollbar = false,
+            _ => {}
+        }
+    }
+
+    /// スクロールバー上の `row` の位置に対応する所までスクロールする
+    fn drag_scrollbar(&mut self, scrollbar: Rect, row: u16) {
+        let track = scrollbar.height.saturating_sub(1).max(1) as usize;
+        let offset = row.saturating_sub(scrollbar.y).min(scrollbar.height) as usize;
+        self.scroll_offset = (offset * self.max_scroll() + track / 2) / track;
+        self.clamp_scroll();
+    }
+
+    /// ファイルツリーの `row` 行目を選ぶ。ディレクトリなら開閉し、ファイルなら表示する
+    fn click_file_tree(&mut self, area: Rect, row: u16) {
+        let Some(tree) = &mut self.file_tree else {
+            return;
+        };
+        let Some(&clicked) = tree
+            .rows()
+            .get(tree.offset + row.saturating_sub(area.y) as usize)
+        else {
+            return;
+        };
+        tree.selected = clicked;
+        if tree.entries[clicked].is_dir {
+            let collapsed = tree.is_collapsed(&tree.entries[clicked]);
+            tree.set_collapsed(clicked, !collapsed);
+        } else {
+            self.preview_tree_selection();
+        }
+    }
+
+    /// 目次の `row` 行目の見出しにジャンプする。`▸` / `▾` の上なら開閉する
+    fn click_toc(&mut self, toc: Rect, column: u16, row: u16) {
+        let rows = self.toc_rows();
+        let Some(clicked) = rows.get(self.toc_offset + row.saturating_sub(toc.y) as usize) else {
+            return;
+        };
+        self.toc_selected = clicked.index;
+
+        let entry = &self.document.toc[clicked.index];
+        let marker = toc.x as usize + toc::indent_width(entry.level);
+        if clicked.has_children && (marker..marker + 2).contains(&(column as usize)) {
+            if clicked.collapsed {
+                self.toc_expand();
+            } else {
+                self.toc_collapse();
+            }
+        } else {
+            self.record_jump();
+            self.jump_to_heading();
+        }
     }
 
     pub fn jump_to_heading(&mut self) {
         if let Some(entry) = self.document.toc.get(self.toc_selected) {
-            self.scroll_offset = entry.line_number.saturating_sub(2);
-            self.current_line = entry.line_number;
+            let block = self
+                .heading_block(self.toc_selected)
+                .unwrap_or_else(|| self.document.block_at_line(entry.line_number));
+            self.reveal_block(block);
+            self.scroll_to_block(block);
             self.show_toc = false;
+            self.toc_view.filter = None;
+            self.toc_view.filtering = false;
+            // 末尾近くの見出しでスクロールが止まっても選択は変えない
+            if self.layout.is_some() {
+                self.toc_synced_offset = Some(self.scroll_offset);
+            }
+        }
+    }
+
+    /// `#anchor` に一致する見出しにジャンプする。見つからなければ false
+    pub fn jump_to_anchor(&mut self, anchor: &str) -> bool {
+        let toc = &self.document.toc;
+        let found = toc
+            .iter()
+            .position(|entry| entry.anchor == anchor)
+            .or_else(|| {
+                let anchor = anchor.to_lowercase();
+                toc.iter().position(|entry| entry.anchor == anchor)
+            });
+
+        if let Some(idx) = found {
+            self.toc_selected = idx;
+            self.jump_to_heading();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// toc[n] に対応する見出しの `parsed_lines` 上のインデックス
+    fn heading_block(&self, toc_index: usize) -> Option<usize> {
+        self.document
+            .parsed_lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| matches!(line, ParsedLine::Heading { .. }))
+            .nth(toc_index)
+            .map(|(idx, _)| idx)
+    }
+
+    /// 表示位置を含むセクションの見出し（目次表示中は選択中の見出し）の TOC インデックス
+    pub fn current_heading(&self) -> Option<usize> {
+        if self.show_toc {
+            return (self.toc_selected < self.document.toc.len()).then_some(self.toc_selected);
+        }
+
+        self.heading_at_block(self.top_block())
+    }
+
+    /// ブロックを含むセクションの見出しの TOC インデックス
+    fn heading_at_block(&self, block: usize) -> Option<usize> {
+        // parsed_lines 中の n 番目の見出しが toc[n] に対応する
+        self.document
+            .parsed_lines
+            .iter()
+            .take(block + 1)
+            .filter(|line| matches!(line, ParsedLine::Heading { .. }))
+            .count()
+            .checked_sub(1)
+    }
+
+    /// 現在の見出しへのリンク（`README.md#installation`）をクリップボードにコピーする
+    fn copy_heading_anchor(&mut self) {
+        let Some(entry) = self
+            .current_heading()
+            .and_then(|idx| self.document.toc.get(idx))
+        else {
+            self.status_message = Some("No heading to copy".to_string());
+            return;
+        };
+
+        let file_name = self
+            .document
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let permalink = format!("{file_name}#{}", entry.anchor);
+        self.status_message = Some(format!("Copied {permalink}"));
+        self.pending_action = Some(AppAction::CopyToClipboard(permalink));
+    }
+
+    /// 表示中のブロックから参照されている脚注をポップアップで表示する
+    fn show_footnotes(&mut self) {
+        let mut numbers: Vec<usize> = Vec::new();
+        for line in &self.document.parsed_lines[self.visible_blocks()] {
+            for_each_inline(line, |spans| {
+                for number in spans.iter().filter_map(|span| span.footnote) {
+                    if !numbers.contains(&number) {
+                        numbers.push(number);
+                    }
+                }
+            });
+        }
+
+        if numbers.is_empty() {
+            self.status_message = Some("No footnotes on screen".to_string());
+        }
+        self.footnote_popup = numbers;
+    }
+
+    fn next_link(&mut self) {
+        let count = self.document.links.len();
+        if count == 0 {
+            return;
+        }
+        let next = match self.selected_link {
+            Some(index) => (index + 1) % count,
+            // 未選択なら表示位置以降の最初のリンクから
+            None => self
+                .document
+                .links
+                .iter()
+                .position(|link| link.line_index >= self.top_block())
+                .unwrap_or(0),
+        };
+        self.select_link(next);
+    }
+
+    fn prev_link(&mut self) {
+        let count = self.document.links.len();
+        if count == 0 {
+            return;
+        }
+        let prev = match self.selected_link {
+            Some(index) => (index + count - 1) % count,
+            None => {
+                let visible_end = self.visible_blocks().end;
+                self.document
+                    .links
+                    .iter()
+                    .rposition(|link| link.line_index < visible_end)
+                    .unwrap_or(count - 1)
+            }
+        };
+        self.select_link(prev);
+    }
+
+    /// リンクを選択し、画面外ならそこまでスクロールする
+    fn select_link(&mut self, index: usize) {
+        self.selected_link = Some(index);
+        let Some(link) = self.document.links.get(index) else {
+            return;
+        };
+        self.status_message = Some(format!("→ {}", link.url));
+
+        match self
+            .layout
+            .as_ref()
+            .and_then(|layout| layout.link_row(index))
+        {
+            Some(row) => {
+                let visible = self.scroll_offset..self.scroll_offset + self.viewport_height.max(1);
+                if !visible.contains(&row) {
+                    self.scroll_offset = row;
+                    self.clamp_scroll();
+                }
+            }
+            None => {
+                let block = link.line_index;
+                if !self.visible_blocks().contains(&block) {
+                    self.scroll_to_block(block);
+                }
+            }
+        }
+    }
+
+    fn follow_link(&mut self) {
+        let Some(link) = self
+            .selected_link
+            .and_then(|index| self.document.links.get(index))
+        else {
+            return;
+        };
+
+        let base_dir = self
+            .document
+            .path
+            .parent()
+            .unwrap_or_else(|| Path::new("."));
+        match LinkTarget::resolve(&link.url, base_dir) {
+            LinkTarget::Anchor(anchor) => {
+                self.selected_link = None;
+                let from = self.jump_at(self.scroll_offset);
+                if self.jump_to_anchor(&anchor) {
+                    self.jumps.push(from);
+                } else {
+                    self.status_message = Some(format!("Anchor not found: #{anchor}"));
+                }
+            }
+            LinkTarget::Markdown { path, anchor } => {
+                self.record_jump();
+                self.pending_action = Some(AppAction::OpenFile { path, anchor });
+            }
+            LinkTarget::External(target) => {
+                self.pending_action = Some(AppAction::OpenExternal(target));
+            }
         }
     }
 
@@ -173,3 +1972,14 @@ impl<'a> App<'a> {
         self.toc_width_cache = None;
     }
 }
+
+/// 同じファイルを指すパスか（相対パスやシンボリックリンクの違いは無視する）
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => matches!(
+            (std::path::absolute(a), std::path::absolute(b)),
+            (Ok(a), Ok(b)) if a == b
+        ),
+    }
+}
@@ -1,9 +1,29 @@
 use crate::error::MdError;
-use crossterm::event::{Event, EventStream, KeyEvent};
+use crossterm::event::{Event, EventStream, KeyEvent, MouseEvent, MouseEventKind};
 use futures::StreamExt;
+use std::time::Duration;
+use tokio::time::{interval, Interval, MissedTickBehavior};
+
+/// 時間で変わる表示（ステータスメッセージを消すなど）のための間隔
+const TICK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// イベントループで扱う入力
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AppEvent {
+    Key(KeyEvent),
+    Mouse(MouseEvent),
+    /// 端末の新しい幅と高さ
+    Resize(u16, u16),
+    FocusGained,
+    FocusLost,
+    /// ブラケットペーストで貼り付けられた文字列
+    Paste(String),
+    Tick,
+}
 
 pub struct EventHandler {
     stream: EventStream,
+    tick: Interval,
 }
 
 impl Default for EventHandler {
@@ -14,17 +34,36 @@ impl Default for EventHandler {
 
 impl EventHandler {
     pub fn new() -> Self {
+        let mut tick = interval(TICK_INTERVAL);
+        tick.set_missed_tick_behavior(MissedTickBehavior::Skip);
         Self {
             stream: EventStream::new(),
+            tick,
         }
     }
 
-    pub async fn next_key(&mut self) -> Result<Option<KeyEvent>, MdError> {
-        if let Some(event) = self.stream.next().await {
-            if let Event::Key(key) = event? {
-                return Ok(Some(key));
-            }
+    /// 次のイベントを待つ。入力の終わりでは `None`
+    ///
+    /// ボタンを押していないマウスの移動は再描画が増えるだけなので捨てる。
+    pub async fn next_event(&mut self) -> Result<Option<AppEvent>, MdError> {
+        loop {
+            let event = tokio::select! {
+                event = self.stream.next() => event,
+                _ = self.tick.tick() => return Ok(Some(AppEvent::Tick)),
+            };
+            let Some(event) = event else {
+                return Ok(None);
+            };
+
+            return Ok(Some(match event? {
+                Event::Key(key) => AppEvent::Key(key),
+                Event::Mouse(mouse) if mouse.kind == MouseEventKind::Moved => continue,
+                Event::Mouse(mouse) => AppEvent::Mouse(mouse),
+                Event::Resize(width, height) => AppEvent::Resize(width, height),
+                Event::FocusGained => AppEvent::FocusGained,
+                Event::FocusLost => AppEvent::FocusLost,
+                Event::Paste(text) => AppEvent::Paste(text),
+            }));
         }
-        Ok(None)
     }
 }
@@ -0,0 +1,269 @@
+//! ディレクトリを開いたときのファイルツリー
+
+use crate::markdown::link::is_markdown_path;
+use ignore::WalkBuilder;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// ディレクトリを開いたときに最初に表示するファイル（この順で探す）
+const DEFAULT_FILES: &[&str] = &["README.md", "index.md"];
+
+/// ファイルツリーの1行
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeEntry {
+    /// ルートのパスにつないだパス
+    pub path: PathBuf,
+    /// ルート直下を 0 とする深さ
+    pub depth: usize,
+    pub is_dir: bool,
+}
+
+impl TreeEntry {
+    pub fn name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+}
+
+/// ディレクトリ以下のマークダウンファイルの一覧と、その選択・折りたたみの状態
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FileTree {
+    pub root: PathBuf,
+    /// ディレクトリを先に名前順で並べた一覧（マークダウンを含まないディレクトリは載せない）
+    pub entries: Vec<TreeEntry>,
+    /// 折りたたんだディレクトリ
+    pub collapsed: HashSet<PathBuf>,
+    /// 選択中の `entries` のインデックス
+    pub selected: usize,
+    /// 表示を始める行
+    pub offset: usize,
+}
+
+impl FileTree {
+    pub fn new(root: &Path) -> Self {
+        let mut tree = Self {
+            root: root.to_path_buf(),
+            ..Self::default()
+        };
+        tree.rescan();
+        tree
+    }
+
+    /// ディレクトリを読み直す。選択と折りたたみはパスで引き継ぐ
+    ///
+    /// 一覧が変わったら true を返す。
+    pub fn rescan(&mut self) -> bool {
+        let entries = build_entries(&self.root, &markdown_files(&self.root));
+        if entries == self.entries {
+            return false;
+        }
+
+        let selected = self.selected_entry().map(|entry| entry.path.clone());
+        self.entries = entries;
+        let entries = &self.entries;
+        self.collapsed
+            .retain(|dir| entries.iter().any(|entry| &entry.path == dir));
+        self.selected = selected
+            .and_then(|path| self.position(&path))
+            .unwrap_or(self.selected.min(self.entries.len().saturating_sub(1)));
+        true
+    }
+
+    /// 最初に表示するファイル（ルート直下の `README.md` / `index.md`、なければ最初のファイル）
+    pub fn default_file(&self) -> Option<PathBuf> {
+        let top_level = |name: &str| {
+            self.entries.iter().find(|entry| {
+                entry.depth == 0 && !entry.is_dir && entry.name().eq_ignore_ascii_case(name)
+            })
+        };
+        DEFAULT_FILES
+            .iter()
+            .find_map(|name| top_level(name))
+            .or_else(|| self.entries.iter().find(|entry| !entry.is_dir))
+            .map(|entry| entry.path.clone())
+    }
+
+    /// 表示する行（折りたたんだディレクトリの中は除く）の `entries` のインデックス
+    pub fn rows(&self) -> Vec<usize> {
+        let mut rows = Vec::new();
+        // この深さより深いエントリは折りたたまれたディレクトリの中にある
+        let mut hidden_below: Option<usize> = None;
+        for (index, entry) in self.entries.iter().enumerate() {
+            if hidden_below.is_some_and(|depth| entry.depth > depth) {
+                continue;
+            }
+            hidden_below =
+                (entry.is_dir && self.collapsed.contains(&entry.path)).then_some(entry.depth);
+            rows.push(index);
+        }
+        rows
+    }
+
+    pub fn selected_entry(&self) -> Option<&TreeEntry> {
+        self.entries.get(self.selected)
+    }
+
+    pub fn position(&self, path: &Path) -> Option<usize> {
+        self.entries.iter().position(|entry| entry.path == path)
+    }
+
+    /// `path` のファイルを選び、親のディレクトリを開く
+    pub fn select_path(&mut self, path: &Path) {
+        let Some(index) = self.position(path) else {
+            return;
+        };
+        self.selected = index;
+        let mut current = index;
+        while let Some(parent) = self.parent(current) {
+            self.collapsed.remove(&self.entries[parent].path);
+            current = parent;
+        }
+    }
+
+    /// 表示している行の中で選択を `delta` 行動かす
+    pub fn move_selection(&mut self, delta: isize) {
+        let rows = self.rows();
+        let Some(row) = rows.iter().position(|&index| index == self.selected) else {
+            self.selected = rows.first().copied().unwrap_or(0);
+            return;
+        };
+        let row = row.saturating_add_signed(delta).min(rows.len() - 1);
+        self.selected = rows[row];
+    }
+
+    pub fn is_collapsed(&self, entry: &TreeEntry) -> bool {
+        entry.is_dir && self.collapsed.contains(&entry.path)
+    }
+
+    pub fn set_collapsed(&mut self, index: usize, collapsed: bool) {
+        let Some(entry) = self.entries.get(index).filter(|entry| entry.is_dir) else {
+            return;
+        };
+        if collapsed {
+            self.collapsed.insert(entry.path.clone());
+        } else {
+            self.collapsed.remove(&entry.path);
+        }
+    }
+
+    /// 親のディレクトリ（手前にある、より浅いエントリ）
+    pub fn parent(&self, index: usize) -> Option<usize> {
+        let depth = self.entries.get(index)?.depth;
+        self.entries[..index]
+            .iter()
+            .rposition(|entry| entry.depth < depth)
+    }
+
+    /// 変更の通知があったパスが一覧に関わるか（マークダウンファイルかディレクトリ）
+    pub fn is_affected_by(&self, path: &Path) -> bool {
+        // 通知のパスは絶対パスで、シンボリックリンクを解決していることもある
+        let in_root = path.starts_with(&self.root)
+            || std::path::absolute(&self.root).is_ok_and(|root| path.starts_with(root))
+            || self
+                .root
+                .canonicalize()
+                .is_ok_and(|root| path.starts_with(root));
+        in_root && (is_markdown_path(path) || path.extension().is_none())
+    }
+}
+
+/// `root` 以下のマークダウンファイル（`root` からの相対パス）
+///
+/// `.gitignore` と `.ignore` で除外されたファイルと隠しファイルは含めない。
+fn markdown_files(root: &Path) -> Vec<PathBuf> {
+    WalkBuilder::new(root)
+        // Git の管理下でなくても `.gitignore` を読む
+        .require_git(false)
+        .build()
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            entry
+                .file_type()
+                .is_some_and(|file_type| file_type.is_file())
+        })
+        .filter(|entry| is_markdown_path(entry.path()))
+        .filter_map(|entry| entry.path().strip_prefix(root).ok().map(Path::to_path_buf))
+        .collect()
+}
+
+#[derive(Default)]
+struct DirNode {
+    dirs: BTreeMap<OsString, DirNode>,
+    files: BTreeSet<OsString>,
+}
+
+/// 相対パスの一覧を、ディレクトリを先にした深さ優先の順に並べる
+fn build_entries(root: &Path, files: &[PathBuf]) -> Vec<TreeEntry> {
+    let mut top = DirNode::default();
+    for file in files {
+        let mut components: Vec<OsString> = file
+            .components()
+            .map(|component| component.as_os_str().to_os_string())
+            .collect();
+        let Some(name) = components.pop() else {
+            continue;
+        };
+        let mut node = &mut top;
+        for component in components {
+            node = node.dirs.entry(component).or_default();
+        }
+        node.files.insert(name);
+    }
+
+    fn push(node: &DirNode, dir: &Path, depth: usize, entries: &mut Vec<TreeEntry>) {
+        for (name, child) in &node.dirs {
+            let path = dir.join(name);
+            entries.push(TreeEntry {
+                path: path.clone(),
+                depth,
+                is_dir: true,
+            });
+            push(child, &path, depth + 1, entries);
+        }
+        for name in &node.files {
+            entries.push(TreeEntry {
+                path: dir.join(name),
+                depth,
+                is_dir: false,
+            });
+        }
+    }
+
+    let mut entries = Vec::new();
+    push(&top, root, 0, &mut entries);
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_entries_puts_directories_first() {
+        let files = [
+            PathBuf::from("z.md"),
+            PathBuf::from("guide/b.md"),
+            PathBuf::from("guide/advanced/c.md"),
+            PathBuf::from("README.md"),
+        ];
+        let entries = build_entries(Path::new("docs"), &files);
+        let listed: Vec<(String, usize, bool)> = entries
+            .iter()
+            .map(|entry| (entry.path.display().to_string(), entry.depth, entry.is_dir))
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                ("docs/guide".to_string(), 0, true),
+                ("docs/guide/advanced".to_string(), 1, true),
+                ("docs/guide/advanced/c.md".to_string(), 2, false),
+                ("docs/guide/b.md".to_string(), 1, false),
+                ("docs/README.md".to_string(), 0, false),
+                ("docs/z.md".to_string(), 0, false),
+            ]
+        );
+    }
+}
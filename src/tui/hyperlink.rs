@@ -0,0 +1,162 @@
+use crate::tui::ui::ScreenLink;
+use crossterm::{
+    cursor::{MoveTo, RestorePosition, SavePosition},
+    queue,
+    style::{Attribute, Print, ResetColor, SetAttribute, SetBackgroundColor, SetForegroundColor},
+};
+use ratatui::{buffer::Buffer, style::Modifier};
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+use unicode_width::UnicodeWidthStr;
+
+/// OSC 8 ハイパーリンクを出力するかどうか
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum HyperlinkMode {
+    /// 対応していそうな端末でのみ出力
+    Auto,
+    Always,
+    Never,
+}
+
+impl HyperlinkMode {
+    pub fn enabled(self) -> bool {
+        match self {
+            HyperlinkMode::Auto => terminal_supports_hyperlinks(),
+            HyperlinkMode::Always => true,
+            HyperlinkMode::Never => false,
+        }
+    }
+}
+
+/// 環境変数から端末がOSC 8に対応しているかを推測する
+fn terminal_supports_hyperlinks() -> bool {
+    let env = |key: &str| std::env::var(key).unwrap_or_default();
+
+    // tmux / screen は素通ししないことがある
+    if !env("TMUX").is_empty() || env("TERM").starts_with("screen") {
+        return false;
+    }
+
+    if !env("WT_SESSION").is_empty()
+        || !env("KITTY_WINDOW_ID").is_empty()
+        || !env("WEZTERM_EXECUTABLE").is_empty()
+        || !env("KONSOLE_VERSION").is_empty()
+    {
+        return true;
+    }
+
+    if env("VTE_VERSION").parse::<u32>().unwrap_or(0) >= 5000 {
+        return true;
+    }
+
+    matches!(
+        env("TERM_PROGRAM").as_str(),
+        "iTerm.app" | "WezTerm" | "vscode" | "ghostty" | "Hyper"
+    ) || matches!(env("TERM").as_str(), "xterm-kitty" | "alacritty" | "foot")
+}
+
+/// 描画済みのリンク領域を OSC 8 で囲んで書き直す
+///
+/// ratatui のバッファはエスケープシーケンスを含む文字列を1セルとして扱えないため、
+/// 描画後にセルの内容を同じ見た目のまま端末へ直接書き出す。
+pub fn write_hyperlinks<W: Write>(
+    out: &mut W,
+    buffer: &Buffer,
+    links: &[ScreenLink],
+) -> io::Result<()> {
+    if links.is_empty() {
+        return Ok(());
+    }
+
+    queue!(out, SavePosition)?;
+    for link in links {
+        let area = link.area.intersection(buffer.area);
+        if area.is_empty() {
+            continue;
+        }
+
+        queue!(out, MoveTo(area.x, area.y))?;
+        write!(out, "\x1b]8;;{}\x1b\\", sanitize_url(&link.url))?;
+
+        let mut covered = 0;
+        for x in area.left()..area.right() {
+            // 全角文字の2セル目は直前のセルが描画済み
+            if covered > 0 {
+                covered -= 1;
+                continue;
+            }
+            let cell = &buffer[(x, area.y)];
+            queue!(
+                out,
+                SetAttribute(Attribute::Reset),
+                SetForegroundColor(cell.fg.into()),
+                SetBackgroundColor(cell.bg.into()),
+            )?;
+            for (modifier, attribute) in [
+                (Modifier::BOLD, Attribute::Bold),
+                (Modifier::DIM, Attribute::Dim),
+                (Modifier::ITALIC, Attribute::Italic),
+                (Modifier::UNDERLINED, Attribute::Underlined),
+                (Modifier::REVERSED, Attribute::Reverse),
+                (Modifier::CROSSED_OUT, Attribute::CrossedOut),
+            ] {
+                if cell.modifier.contains(modifier) {
+                    queue!(out, SetAttribute(attribute))?;
+                }
+            }
+            queue!(out, Print(cell.symbol()))?;
+            covered = cell.symbol().width().saturating_sub(1);
+        }
+
+        write!(out, "\x1b]8;;\x1b\\")?;
+        queue!(out, SetAttribute(Attribute::Reset), ResetColor)?;
+    }
+    queue!(out, RestorePosition)?;
+    out.flush()
+}
+
+/// OSC 52 で端末経由のクリップボードにテキストをコピーする
+///
+/// SSH 越しでも手元の端末のクリップボードに届く。
+pub fn copy_to_clipboard<W: Write>(out: &mut W, text: &str) -> io::Result<()> {
+    use base64::Engine;
+
+    let encoded = base64::engine::general_purpose::STANDARD.encode(text);
+    write!(out, "\x1b]52;c;{encoded}\x07")?;
+    out.flush()
+}
+
+/// エスケープシーケンスを壊す制御文字を取り除く
+fn sanitize_url(url: &str) -> String {
+    url.chars().filter(|c| !c.is_control()).collect()
+}
+
+/// プラットフォーム既定のオープナー
+pub fn default_opener() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "open"
+    } else if cfg!(target_os = "windows") {
+        "explorer"
+    } else {
+        "xdg-open"
+    }
+}
+
+/// 外部コマンドでURLやファイルを開く（終了は待たない）
+///
+/// `opener` は空白区切りで引数を含められる（例: `firefox --new-tab`）。
+pub fn open_external(opener: &str, target: &str) -> io::Result<()> {
+    let mut parts = opener.split_whitespace();
+    let program = parts
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "empty opener command"))?;
+
+    Command::new(program)
+        .args(parts)
+        .arg(target)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(|_| ())
+}
@@ -0,0 +1,275 @@
+//! 文書を現在の幅で画面の行に展開するレイアウト
+//!
+//! スクロール位置はこのレイアウト上の行番号で表す。幅・テーマ・文書が変わったら作り直す。
+
+use crate::markdown::MarkdownDocument;
+use crate::tui::ui::{render_folded_line, render_nested_line};
+use crate::tui::UiTheme;
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use std::ops::Range;
+use unicode_width::UnicodeWidthChar;
+
+/// レイアウト上のリンクの位置
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutLink {
+    pub row: usize,
+    pub column: usize,
+    pub width: usize,
+    /// `Link::index` of the link
+    pub index: usize,
+    pub url: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct DocumentLayout {
+    /// 本文の枠を含む幅
+    pub area_width: u16,
+    /// 作成に使ったテーマの名前
+    pub theme_name: String,
+    rows: Vec<Line<'static>>,
+    /// 各行が属するブロック（`parsed_lines` のインデックス）
+    row_blocks: Vec<usize>,
+    /// 各ブロックの先頭行（末尾に総行数を持つ）
+    block_starts: Vec<usize>,
+    links: Vec<LayoutLink>,
+}
+
+impl DocumentLayout {
+    /// `area_width` は枠を含む本文の幅。閉じた `<details>` や折りたたんだセクションの中身は行を持たない
+    pub fn build(
+        document: &MarkdownDocument,
+        theme: &UiTheme,
+        theme_name: &str,
+        area_width: u16,
+    ) -> Self {
+        let row_width = area_width.saturating_sub(2).max(1) as usize;
+        let mut rows = Vec::new();
+        let mut row_blocks = Vec::new();
+        let mut block_starts = Vec::with_capacity(document.parsed_lines.len() + 1);
+        let mut links = Vec::new();
+
+        for (block, (line, nesting)) in document
+            .parsed_lines
+            .iter()
+            .zip(&document.nesting)
+            .enumerate()
+        {
+            block_starts.push(rows.len());
+            if document.is_hidden(block) {
+                continue;
+            }
+
+            let mut regions = Vec::new();
+            let lines = if document.is_folded(block) {
+                render_folded_line(
+                    line,
+                    nesting,
+                    theme,
+                    area_width as usize,
+                    document.folded_line_count(block),
+                    &mut regions,
+                )
+            } else {
+                render_nested_line(line, nesting, theme, area_width as usize, &mut regions)
+            };
+
+            // 描画した行ごとの (先頭行, 折り返した各行の開始桁)
+            let mut placements = Vec::with_capacity(lines.len());
+            for rendered in lines {
+                let wrapped = wrap_line(rendered, row_width);
+                placements.push((
+                    rows.len(),
+                    wrapped
+                        .iter()
+                        .map(|(column, _)| *column)
+                        .collect::<Vec<_>>(),
+                ));
+                for (_, row) in wrapped {
+                    rows.push(row);
+                    row_blocks.push(block);
+                }
+            }
+
+            for region in regions {
+                let Some((first_row, starts)) = placements.get(region.line) else {
+                    continue;
+                };
+                let segment = starts.partition_point(|&start| start <= region.column) - 1;
+                let segment_end = starts.get(segment + 1).copied().unwrap_or(usize::MAX);
+                // 折り返しをまたぐリンクは位置を持たない
+                if region.column + region.width > segment_end {
+                    continue;
+                }
+                links.push(LayoutLink {
+                    row: first_row + segment,
+                    column: region.column - starts[segment],
+                    width: region.width,
+                    index: region.index,
+                    url: region.url,
+                });
+            }
+        }
+        block_starts.push(rows.len());
+
+        Self {
+            area_width,
+            theme_name: theme_name.to_string(),
+            rows,
+            row_blocks,
+            block_starts,
+            links,
+        }
+    }
+
+    pub fn row_count(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// `range` の行（範囲外は切り詰める）
+    pub fn rows(&self, range: Range<usize>) -> &[Line<'static>] {
+        let end = range.end.min(self.rows.len());
+        &self.rows[range.start.min(end)..end]
+    }
+
+    /// `row` 行目を含むブロック
+    pub fn block_at_row(&self, row: usize) -> usize {
+        self.row_blocks
+            .get(row)
+            .or(self.row_blocks.last())
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// ブロックが占める行（閉じた `<details>` の中身は空）
+    pub fn block_rows(&self, block: usize) -> Range<usize> {
+        let last = self.block_starts.len() - 1;
+        self.block_starts[block.min(last)]..self.block_starts[(block + 1).min(last)]
+    }
+
+    /// `rows` に一部でも表示されるブロック
+    pub fn blocks_in(&self, rows: Range<usize>) -> Range<usize> {
+        let end = rows.end.min(self.rows.len());
+        if rows.start >= end {
+            return 0..0;
+        }
+        self.block_at_row(rows.start)..self.block_at_row(end - 1) + 1
+    }
+
+    /// `rows` に表示されるリンク
+    pub fn links_in(&self, rows: Range<usize>) -> impl Iterator<Item = &LayoutLink> {
+        self.links
+            .iter()
+            .filter(move |link| rows.contains(&link.row))
+    }
+
+    /// リンク（`Link::index`）が最初に現れる行
+    pub fn link_row(&self, index: usize) -> Option<usize> {
+        self.links
+            .iter()
+            .find(|link| link.index == index)
+            .map(|link| link.row)
+    }
+}
+
+/// 1行を `width` 桁ごとの行に折り返し、各行の元の行での開始桁とともに返す
+///
+/// 空白の後ろで折り返せる場合はそこで折り返し、長い単語は途中で切る。
+fn wrap_line(line: Line<'static>, width: usize) -> Vec<(usize, Line<'static>)> {
+    if line.width() <= width {
+        return vec![(0, line)];
+    }
+
+    let cells: Vec<(char, Style)> = line
+        .spans
+        .iter()
+        .flat_map(|span| span.content.chars().map(move |c| (c, span.style)))
+        .collect();
+    let mut columns = Vec::with_capacity(cells.len() + 1);
+    let mut column = 0;
+    for (c, _) in &cells {
+        columns.push(column);
+        column += c.width().unwrap_or(0);
+    }
+    columns.push(column);
+
+    let mut rows = Vec::new();
+    let mut start = 0;
+    let mut break_at = None;
+    let mut i = 0;
+    while i < cells.len() {
+        let (c, _) = cells[i];
+        if columns[i + 1] - columns[start] > width && i > start {
+            let end = break_at.filter(|&end| end > start).unwrap_or(i);
+            rows.push((
+                columns[start],
+                cells_to_line(&cells[start..end], line.style),
+            ));
+            start = end;
+            break_at = None;
+            continue;
+        }
+        if c.is_whitespace() {
+            break_at = Some(i + 1);
+        }
+        i += 1;
+    }
+    rows.push((columns[start], cells_to_line(&cells[start..], line.style)));
+    rows
+}
+
+fn cells_to_line(cells: &[(char, Style)], style: Style) -> Line<'static> {
+    let mut spans: Vec<Span<'static>> = Vec::new();
+    let mut text = String::new();
+    let mut current = None;
+    for &(c, cell_style) in cells {
+        if current.is_some_and(|current| current != cell_style) {
+            spans.push(Span::styled(std::mem::take(&mut text), current.unwrap()));
+        }
+        current = Some(cell_style);
+        text.push(c);
+    }
+    if let Some(current) = current {
+        spans.push(Span::styled(text, current));
+    }
+    Line::from(spans).style(style)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::Color;
+
+    #[test]
+    fn test_wrap_line_breaks_after_spaces() {
+        let bold = Style::default().fg(Color::Red);
+        let line = Line::from(vec![
+            Span::raw("aaa bbb "),
+            Span::styled("cccccccccc", bold),
+        ]);
+        let rows = wrap_line(line, 6);
+        let text: Vec<_> = rows
+            .iter()
+            .map(|(column, row)| (*column, row.to_string()))
+            .collect();
+
+        assert_eq!(
+            text,
+            [
+                (0, "aaa ".to_string()),
+                (4, "bbb ".to_string()),
+                (8, "cccccc".to_string()),
+                (14, "cccc".to_string()),
+            ]
+        );
+        assert_eq!(rows[2].1.spans[0].style, bold);
+    }
+
+    #[test]
+    fn test_wrap_line_wide_chars() {
+        let rows = wrap_line(Line::from("日本語の文章"), 5);
+        let text: Vec<_> = rows.iter().map(|(_, row)| row.to_string()).collect();
+
+        assert_eq!(text, ["日本", "語の", "文章"]);
+    }
+}
@@ -1,8 +1,13 @@
 pub mod app;
 pub mod events;
+pub mod file_tree;
+pub mod hyperlink;
+pub mod layout;
 pub mod navigation;
+pub mod search;
 pub mod themes;
+pub mod toc;
 pub mod ui;
 
-pub use app::App;
+pub use app::{App, AppAction};
 pub use themes::{ThemeManager, UiTheme};
@@ -1,2 +1,120 @@
-// Navigation functionality is implemented in app.rs
-// This module is reserved for future navigation enhancements
+//! ナビゲーションの履歴（`Ctrl-o` / `Ctrl-i` で戻る・進む）
+
+use std::path::PathBuf;
+
+/// 履歴に残す数
+const MAX_JUMPS: usize = 100;
+
+/// ジャンプする前の位置
+#[derive(Debug, Clone, PartialEq)]
+pub struct Jump {
+    pub path: PathBuf,
+    /// 画面上端のブロック
+    pub block: usize,
+    /// ブロックの先頭から数えた画面上端の行
+    pub row: usize,
+    /// パンくずに表示する名前（その位置のセクションの見出し）
+    pub label: String,
+}
+
+impl Jump {
+    fn same_position(&self, other: &Jump) -> bool {
+        self.path == other.path && self.block == other.block && self.row == other.row
+    }
+}
+
+/// ブラウザの履歴と同じく、戻った所から新しくジャンプすると先の履歴は捨てる
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct JumpList {
+    entries: Vec<Jump>,
+    /// 今いる位置の `entries` のインデックス（末尾の次なら履歴をたどっていない）
+    index: usize,
+}
+
+impl JumpList {
+    /// ジャンプする前の位置を記録する
+    pub fn push(&mut self, jump: Jump) {
+        self.entries.truncate(self.index);
+        if !self
+            .entries
+            .last()
+            .is_some_and(|last| last.same_position(&jump))
+        {
+            self.entries.push(jump);
+        }
+        if self.entries.len() > MAX_JUMPS {
+            self.entries.remove(0);
+        }
+        self.index = self.entries.len();
+    }
+
+    /// 1つ前の位置。`current` は進むで戻ってこられるように記録する
+    pub fn back(&mut self, current: Jump) -> Option<Jump> {
+        if self.index == self.entries.len() {
+            if self
+                .entries
+                .last()
+                .is_some_and(|last| last.same_position(&current))
+            {
+                self.index -= 1;
+            } else {
+                self.entries.push(current);
+            }
+        } else {
+            self.entries[self.index] = current;
+        }
+
+        self.index = self.index.checked_sub(1)?;
+        Some(self.entries[self.index].clone())
+    }
+
+    /// 戻る前にいた位置
+    pub fn forward(&mut self, current: Jump) -> Option<Jump> {
+        if self.index + 1 >= self.entries.len() {
+            return None;
+        }
+        self.entries[self.index] = current;
+        self.index += 1;
+        Some(self.entries[self.index].clone())
+    }
+
+    /// 今の位置より前の履歴（古い順）
+    pub fn history(&self) -> &[Jump] {
+        &self.entries[..self.index.min(self.entries.len())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jump(block: usize) -> Jump {
+        Jump {
+            path: PathBuf::from("a.md"),
+            block,
+            row: 0,
+            label: format!("Block {block}"),
+        }
+    }
+
+    #[test]
+    fn test_back_and_forward() {
+        let mut jumps = JumpList::default();
+        jumps.push(jump(0));
+        jumps.push(jump(10));
+
+        // 20 から戻ると 10、0 の順にたどり、進むで 20 まで戻る
+        assert_eq!(jumps.back(jump(20)), Some(jump(10)));
+        assert_eq!(jumps.back(jump(10)), Some(jump(0)));
+        assert_eq!(jumps.back(jump(0)), None);
+        assert_eq!(jumps.forward(jump(0)), Some(jump(10)));
+        assert_eq!(jumps.forward(jump(10)), Some(jump(20)));
+        assert_eq!(jumps.forward(jump(20)), None);
+
+        // 戻った所から新しくジャンプすると先の履歴は捨てる
+        jumps.back(jump(20));
+        jumps.push(jump(12));
+        assert_eq!(jumps.history(), &[jump(0), jump(12)]);
+        assert_eq!(jumps.forward(jump(30)), None);
+    }
+}
@@ -0,0 +1,218 @@
+//! 表示中の文書（マークダウンの記号を除いた描画後のテキスト）の検索
+
+use crate::markdown::MarkdownDocument;
+use crate::tui::layout::DocumentLayout;
+use crate::tui::ui::render_nested_line;
+use crate::tui::UiTheme;
+use regex::{Regex, RegexBuilder};
+use unicode_width::UnicodeWidthStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchDirection {
+    /// `/` で始めた検索
+    Forward,
+    /// `?` で始めた検索
+    Backward,
+}
+
+/// 検索語
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchQuery {
+    pub pattern: String,
+    /// 正規表現として扱うか（既定は文字列そのまま）
+    pub regex: bool,
+    pub direction: SearchDirection,
+}
+
+impl SearchQuery {
+    pub fn new(direction: SearchDirection) -> Self {
+        Self {
+            pattern: String::new(),
+            regex: false,
+            direction,
+        }
+    }
+
+    /// 大文字を含まなければ大文字・小文字を区別しない（smart-case）
+    pub fn ignore_case(&self) -> bool {
+        !self.pattern.chars().any(char::is_uppercase)
+    }
+
+    fn matcher(&self) -> Result<Regex, regex::Error> {
+        let pattern = if self.regex {
+            self.pattern.clone()
+        } else {
+            regex::escape(&self.pattern)
+        };
+        RegexBuilder::new(&pattern)
+            .case_insensitive(self.ignore_case())
+            .build()
+    }
+}
+
+/// 検索にマッチした位置
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchMatch {
+    /// `parsed_lines` のインデックス
+    pub block: usize,
+    /// レイアウト上の行（閉じた `<details>` や折りたたんだ所の中なら `None`）
+    pub row: Option<usize>,
+    pub column: usize,
+    pub width: usize,
+}
+
+/// 検索語の入力中の状態（`Esc` で入力前に戻すための情報）
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchPrompt {
+    /// 入力を始めたときのスクロール位置
+    pub origin: usize,
+    /// 入力を始める前の検索
+    pub previous: Option<SearchState>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchState {
+    pub query: SearchQuery,
+    pub matches: Vec<SearchMatch>,
+    /// 選択中のマッチ
+    pub current: Option<usize>,
+    /// 正規表現の誤り
+    pub error: Option<String>,
+}
+
+impl SearchState {
+    pub fn new(query: SearchQuery) -> Self {
+        Self {
+            query,
+            matches: Vec::new(),
+            current: None,
+            error: None,
+        }
+    }
+
+    /// `3/17` 形式の位置
+    pub fn counter(&self) -> Option<String> {
+        let current = self.current?;
+        Some(format!("{}/{}", current + 1, self.matches.len()))
+    }
+
+    /// マッチを探し直す
+    pub fn update(
+        &mut self,
+        document: &MarkdownDocument,
+        layout: &DocumentLayout,
+        theme: &UiTheme,
+    ) {
+        self.error = None;
+        self.matches = match self.query.matcher() {
+            Ok(_) if self.query.pattern.is_empty() => Vec::new(),
+            Ok(matcher) => find_matches(&matcher, document, layout, theme),
+            Err(e) => {
+                self.error = Some(error_summary(&e));
+                Vec::new()
+            }
+        };
+        self.current = self
+            .current
+            .filter(|_| !self.matches.is_empty())
+            .map(|current| current.min(self.matches.len() - 1));
+    }
+}
+
+fn error_summary(error: &regex::Error) -> String {
+    match error {
+        // 構文エラーは複数行の説明になるので最後の行だけ使う
+        regex::Error::Syntax(message) => message
+            .lines()
+            .last()
+            .unwrap_or_default()
+            .trim_start_matches("error: ")
+            .to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// レイアウトの各行と、閉じた `<details>` や折りたたんだ所のブロックからマッチを探す
+fn find_matches(
+    matcher: &Regex,
+    document: &MarkdownDocument,
+    layout: &DocumentLayout,
+    theme: &UiTheme,
+) -> Vec<SearchMatch> {
+    let mut matches = Vec::new();
+
+    for (row, line) in layout.rows(0..layout.row_count()).iter().enumerate() {
+        // 折りたたんだコードブロック・テーブルは見出し行ではなく中身を探す
+        if document.is_folded_content(layout.block_at_row(row)) {
+            continue;
+        }
+        let text = line.to_string();
+        for found in matcher.find_iter(&text).filter(|found| !found.is_empty()) {
+            matches.push(SearchMatch {
+                block: layout.block_at_row(row),
+                row: Some(row),
+                column: text[..found.start()].width(),
+                width: found.as_str().width(),
+            });
+        }
+    }
+
+    // 表示されていないブロックも描画したテキストで探す
+    for (block, (line, nesting)) in document
+        .parsed_lines
+        .iter()
+        .zip(&document.nesting)
+        .enumerate()
+        .filter(|(block, _)| document.is_hidden(*block) || document.is_folded_content(*block))
+    {
+        let lines = render_nested_line(
+            line,
+            nesting,
+            theme,
+            layout.area_width as usize,
+            &mut Vec::new(),
+        );
+        for line in lines {
+            let text = line.to_string();
+            for found in matcher.find_iter(&text).filter(|found| !found.is_empty()) {
+                matches.push(SearchMatch {
+                    block,
+                    row: None,
+                    column: text[..found.start()].width(),
+                    width: found.as_str().width(),
+                });
+            }
+        }
+    }
+
+    matches.sort_by_key(|found| (found.block, found.row, found.column));
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_smart_case() {
+        let mut query = SearchQuery::new(SearchDirection::Forward);
+        query.pattern = "rust".to_string();
+        assert!(query.matcher().unwrap().is_match("Rust"));
+
+        query.pattern = "Rust".to_string();
+        assert!(!query.matcher().unwrap().is_match("rust"));
+    }
+
+    #[test]
+    fn test_literal_and_regex() {
+        let mut query = SearchQuery::new(SearchDirection::Forward);
+        query.pattern = "a.c".to_string();
+        assert!(!query.matcher().unwrap().is_match("abc"));
+
+        query.regex = true;
+        assert!(query.matcher().unwrap().is_match("abc"));
+
+        query.pattern = "(".to_string();
+        assert!(query.matcher().is_err());
+    }
+}
@@ -153,6 +153,126 @@ impl InlineCodeColors {
     }
 }
 
+/// リンクの色
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkColors {
+    pub text: String,
+}
+
+impl LinkColors {
+    pub fn text(&self) -> Color {
+        UiTheme::parse_color(&self.text)
+    }
+}
+
+impl Default for LinkColors {
+    fn default() -> Self {
+        Self {
+            text: "LightBlue".to_string(),
+        }
+    }
+}
+
+/// 脚注の色
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FootnoteColors {
+    pub marker: String,
+}
+
+impl FootnoteColors {
+    pub fn marker(&self) -> Color {
+        UiTheme::parse_color(&self.marker)
+    }
+}
+
+impl Default for FootnoteColors {
+    fn default() -> Self {
+        Self {
+            marker: "Cyan".to_string(),
+        }
+    }
+}
+
+/// 数式の色
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MathColors {
+    pub text: String,
+}
+
+impl MathColors {
+    pub fn text(&self) -> Color {
+        UiTheme::parse_color(&self.text)
+    }
+}
+
+/// `<kbd>` のキーキャップの色
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KbdColors {
+    pub foreground: String,
+    pub background: String,
+}
+
+impl KbdColors {
+    pub fn foreground(&self) -> Color {
+        UiTheme::parse_color(&self.foreground)
+    }
+
+    pub fn background(&self) -> Color {
+        UiTheme::parse_color(&self.background)
+    }
+}
+
+impl Default for KbdColors {
+    fn default() -> Self {
+        Self {
+            foreground: "White".to_string(),
+            background: "DarkGray".to_string(),
+        }
+    }
+}
+
+/// 検索でマッチした文字の色
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchColors {
+    pub foreground: String,
+    /// マッチの背景
+    pub background: String,
+    /// 選択中のマッチの背景
+    pub current: String,
+}
+
+impl SearchColors {
+    pub fn foreground(&self) -> Color {
+        UiTheme::parse_color(&self.foreground)
+    }
+
+    pub fn background(&self) -> Color {
+        UiTheme::parse_color(&self.background)
+    }
+
+    pub fn current(&self) -> Color {
+        UiTheme::parse_color(&self.current)
+    }
+}
+
+impl Default for SearchColors {
+    fn default() -> Self {
+        Self {
+            foreground: "Black".to_string(),
+            background: "Yellow".to_string(),
+            current: "LightRed".to_string(),
+        }
+    }
+}
+
+impl Default for MathColors {
+    fn default() -> Self {
+        Self {
+            text: "LightYellow".to_string(),
+        }
+    }
+}
+
 /// 境界線の色
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BorderColors {
@@ -235,6 +355,9 @@ pub struct LayoutSettings {
     pub wrap_text: bool,
     pub toc_width_percent: u8,
     pub code_block_width_percent: u8,
+    /// 入れ子の番号付きリストを `1.` → `a.` → `i.` で表示する
+    #[serde(default)]
+    pub nested_list_numbering: bool,
 }
 
 impl LayoutSettings {
@@ -247,6 +370,9 @@ impl LayoutSettings {
     pub fn code_block_width_percent(&self) -> u8 {
         self.code_block_width_percent
     }
+    pub fn nested_list_numbering(&self) -> bool {
+        self.nested_list_numbering
+    }
 }
 
 /// UIテーマ構造体
@@ -266,6 +392,21 @@ pub struct UiTheme {
     pub table: TableColors,
     /// インラインコードの色
     pub inline_code: InlineCodeColors,
+    /// リンクの色（古いテーマファイルとの互換のため省略可能）
+    #[serde(default)]
+    pub link: LinkColors,
+    /// 脚注の色（古いテーマファイルとの互換のため省略可能）
+    #[serde(default)]
+    pub footnote: FootnoteColors,
+    /// 数式の色（古いテーマファイルとの互換のため省略可能）
+    #[serde(default)]
+    pub math: MathColors,
+    /// `<kbd>` の色（古いテーマファイルとの互換のため省略可能）
+    #[serde(default)]
+    pub kbd: KbdColors,
+    /// 検索のマッチの色（古いテーマファイルとの互換のため省略可能）
+    #[serde(default)]
+    pub search: SearchColors,
     /// 境界線の色
     pub border: BorderColors,
     /// テキストの色
@@ -362,6 +503,24 @@ impl UiTheme {
                 foreground: "Yellow".to_string(),
                 background: "DarkGray".to_string(),
             },
+            link: LinkColors {
+                text: "LightBlue".to_string(),
+            },
+            footnote: FootnoteColors {
+                marker: "Cyan".to_string(),
+            },
+            math: MathColors {
+                text: "LightYellow".to_string(),
+            },
+            kbd: KbdColors {
+                foreground: "White".to_string(),
+                background: "DarkGray".to_string(),
+            },
+            search: SearchColors {
+                foreground: "Black".to_string(),
+                background: "Yellow".to_string(),
+                current: "LightRed".to_string(),
+            },
             border: BorderColors {
                 primary: "Gray".to_string(),
                 secondary: "DarkGray".to_string(),
@@ -385,6 +544,7 @@ impl UiTheme {
                 wrap_text: false,
                 toc_width_percent: 25,
                 code_block_width_percent: 85,
+                nested_list_numbering: false,
             },
         }
     }
@@ -449,6 +609,24 @@ impl UiTheme {
                 foreground: "Black".to_string(),
                 background: "LightGray".to_string(),
             },
+            link: LinkColors {
+                text: "Blue".to_string(),
+            },
+            footnote: FootnoteColors {
+                marker: "Magenta".to_string(),
+            },
+            math: MathColors {
+                text: "Magenta".to_string(),
+            },
+            kbd: KbdColors {
+                foreground: "Black".to_string(),
+                background: "Gray".to_string(),
+            },
+            search: SearchColors {
+                foreground: "Black".to_string(),
+                background: "LightYellow".to_string(),
+                current: "LightMagenta".to_string(),
+            },
             border: BorderColors {
                 primary: "Gray".to_string(),
                 secondary: "LightGray".to_string(),
@@ -472,6 +650,7 @@ impl UiTheme {
                 wrap_text: false,
                 toc_width_percent: 25,
                 code_block_width_percent: 85,
+                nested_list_numbering: false,
             },
         }
     }
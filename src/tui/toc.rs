@@ -0,0 +1,171 @@
+//! 目次ペインの木構造（折りたたみ）と絞り込み
+
+use crate::markdown::TocEntry;
+use std::collections::HashSet;
+
+/// 目次の折りたたみと絞り込みの状態
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TocView {
+    /// 折りたたんだ見出しのアンカー（再読み込み後も引き継ぐ）
+    pub collapsed: HashSet<String>,
+    /// 絞り込みの文字列（`None` なら絞り込みなし）
+    pub filter: Option<String>,
+    /// 絞り込みの文字列を入力中か
+    pub filtering: bool,
+}
+
+/// 目次ペインの1行
+#[derive(Debug, Clone, PartialEq)]
+pub struct TocRow {
+    /// `toc` のインデックス
+    pub index: usize,
+    pub has_children: bool,
+    pub collapsed: bool,
+    /// 絞り込みの文字列に一致した文字の位置（文字単位）
+    pub matched: Vec<usize>,
+}
+
+impl TocView {
+    /// 表示する行。絞り込み中は一致した見出しを折りたたみに関係なくすべて並べる
+    pub fn rows(&self, toc: &[TocEntry]) -> Vec<TocRow> {
+        let mut rows = Vec::new();
+        // この深さより深い見出しは折りたたまれた見出しの下にある
+        let mut hidden_below: Option<usize> = None;
+
+        for (index, entry) in toc.iter().enumerate() {
+            let has_children = has_children(toc, index);
+            let collapsed = has_children && self.collapsed.contains(&entry.anchor);
+
+            match self.filter.as_deref().filter(|filter| !filter.is_empty()) {
+                Some(filter) => {
+                    if let Some(matched) = fuzzy_match(filter, &entry.title) {
+                        rows.push(TocRow {
+                            index,
+                            has_children,
+                            collapsed,
+                            matched,
+                        });
+                    }
+                }
+                None => {
+                    if hidden_below.is_some_and(|level| entry.level > level) {
+                        continue;
+                    }
+                    hidden_below = collapsed.then_some(entry.level);
+                    rows.push(TocRow {
+                        index,
+                        has_children,
+                        collapsed,
+                        matched: Vec::new(),
+                    });
+                }
+            }
+        }
+        rows
+    }
+
+    pub fn is_collapsed(&self, entry: &TocEntry) -> bool {
+        self.collapsed.contains(&entry.anchor)
+    }
+
+    pub fn set_collapsed(&mut self, entry: &TocEntry, collapsed: bool) {
+        if collapsed {
+            self.collapsed.insert(entry.anchor.clone());
+        } else {
+            self.collapsed.remove(&entry.anchor);
+        }
+    }
+}
+
+/// 見出しの深さに応じた字下げの幅
+pub fn indent_width(level: usize) -> usize {
+    level.saturating_sub(1) * 2
+}
+
+/// 直後の見出しの方が深ければ子を持つ
+pub fn has_children(toc: &[TocEntry], index: usize) -> bool {
+    match (toc.get(index), toc.get(index + 1)) {
+        (Some(entry), Some(next)) => next.level > entry.level,
+        _ => false,
+    }
+}
+
+/// 親の見出し（手前にある、より浅い見出し）
+pub fn parent(toc: &[TocEntry], index: usize) -> Option<usize> {
+    let level = toc.get(index)?.level;
+    toc[..index].iter().rposition(|entry| entry.level < level)
+}
+
+/// `pattern` の文字が順番どおりに `text` に含まれていれば、一致した文字の位置を返す
+///
+/// 大文字を含まなければ大文字・小文字を区別しない（検索と同じ smart-case）。
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<Vec<usize>> {
+    let ignore_case = !pattern.chars().any(char::is_uppercase);
+    let normalize = |c: char| {
+        if ignore_case {
+            c.to_lowercase().next().unwrap_or(c)
+        } else {
+            c
+        }
+    };
+
+    let mut matched = Vec::new();
+    let mut pattern = pattern.chars().filter(|c| !c.is_whitespace()).peekable();
+    for (position, c) in text.chars().enumerate() {
+        let Some(&wanted) = pattern.peek() else {
+            break;
+        };
+        if normalize(c) == normalize(wanted) {
+            matched.push(position);
+            pattern.next();
+        }
+    }
+    pattern.peek().is_none().then_some(matched)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown::slugify;
+
+    fn entry(level: usize, title: &str) -> TocEntry {
+        TocEntry {
+            level,
+            title: title.to_string(),
+            line_number: 1,
+            anchor: slugify(title),
+        }
+    }
+
+    #[test]
+    fn test_fuzzy_match() {
+        assert_eq!(fuzzy_match("ist", "Installation"), Some(vec![0, 2, 3]));
+        assert_eq!(fuzzy_match("in st", "Installation"), Some(vec![0, 1, 2, 3]));
+        assert_eq!(fuzzy_match("usage", "Installation"), None);
+        // 大文字を含むと区別する
+        assert_eq!(fuzzy_match("Inst", "install"), None);
+    }
+
+    #[test]
+    fn test_collapsed_section_hides_descendants() {
+        let toc = vec![
+            entry(1, "Guide"),
+            entry(2, "Install"),
+            entry(3, "Linux"),
+            entry(2, "Usage"),
+            entry(1, "License"),
+        ];
+        let mut view = TocView::default();
+        view.set_collapsed(&toc[1], true);
+
+        let indices: Vec<usize> = view.rows(&toc).iter().map(|row| row.index).collect();
+        assert_eq!(indices, vec![0, 1, 3, 4]);
+        assert_eq!(parent(&toc, 2), Some(1));
+        assert_eq!(parent(&toc, 4), None);
+
+        // 絞り込み中は折りたたまれた見出しも対象にする
+        view.filter = Some("lin".to_string());
+        let indices: Vec<usize> = view.rows(&toc).iter().map(|row| row.index).collect();
+        assert_eq!(indices, vec![2, 4]);
+    }
+}
@@ -1,11 +1,22 @@
-use crate::markdown::{Alignment, ParsedLine};
+use crate::markdown::inline::{is_blank, plain_text, push_span, split_lines, superscript};
+use crate::markdown::parser::AlertType;
+use crate::markdown::{
+    math, Alignment, CodeInfo, ContainerKind, FrontMatterFormat, InlineSpan, ListMarker, Metadata,
+    Nesting, ParsedLine, TocEntry,
+};
 use crate::tui::app::App;
+use crate::tui::file_tree::FileTree;
+use crate::tui::search::{SearchDirection, SearchState};
+use crate::tui::toc::{self, TocRow};
 use crate::tui::UiTheme;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    widgets::{
+        Block, Borders, Clear, List, ListItem, ListState, Paragraph, Scrollbar,
+        ScrollbarOrientation, ScrollbarState,
+    },
     Frame,
 };
 use syntect::highlighting::Color as SyntectColor;
@@ -23,8 +34,8 @@ pub fn calculate_toc_width<'a>(app: &App<'a>, theme: &UiTheme, terminal_width: u
         .toc
         .iter()
         .map(|entry| {
-            let indent = "  ".repeat(entry.level.saturating_sub(1));
-            visible_text_len(&format!("{}{}>> ", indent, entry.title)) + 4 // padding and border
+            // indentation, fold marker, padding and border
+            toc::indent_width(entry.level) + 2 + visible_text_len(&entry.title) + 4
         })
         .max()
         .unwrap_or(20);
@@ -42,61 +53,89 @@ pub fn calculate_toc_width<'a>(app: &App<'a>, theme: &UiTheme, terminal_width: u
     (content_len as u16).clamp(min_toc_width, max_toc_width as u16)
 }
 
-/// Improved word wrapping function with Unicode support
-fn wrap_text(text: &str, max_width: usize) -> Vec<String> {
-    if text.is_empty() {
-        return vec![String::new()];
+/// Word wrapping for inline spans with Unicode support
+///
+/// Words may cross span boundaries (e.g. `**bo**ld`); the whitespace between
+/// words keeps the style of the span it came from.
+fn wrap_spans(spans: &[InlineSpan], max_width: usize) -> Vec<Vec<InlineSpan>> {
+    // (separator before the word, word)
+    let mut words: Vec<(InlineSpan, Vec<InlineSpan>)> = Vec::new();
+    let mut separator = InlineSpan::plain(" ");
+    let mut word: Vec<InlineSpan> = Vec::new();
+
+    for span in spans {
+        for (i, part) in span.text.split(char::is_whitespace).enumerate() {
+            if i > 0 {
+                if !word.is_empty() {
+                    words.push((separator.clone(), std::mem::take(&mut word)));
+                }
+                separator = InlineSpan {
+                    text: " ".to_string(),
+                    ..span.clone()
+                };
+            }
+            push_span(
+                &mut word,
+                InlineSpan {
+                    text: part.to_string(),
+                    ..span.clone()
+                },
+            );
+        }
+    }
+    if !word.is_empty() {
+        words.push((separator, word));
     }
 
     let mut result = Vec::new();
-    let mut current_line = String::new();
+    let mut current_line: Vec<InlineSpan> = Vec::new();
 
-    // Split by whitespace, but preserve spaces within words
-    for word in text.split_whitespace() {
-        let word_len = visible_text_len(word);
-        let current_len = visible_text_len(&current_line);
+    for (separator, word) in words {
+        let word_len = spans_width(&word);
+        let current_len = spans_width(&current_line);
 
         if current_len + word_len < max_width || current_line.is_empty() {
             // Word fits on current line
             if !current_line.is_empty() {
-                current_line.push(' ');
+                push_span(&mut current_line, separator);
+            }
+            for span in word {
+                push_span(&mut current_line, span);
             }
-            current_line.push_str(word);
         } else {
             // Word doesn't fit
             if !current_line.is_empty() {
-                result.push(current_line);
-                current_line = String::new();
+                result.push(std::mem::take(&mut current_line));
             }
 
             // Handle very long words by breaking them
             if word_len > max_width {
-                let mut remaining = word;
-                while !remaining.is_empty() {
-                    let mut chunk_len = 0;
-                    let mut chunk = String::new();
+                let mut chunk: Vec<InlineSpan> = Vec::new();
+                let mut chunk_len = 0;
 
-                    for ch in remaining.chars() {
+                for span in word {
+                    for ch in span.text.chars() {
                         let ch_width = ch.width().unwrap_or(1);
-                        if chunk_len + ch_width > max_width {
-                            break;
+                        if chunk_len + ch_width > max_width && !chunk.is_empty() {
+                            result.push(std::mem::take(&mut chunk));
+                            chunk_len = 0;
                         }
-                        chunk.push(ch);
+                        push_span(
+                            &mut chunk,
+                            InlineSpan {
+                                text: ch.to_string(),
+                                ..span.clone()
+                            },
+                        );
                         chunk_len += ch_width;
                     }
+                }
 
-                    if chunk.is_empty() {
-                        // Single character is too wide, add anyway
-                        chunk.push(remaining.chars().next().unwrap());
-                        remaining = &remaining[chunk.len()..];
-                    } else {
-                        remaining = &remaining[chunk.len()..];
-                    }
-
+                if !chunk.is_empty() {
                     result.push(chunk);
                 }
             } else {
-                current_line = word.to_string();
+                current_line = word;
             }
         }
     }
@@ -106,7 +145,7 @@ fn wrap_text(text: &str, max_width: usize) -> Vec<String> {
     }
 
     if result.is_empty() {
-        result.push(String::new());
+        result.push(Vec::new());
     }
 
     result
@@ -120,14 +159,45 @@ fn render_status_bar<'a>(
     theme_manager: &'a crate::tui::ThemeManager,
 ) {
     let theme = &theme_manager.current_theme();
-    let status_text = format!(
-        " {} | Line {}/{} | Mode: {} | Theme: {} ",
-        app.document.path.display(),
-        app.current_line + 1,
-        app.document.parsed_lines.len(),
-        if app.show_toc { "TOC" } else { "View" },
-        theme_manager.current_theme_name()
-    );
+    let status_area = Rect {
+        x: area.x,
+        y: area.y + area.height - 1,
+        width: area.width,
+        height: 1,
+    };
+
+    if let Some(command) = &app.command_line {
+        render_command_line(frame, status_area, command, theme);
+        return;
+    }
+
+    if app.search_prompt.is_some() {
+        if let Some(search) = &app.search {
+            render_search_prompt(frame, status_area, search, theme);
+            return;
+        }
+    }
+
+    let match_counter = app
+        .search
+        .as_ref()
+        .and_then(|search| search.counter())
+        .map(|counter| format!(" | Match {counter}"))
+        .unwrap_or_default();
+    let breadcrumbs = format_breadcrumbs(&app.breadcrumbs());
+    let status_text = match &app.status_message {
+        Some(message) => format!(" {message} "),
+        None => format!(
+            " {}{} | Line {}/{}{} | Mode: {} | Theme: {} ",
+            app.document.display_title(),
+            breadcrumbs,
+            app.document.source_line(app.top_block()),
+            app.document.line_count(),
+            match_counter,
+            if app.show_toc { "TOC" } else { "View" },
+            theme_manager.current_theme_name()
+        ),
+    };
 
     let status_bar = Paragraph::new(status_text)
         .style(
@@ -137,14 +207,139 @@ fn render_status_bar<'a>(
         )
         .alignment(ratatui::layout::Alignment::Center);
 
-    let status_area = Rect {
-        x: area.x,
-        y: area.y + area.height - 1,
-        width: area.width,
-        height: 1,
+    frame.render_widget(status_bar, status_area);
+}
+
+/// The jump history as ` | Intro › Install › Usage`, keeping the most recent entries
+fn format_breadcrumbs(labels: &[String]) -> String {
+    const MAX_CRUMBS: usize = 4;
+    const MAX_LABEL_WIDTH: usize = 24;
+
+    if labels.is_empty() {
+        return String::new();
+    }
+    let skipped = labels.len().saturating_sub(MAX_CRUMBS);
+    let mut crumbs: Vec<String> = labels[skipped..]
+        .iter()
+        .map(|label| {
+            if visible_text_len(label) > MAX_LABEL_WIDTH {
+                let mut width = 0;
+                let short: String = label
+                    .chars()
+                    .take_while(|c| {
+                        width += c.width().unwrap_or(0);
+                        width < MAX_LABEL_WIDTH
+                    })
+                    .collect();
+                format!("{short}…")
+            } else {
+                label.clone()
+            }
+        })
+        .collect();
+    if skipped > 0 {
+        crumbs.insert(0, "…".to_string());
+    }
+    format!(" | {}", crumbs.join(" › "))
+}
+
+/// Search input in place of the status bar, e.g. `/pattern   [regex] 3/17`
+fn render_search_prompt(frame: &mut Frame, area: Rect, search: &SearchState, theme: &UiTheme) {
+    let prefix = match search.query.direction {
+        SearchDirection::Forward => "/",
+        SearchDirection::Backward => "?",
     };
+    let input = format!("{prefix}{}", search.query.pattern);
 
-    frame.render_widget(status_bar, status_area);
+    let mut info = Vec::new();
+    if search.query.regex {
+        info.push("[regex]".to_string());
+    }
+    if let Some(error) = &search.error {
+        info.push(format!("Invalid regex: {error}"));
+    } else if !search.query.pattern.is_empty() {
+        info.push(search.counter().unwrap_or_else(|| "No matches".to_string()));
+    }
+    let info = info.join(" ");
+
+    let style = Style::default()
+        .fg(theme.status_bar.foreground())
+        .bg(theme.status_bar.background());
+    let padding = (area.width as usize)
+        .saturating_sub(visible_text_len(&input) + visible_text_len(&info) + 1);
+    let line = Line::from(vec![
+        Span::raw(input.clone()),
+        Span::raw(" ".repeat(padding)),
+        Span::raw(format!("{info} ")),
+    ]);
+    frame.render_widget(Paragraph::new(line).style(style), area);
+
+    let cursor_x = area.x + (visible_text_len(&input) as u16).min(area.width.saturating_sub(1));
+    frame.set_cursor_position((cursor_x, area.y));
+}
+
+/// Command input in place of the status bar, e.g. `:b 2`
+fn render_command_line(frame: &mut Frame, area: Rect, command: &str, theme: &UiTheme) {
+    let style = Style::default()
+        .fg(theme.status_bar.foreground())
+        .bg(theme.status_bar.background());
+    let input = format!(":{command}");
+    frame.render_widget(Paragraph::new(input.clone()).style(style), area);
+
+    let cursor_x = area.x + (visible_text_len(&input) as u16).min(area.width.saturating_sub(1));
+    frame.set_cursor_position((cursor_x, area.y));
+}
+
+/// One tab per open file (` 1:a.md `), scrolled so that the current one is visible
+fn render_tab_bar(frame: &mut Frame, area: Rect, app: &App, theme: &UiTheme) {
+    let bar_style = Style::default()
+        .fg(theme.status_bar.foreground())
+        .bg(theme.status_bar.background());
+    let current_style = Style::default()
+        .fg(theme.toc.selected())
+        .bg(theme.toc.highlight_bg())
+        .add_modifier(Modifier::BOLD);
+
+    let tabs: Vec<String> = app
+        .documents()
+        .enumerate()
+        .map(|(index, document)| {
+            let name = document
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| document.path.display().to_string());
+            format!(" {}:{name} ", index + 1)
+        })
+        .collect();
+
+    // 表示中のタブが右端からはみ出すなら左のタブを省く
+    let current = app.current_buffer();
+    let mut first = 0;
+    while first < current
+        && tabs[first..=current]
+            .iter()
+            .map(|tab| visible_text_len(tab))
+            .sum::<usize>()
+            > area.width as usize
+    {
+        first += 1;
+    }
+
+    let spans: Vec<Span> = tabs
+        .into_iter()
+        .enumerate()
+        .skip(first)
+        .map(|(index, tab)| {
+            let style = if index == current {
+                current_style
+            } else {
+                bar_style
+            };
+            Span::styled(tab, style)
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(Line::from(spans)).style(bar_style), area);
 }
 
 /// Render the TUI interface
@@ -153,14 +348,52 @@ pub fn render<'a>(
     app: &mut App<'a>,
     theme_manager: &'a crate::tui::ThemeManager,
 ) {
-    let size = frame.area();
-    app.viewport_height = size.height.saturating_sub(3) as usize; // -1 for status bar, -2 for borders
+    let screen = frame.area();
     let theme = &theme_manager.current_theme();
 
+    // 複数のファイルを開いているときは最上行にタブを並べる
+    let size = if app.buffer_count() > 1 {
+        render_tab_bar(
+            frame,
+            Rect {
+                height: 1,
+                ..screen
+            },
+            app,
+            theme,
+        );
+        Rect {
+            y: screen.y + 1,
+            height: screen.height.saturating_sub(1),
+            ..screen
+        }
+    } else {
+        screen
+    };
+
+    // The file tree of a directory sits on the left of the content
+    let body = match &app.file_tree {
+        Some(tree) if app.show_file_tree => {
+            let tree_width = calculate_file_tree_width(tree, theme, size.width);
+            let chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Length(tree_width), Constraint::Min(0)])
+                .split(size);
+            render_file_tree(frame, chunks[0], app, theme);
+            chunks[1]
+        }
+        _ => size,
+    };
+
+    let metadata_height = match &app.document.metadata {
+        Some(metadata) if app.show_metadata => metadata_panel_height(metadata, body.height),
+        _ => 0,
+    };
+
     if app.show_toc {
         // Calculate TOC width based on content (with caching)
-        let toc_width = app.get_toc_width(theme, size.width);
-        let content_width = size.width.saturating_sub(toc_width);
+        let toc_width = app.get_toc_width(theme, body.width);
+        let content_width = body.width.saturating_sub(toc_width);
 
         let chunks = Layout::default()
             .direction(Direction::Horizontal)
@@ -168,20 +401,26 @@ pub fn render<'a>(
                 Constraint::Length(content_width),
                 Constraint::Length(toc_width),
             ])
-            .split(size);
+            .split(body);
 
-        render_content(frame, chunks[0], app, theme);
+        render_document(frame, chunks[0], app, theme, metadata_height);
+        app.sync_toc_selection();
         render_toc(frame, chunks[1], app, theme);
     } else {
-        render_content(frame, size, app, theme);
+        render_document(frame, body, app, theme, metadata_height);
+        app.sync_toc_selection();
     }
 
     // Render status bar at the bottom
     render_status_bar(frame, size, app, theme_manager);
 
+    if !app.footnote_popup.is_empty() {
+        render_footnote_popup(frame, screen, app, theme);
+    }
+
     // ヘルプオーバーレイは最後に描画して最前面に表示
     if app.show_help {
-        render_help_overlay(frame, size, theme);
+        render_help_overlay(frame, screen, theme);
     }
 }
 
@@ -195,15 +434,33 @@ fn render_help_overlay(frame: &mut Frame, area: Rect, theme: &UiTheme) {
         )),
         Line::from(""),
         Line::from(" j / k or Up / Down   Scroll up / down"),
-        Line::from(" g / G                Jump to top / bottom"),
+        Line::from(" gg / G               Jump to top / bottom"),
         Line::from(" PageUp / PageDown    Scroll by one page"),
         Line::from(" t                    Toggle table of contents"),
+        Line::from(" e                    Toggle file tree (directories)"),
         Line::from(" Enter (in TOC)       Jump to selected heading"),
-        Line::from(" ?                    Toggle this help"),
+        Line::from(" h / l (in TOC)       Collapse / expand section"),
+        Line::from(" / (in TOC)           Filter headings"),
+        Line::from(" Tab / Shift+Tab      Select next / previous link"),
+        Line::from(" Enter (on link)      Follow selected link"),
+        Line::from(" Esc                  Clear link selection"),
+        Line::from(" Ctrl+O / Ctrl+I      Jump back / forward (also Backspace / ])"),
+        Line::from(" y                    Copy link to current heading"),
+        Line::from(" f                    Show footnotes on screen"),
+        Line::from(" m                    Toggle front matter panel"),
+        Line::from(" o                    Open / close <details> on screen"),
+        Line::from(" za / zc / zo         Toggle / close / open fold"),
+        Line::from(" zM / zR              Fold all sections / open all"),
+        Line::from(" / or ?               Search forward / backward"),
+        Line::from(" n / N                Next / previous match"),
+        Line::from(" Ctrl+R (searching)   Toggle regex search"),
+        Line::from(" gt / gT              Next / previous file"),
+        Line::from(" :b N or :b name      Switch to file (:ls lists them)"),
+        Line::from(" F1                   Toggle this help"),
         Line::from(" q / Ctrl+C           Quit"),
         Line::from(""),
         Line::from(Span::styled(
-            " Press ?, Esc or q to close ",
+            " Press F1, Esc or q to close ",
             Style::default().fg(theme.text.muted()),
         )),
         Line::from(""),
@@ -237,8 +494,167 @@ fn render_help_overlay(frame: &mut Frame, area: Rect, theme: &UiTheme) {
     frame.render_widget(popup, popup_area);
 }
 
-fn render_content<'a>(frame: &mut Frame, area: Rect, app: &App<'a>, theme: &UiTheme) {
-    let visible_count = area.height.saturating_sub(2) as usize;
+/// 画面内で参照されている脚注の本文をポップアップで表示する
+fn render_footnote_popup(frame: &mut Frame, area: Rect, app: &App, theme: &UiTheme) {
+    let popup_width = area.width.saturating_sub(4).clamp(20, 72);
+    let text_width = popup_width.saturating_sub(2) as usize;
+    let marker_style = Style::default()
+        .fg(theme.footnote.marker())
+        .add_modifier(Modifier::BOLD);
+
+    let mut lines: Vec<Line> = Vec::new();
+    for &number in &app.footnote_popup {
+        if !lines.is_empty() {
+            lines.push(Line::from(""));
+        }
+
+        let marker = format!(" {} ", superscript(number));
+        let marker_width = marker.chars().count();
+        let content = app.document.footnote(number).unwrap_or_default();
+        let mut first = true;
+
+        for line in split_lines(content) {
+            for wrapped_line in wrap_spans(&line, text_width.saturating_sub(marker_width)) {
+                let mut spans = if first {
+                    vec![Span::styled(marker.clone(), marker_style)]
+                } else {
+                    vec![Span::raw(" ".repeat(marker_width))]
+                };
+                spans.extend(inline_spans_to_ratatui(
+                    &wrapped_line,
+                    Style::default(),
+                    theme,
+                ));
+                lines.push(Line::from(spans));
+                first = false;
+            }
+        }
+    }
+
+    let popup_height = (lines.len() as u16 + 2).min(area.height.saturating_sub(2));
+    let popup_area = Rect {
+        x: area.x + (area.width.saturating_sub(popup_width)) / 2,
+        y: area.y + (area.height.saturating_sub(popup_height)) / 2,
+        width: popup_width.min(area.width),
+        height: popup_height,
+    };
+
+    let popup = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.border.primary()))
+            .title(" Footnotes ")
+            .title_bottom(Line::from(" f / Esc to close ").right_aligned()),
+    );
+
+    frame.render_widget(Clear, popup_area);
+    frame.render_widget(popup, popup_area);
+}
+
+/// A link as placed on screen by the last render
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScreenLink {
+    pub area: Rect,
+    /// `Link::index` of the link
+    pub index: usize,
+    pub url: String,
+}
+
+/// Areas placed by the last render, used for mouse hit-testing
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ScreenAreas {
+    /// Inside of the content border
+    pub content: Rect,
+    /// Scrollbar on the right border of the content, when the document overflows
+    pub scrollbar: Option<Rect>,
+    /// Inside of the TOC border the last time the TOC was shown
+    pub toc: Option<Rect>,
+    /// Inside of the file tree border the last time the file tree was shown
+    pub file_tree: Option<Rect>,
+}
+
+/// Render the content, with the metadata panel above it when shown
+fn render_document(
+    frame: &mut Frame,
+    area: Rect,
+    app: &mut App,
+    theme: &UiTheme,
+    metadata_height: u16,
+) {
+    match &app.document.metadata {
+        Some(metadata) if metadata_height > 0 => {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(metadata_height), Constraint::Min(0)])
+                .split(area);
+            render_metadata(frame, chunks[0], metadata, theme);
+            render_content(frame, chunks[1], app, theme);
+        }
+        _ => render_content(frame, area, app, theme),
+    }
+}
+
+/// Height of the metadata panel: one row per entry, at most half the screen
+fn metadata_panel_height(metadata: &Metadata, screen_height: u16) -> u16 {
+    let rows = metadata.entries.len().max(1) as u16 + 2;
+    rows.min(screen_height / 2)
+}
+
+fn render_metadata(frame: &mut Frame, area: Rect, metadata: &Metadata, theme: &UiTheme) {
+    let key_style = Style::default()
+        .fg(theme.heading.h3())
+        .add_modifier(Modifier::BOLD);
+    let key_width = metadata
+        .entries
+        .iter()
+        .map(|(key, _)| visible_text_len(key))
+        .max()
+        .unwrap_or(0);
+
+    let lines: Vec<Line> = if metadata.entries.is_empty() {
+        vec![Line::from(Span::styled(
+            "(empty)",
+            Style::default().fg(theme.text.muted()),
+        ))]
+    } else {
+        metadata
+            .entries
+            .iter()
+            .map(|(key, value)| {
+                let padding = " ".repeat(key_width - visible_text_len(key));
+                Line::from(vec![
+                    Span::styled(format!("{key}{padding}  "), key_style),
+                    Span::raw(value.to_string()),
+                ])
+            })
+            .collect()
+    };
+
+    let label = match metadata.format {
+        FrontMatterFormat::Yaml => " Metadata (YAML) ",
+        FrontMatterFormat::Toml => " Metadata (TOML) ",
+    };
+    let paragraph = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.border.primary()))
+            .title(label),
+    );
+    frame.render_widget(paragraph, area);
+}
+
+fn render_content<'a>(frame: &mut Frame, area: Rect, app: &mut App<'a>, theme: &UiTheme) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" {} ", app.document.display_title()));
+    let inner = block.inner(area);
+    let visible_count = inner.height as usize;
+
+    app.viewport_height = visible_count;
+    app.ensure_layout(area.width);
+    app.link_regions.clear();
+    app.screen_areas.content = inner;
+    app.screen_areas.scrollbar = None;
 
     // Handle empty document
     let visible_lines: Vec<Line> = if app.document.parsed_lines.is_empty() {
@@ -249,70 +665,318 @@ fn render_content<'a>(frame: &mut Frame, area: Rect, app: &App<'a>, theme: &UiTh
                 Style::default().fg(theme.text.muted()),
             )),
         ]
+    } else if let Some(layout) = &app.layout {
+        // The layout is already wrapped to the inner width, so the top block may be cut off
+        let rows = app.scroll_offset..app.scroll_offset + visible_count;
+        for link in layout.links_in(rows.clone()) {
+            app.link_regions.push(ScreenLink {
+                area: Rect {
+                    x: inner.x + link.column as u16,
+                    y: inner.y + (link.row - app.scroll_offset) as u16,
+                    width: link.width as u16,
+                    height: 1,
+                },
+                index: link.index,
+                url: link.url.clone(),
+            });
+        }
+        layout.rows(rows).to_vec()
     } else {
-        app.document
-            .parsed_lines
-            .iter()
-            .skip(app.scroll_offset)
-            .take(visible_count)
-            .flat_map(|line| parsed_line_to_ratatui_lines(line, theme, area.width as usize))
-            .collect()
+        Vec::new()
     };
 
-    let title = format!(" {} ", app.document.path.display());
-    let paragraph = Paragraph::new(visible_lines)
-        .block(Block::default().borders(Borders::ALL).title(title))
-        .wrap(Wrap { trim: false });
+    let paragraph = Paragraph::new(visible_lines).block(block);
 
     frame.render_widget(paragraph, area);
+
+    // 文書が画面に収まらなければ右の枠にスクロールバーを表示
+    let max_scroll = app.max_scroll();
+    if max_scroll > 0 && inner.height > 0 {
+        let scrollbar_area = Rect {
+            x: area.right().saturating_sub(1),
+            y: inner.y,
+            width: 1,
+            height: inner.height,
+        };
+        let mut state = ScrollbarState::new(max_scroll)
+            .position(app.scroll_offset)
+            .viewport_content_length(visible_count);
+        frame.render_stateful_widget(
+            Scrollbar::new(ScrollbarOrientation::VerticalRight)
+                .begin_symbol(None)
+                .end_symbol(None)
+                .track_symbol(Some("│"))
+                .thumb_style(Style::default().fg(theme.border.primary()))
+                .track_style(Style::default().fg(theme.border.secondary())),
+            scrollbar_area,
+            &mut state,
+        );
+        app.screen_areas.scrollbar = Some(scrollbar_area);
+    }
+
+    // 検索のマッチを強調表示
+    if let Some(search) = &app.search {
+        let rows = app.scroll_offset..app.scroll_offset + visible_count;
+        for (index, found) in search.matches.iter().enumerate() {
+            let Some(row) = found.row.filter(|row| rows.contains(row)) else {
+                continue;
+            };
+            let background = if search.current == Some(index) {
+                theme.search.current()
+            } else {
+                theme.search.background()
+            };
+            let area = Rect {
+                x: inner.x + found.column as u16,
+                y: inner.y + (row - app.scroll_offset) as u16,
+                width: found.width as u16,
+                height: 1,
+            };
+            frame.buffer_mut().set_style(
+                area.intersection(inner),
+                Style::default()
+                    .fg(theme.search.foreground())
+                    .bg(background),
+            );
+        }
+    }
+
+    // リンクヒントモードで選択中のリンクを反転表示
+    if let Some(selected) = app.selected_link {
+        for link in app.link_regions.iter().filter(|l| l.index == selected) {
+            frame.buffer_mut().set_style(
+                link.area.intersection(inner),
+                Style::default().add_modifier(Modifier::REVERSED),
+            );
+        }
+    }
 }
 
 fn render_toc<'a>(frame: &mut Frame, area: Rect, app: &mut App<'a>, theme: &UiTheme) {
-    // Handle empty TOC
-    let items: Vec<ListItem> = if app.document.toc.is_empty() {
+    let rows = app.toc_rows();
+    let empty_message = if app.document.toc.is_empty() {
+        Some("  (No headings)")
+    } else if rows.is_empty() {
+        Some("  (No matches)")
+    } else {
+        None
+    };
+    let items: Vec<ListItem> = match empty_message {
+        Some(message) => vec![ListItem::new(Span::styled(
+            message,
+            Style::default().fg(theme.text.muted()),
+        ))],
+        None => rows
+            .iter()
+            .map(|row| ListItem::new(toc_row_line(row, &app.document.toc[row.index], theme)))
+            .collect(),
+    };
+
+    // 絞り込み中は見出しの代わりに絞り込みの文字列を表示する
+    let title = match &app.toc_view.filter {
+        Some(filter) => format!(" /{filter} "),
+        None => " Table of Contents ".to_string(),
+    };
+    let block = Block::default().borders(Borders::ALL).title(title);
+    let inner = block.inner(area);
+    app.screen_areas.toc = Some(inner);
+
+    // ホイールで動かした位置を保ち、選択中の見出しが範囲外ならハイライトしない
+    let height = inner.height as usize;
+    app.toc_offset = app.toc_offset.min(rows.len().saturating_sub(height));
+    let visible = app.toc_offset..app.toc_offset + height;
+
+    let list = List::new(items).block(block).highlight_style(
+        Style::default()
+            .fg(theme.toc.selected())
+            .bg(theme.toc.highlight_bg())
+            .add_modifier(Modifier::BOLD),
+    );
+
+    let mut list_state = ListState::default().with_offset(app.toc_offset);
+    if let Some(selected) = app
+        .toc_selected_row(&rows)
+        .filter(|row| empty_message.is_none() && visible.contains(row))
+    {
+        list_state.select(Some(selected));
+    }
+
+    frame.render_stateful_widget(list, area, &mut list_state);
+
+    if app.toc_view.filtering {
+        let filter_width = app.toc_view.filter.as_deref().map_or(0, visible_text_len);
+        // 上の枠線の `┌ /` の後ろ
+        let cursor_x = area.x + 3 + filter_width as u16;
+        if cursor_x < area.right().saturating_sub(1) {
+            frame.set_cursor_position((cursor_x, area.y));
+        }
+    }
+}
+
+/// Width of the file tree: the longest entry, within the same bounds as the TOC
+fn calculate_file_tree_width(tree: &FileTree, theme: &UiTheme, terminal_width: u16) -> u16 {
+    let longest = tree
+        .entries
+        .iter()
+        .map(|entry| {
+            // indentation, fold marker, trailing `/` of directories, padding and border
+            toc::indent_width(entry.depth + 1) + 2 + visible_text_len(&entry.name()) + 1 + 4
+        })
+        .chain([visible_text_len(&file_tree_title(tree)) + 4])
+        .max()
+        .unwrap_or(20);
+
+    let max_percent = theme.layout.toc_width_percent() as usize;
+    let max_width = (terminal_width as usize * max_percent / 100).max(20);
+    longest.clamp(20, max_width) as u16
+}
+
+fn file_tree_title(tree: &FileTree) -> String {
+    let name = tree
+        .root
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| tree.root.display().to_string());
+    format!(" {name}/ ")
+}
+
+fn render_file_tree(frame: &mut Frame, area: Rect, app: &mut App, theme: &UiTheme) {
+    let Some(tree) = &mut app.file_tree else {
+        return;
+    };
+    let rows = tree.rows();
+    let items: Vec<ListItem> = if rows.is_empty() {
         vec![ListItem::new(Span::styled(
-            "  (No headings)",
+            "  (No markdown files)",
             Style::default().fg(theme.text.muted()),
         ))]
     } else {
-        app.document
-            .toc
-            .iter()
-            .map(|entry| {
-                let indent = "  ".repeat(entry.level.saturating_sub(1));
-                let content = format!("{}{}", indent, entry.title);
-                ListItem::new(content)
+        rows.iter()
+            .map(|&index| {
+                let entry = &tree.entries[index];
+                let (marker, name, style) = if entry.is_dir {
+                    let marker = if tree.is_collapsed(entry) {
+                        "▸ "
+                    } else {
+                        "▾ "
+                    };
+                    let style = Style::default().fg(theme.text.secondary());
+                    (marker, format!("{}/", entry.name()), style)
+                } else if entry.path == app.document.path {
+                    // The file shown in the content pane
+                    let style = Style::default()
+                        .fg(theme.toc.selected())
+                        .add_modifier(Modifier::BOLD);
+                    ("  ", entry.name(), style)
+                } else {
+                    ("  ", entry.name(), Style::default())
+                };
+                ListItem::new(Line::from(vec![
+                    Span::raw(format!(
+                        "{}{marker}",
+                        " ".repeat(toc::indent_width(entry.depth + 1))
+                    )),
+                    Span::styled(name, style),
+                ]))
             })
             .collect()
     };
 
-    let list = List::new(items)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title(" Table of Contents "),
-        )
-        .highlight_style(
-            Style::default()
-                .fg(theme.toc.selected())
-                .bg(theme.toc.highlight_bg())
-                .add_modifier(Modifier::BOLD),
-        )
-        .highlight_symbol(">> ");
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(file_tree_title(tree));
+    let inner = block.inner(area);
+    app.screen_areas.file_tree = Some(inner);
+
+    let height = inner.height as usize;
+    tree.offset = tree.offset.min(rows.len().saturating_sub(height));
+    let visible = tree.offset..tree.offset + height;
+
+    let list = List::new(items).block(block).highlight_style(
+        Style::default()
+            .fg(theme.toc.selected())
+            .bg(theme.toc.highlight_bg())
+            .add_modifier(Modifier::BOLD),
+    );
+    let mut list_state = ListState::default().with_offset(tree.offset);
+    if let Some(selected) = rows
+        .iter()
+        .position(|&index| index == tree.selected)
+        .filter(|row| visible.contains(row))
+    {
+        list_state.select(Some(selected));
+    }
+    frame.render_stateful_widget(list, area, &mut list_state);
+}
 
-    let mut list_state = ListState::default();
-    // Only select if TOC is not empty
-    if !app.document.toc.is_empty() {
-        list_state.select(Some(app.toc_selected));
+/// One TOC line: indentation, `▾` / `▸` for sections with children and the title,
+/// with the characters matched by the filter highlighted
+fn toc_row_line(row: &TocRow, entry: &TocEntry, theme: &UiTheme) -> Line<'static> {
+    let marker = match (row.has_children, row.collapsed) {
+        (true, true) => "▸ ",
+        (true, false) => "▾ ",
+        _ => "  ",
+    };
+    let mut spans = vec![Span::raw(format!(
+        "{}{marker}",
+        " ".repeat(toc::indent_width(entry.level))
+    ))];
+
+    let matched_style = Style::default()
+        .fg(theme.search.foreground())
+        .bg(theme.search.background());
+    let mut text = String::new();
+    let mut in_match = false;
+    for (position, c) in entry.title.chars().enumerate() {
+        let matched = row.matched.contains(&position);
+        if matched != in_match && !text.is_empty() {
+            let style = if in_match {
+                matched_style
+            } else {
+                Style::default()
+            };
+            spans.push(Span::styled(std::mem::take(&mut text), style));
+        }
+        in_match = matched;
+        text.push(c);
     }
+    if !text.is_empty() {
+        let style = if in_match {
+            matched_style
+        } else {
+            Style::default()
+        };
+        spans.push(Span::styled(text, style));
+    }
+    Line::from(spans)
+}
 
-    frame.render_stateful_widget(list, area, &mut list_state);
+/// Where a link ended up in the lines rendered for a block
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkRegion {
+    /// Index into the rendered lines
+    pub line: usize,
+    pub column: usize,
+    pub width: usize,
+    /// `Link::index` of the link
+    pub index: usize,
+    pub url: String,
+}
+
+pub fn parsed_line_to_ratatui_lines(
+    line: &ParsedLine,
+    theme: &crate::tui::UiTheme,
+    area_width: usize,
+) -> Vec<Line<'static>> {
+    render_parsed_line(line, theme, area_width, &mut Vec::new())
 }
 
-fn parsed_line_to_ratatui_lines(
+/// Render a block, recording where its links were placed
+pub fn render_parsed_line(
     line: &ParsedLine,
     theme: &crate::tui::UiTheme,
     area_width: usize,
+    links: &mut Vec<LinkRegion>,
 ) -> Vec<Line<'static>> {
     match line {
         ParsedLine::Heading { level, text, .. } => {
@@ -354,18 +1018,20 @@ fn parsed_line_to_ratatui_lines(
                 ),
             };
 
-            vec![
-                Line::from(""),
-                Line::from(vec![Span::styled(format!("{prefix}{text}{suffix}"), style)]),
-                Line::from(""),
-            ]
+            let mut spans = vec![Span::styled(prefix, style)];
+            push_inline(&mut spans, text, style, theme, 1, links);
+            if !suffix.is_empty() {
+                spans.push(Span::styled(suffix, style));
+            }
+
+            vec![Line::from(""), Line::from(spans), Line::from("")]
         }
         ParsedLine::Code {
             lang,
             content,
             highlighted,
         } => {
-            let lang_display = lang.as_deref().unwrap_or("text");
+            let (lang_text, file_name) = code_labels(lang.as_deref());
             let border_style = Style::default().fg(theme.code.border());
             let lang_style = Style::default()
                 .fg(theme.code.lang_label())
@@ -374,22 +1040,34 @@ fn parsed_line_to_ratatui_lines(
             // Responsive width for code blocks based on theme setting
             let available_width = area_width.saturating_sub(4); // Account for borders
             let percent = theme.layout.code_block_width_percent() as usize;
-            let block_width: usize = (available_width * percent / 100).clamp(40, 120);
+            // 入れ子の中では前置きを除いた幅に収める（狭くても枠だけは描ける幅にする）
+            let block_width: usize = (available_width * percent / 100)
+                .clamp(40, 120)
+                .min(available_width)
+                .max(4);
 
-            // Header
-            let lang_text = format!("[ {lang_display} ]");
+            // Header: ┌─[ lang ] name ───┐ (the name is dropped when it does not fit)
             let lang_width = visible_text_len(&lang_text);
-            let header_line_len = block_width.saturating_sub(2 + lang_width + 1); // ┌─ + lang + ┐
-
-            let mut lines = vec![
-                Line::from(""),
-                Line::from(vec![
-                    Span::styled("┌─", border_style),
-                    Span::styled(lang_text, lang_style),
-                    Span::styled("─".repeat(header_line_len), border_style),
-                    Span::styled("┐", border_style),
-                ]),
+            let name_text = file_name
+                .map(|name| format!(" {name} "))
+                .filter(|name| 2 + lang_width + visible_text_len(name) + 1 < block_width)
+                .unwrap_or_default();
+            let header_line_len =
+                block_width.saturating_sub(2 + lang_width + visible_text_len(&name_text) + 1); // ┌─ + lang + name + ┐
+
+            let mut header = vec![
+                Span::styled("┌─", border_style),
+                Span::styled(lang_text, lang_style),
             ];
+            if !name_text.is_empty() {
+                header.push(Span::styled(
+                    name_text,
+                    Style::default().fg(theme.text.muted()),
+                ));
+            }
+            header.push(Span::styled("─".repeat(header_line_len), border_style));
+            header.push(Span::styled("┐", border_style));
+            let mut lines = vec![Line::from(""), Line::from(header)];
 
             // Content
             for highlighted_line in highlighted {
@@ -443,30 +1121,76 @@ fn parsed_line_to_ratatui_lines(
             lines.push(Line::from(""));
             lines
         }
+        ParsedLine::Math {
+            source,
+            highlighted,
+        } => {
+            let mut lines = vec![Line::from("")];
+
+            match math::render_display(source) {
+                Ok(rows) => {
+                    // 数式全体を中央に寄せる
+                    let width = rows
+                        .iter()
+                        .map(|row| visible_text_len(row))
+                        .max()
+                        .unwrap_or(0);
+                    let indent = " ".repeat(area_width.saturating_sub(4 + width) / 2);
+                    let style = Style::default().fg(theme.math.text());
+                    for row in rows {
+                        lines.push(Line::from(Span::styled(format!("{indent}{row}"), style)));
+                    }
+                }
+                Err(_) => {
+                    // 組版できない数式はソースをそのまま表示する
+                    let delimiter_style = Style::default().fg(theme.text.muted());
+                    lines.push(Line::from(Span::styled("$$", delimiter_style)));
+                    for highlighted_line in highlighted {
+                        let mut spans = vec![Span::raw("  ")];
+                        spans.extend(highlighted_line.iter().map(|styled_span| {
+                            Span::styled(
+                                styled_span.text.clone(),
+                                Style::default()
+                                    .fg(syntect_to_ratatui_color(styled_span.style.foreground)),
+                            )
+                        }));
+                        lines.push(Line::from(spans));
+                    }
+                    lines.push(Line::from(Span::styled("$$", delimiter_style)));
+                }
+            }
+
+            lines.push(Line::from(""));
+            lines
+        }
         ParsedLine::Text { content } => {
-            if content.trim().is_empty() {
+            if is_blank(content) {
                 vec![Line::from("")]
             } else {
                 let mut result: Vec<Line> = Vec::new();
 
-                for line in content.split('\n') {
+                for line in split_lines(content) {
                     if theme.layout.wrap_text() && !line.is_empty() {
                         // Word wrapping for long lines
-                        let wrapped_lines = wrap_text(line, area_width.saturating_sub(4));
-                        for wrapped_line in wrapped_lines {
-                            if wrapped_line.contains("⟨INLINE_CODE⟩") {
-                                result.push(parse_inline_code(&wrapped_line, theme));
-                            } else {
-                                result.push(Line::from(Span::raw(wrapped_line)));
-                            }
+                        for wrapped_line in wrap_spans(&line, area_width.saturating_sub(4)) {
+                            let mut spans = Vec::new();
+                            let line_idx = result.len();
+                            push_inline(
+                                &mut spans,
+                                &wrapped_line,
+                                Style::default(),
+                                theme,
+                                line_idx,
+                                links,
+                            );
+                            result.push(Line::from(spans));
                         }
                     } else {
                         // No wrapping
-                        if line.contains("⟨INLINE_CODE⟩") {
-                            result.push(parse_inline_code(line, theme));
-                        } else {
-                            result.push(Line::from(Span::raw(line.to_string())));
-                        }
+                        let mut spans = Vec::new();
+                        let line_idx = result.len();
+                        push_inline(&mut spans, &line, Style::default(), theme, line_idx, links);
+                        result.push(Line::from(spans));
                     }
                 }
 
@@ -479,65 +1203,23 @@ fn parsed_line_to_ratatui_lines(
             indent,
             content,
             checked,
-            ..
-        } => {
-            let indent_str = "  ".repeat(*indent);
-
-            let (bullet, bullet_style) = match checked {
-                Some(true) => (
-                    format!("{indent_str}[✓] "),
-                    Style::default()
-                        .fg(theme.list.checked())
-                        .add_modifier(Modifier::BOLD),
-                ),
-                Some(false) => (
-                    format!("{indent_str}[ ] "),
-                    Style::default()
-                        .fg(theme.list.unchecked())
-                        .add_modifier(Modifier::BOLD),
-                ),
-                None => (
-                    format!("{indent_str}● "),
-                    Style::default()
-                        .fg(theme.list.bullet())
-                        .add_modifier(Modifier::BOLD),
-                ),
-            };
-
-            let mut spans = vec![Span::styled(bullet, bullet_style)];
-
-            // Check for inline code markers
-            if content.contains("⟨INLINE_CODE⟩") {
-                spans.extend(parse_inline_code_to_spans(content, Style::default(), theme));
-            } else {
-                spans.push(Span::raw(content.clone()));
-            }
-
-            vec![Line::from(spans)]
-        }
+            marker,
+        } => render_list_item(
+            *indent, *indent, content, *checked, marker, theme, area_width, links,
+        ),
         ParsedLine::BlockQuote { content } => {
             let border_style = Style::default().fg(theme.blockquote.border());
             let text_style = Style::default()
                 .fg(theme.blockquote.text())
                 .add_modifier(Modifier::ITALIC);
 
-            let lines: Vec<Line> = content
-                .split('\n')
-                .map(|line| {
-                    let mut spans = vec![Span::styled("▐ ", border_style)];
-
-                    // Check for inline code markers
-                    if line.contains("⟨INLINE_CODE⟩") {
-                        spans.extend(parse_inline_code_to_spans(line, text_style, theme));
-                    } else {
-                        spans.push(Span::styled(line.to_string(), text_style));
-                    }
-
-                    Line::from(spans)
-                })
-                .collect();
             let mut result = vec![Line::from("")];
-            result.extend(lines);
+            for line in split_lines(content) {
+                let mut spans = vec![Span::styled("▐ ", border_style)];
+                let line_idx = result.len();
+                push_inline(&mut spans, &line, text_style, theme, line_idx, links);
+                result.push(Line::from(spans));
+            }
             result.push(Line::from(""));
             result
         }
@@ -545,40 +1227,7 @@ fn parsed_line_to_ratatui_lines(
             alert_type,
             content,
         } => {
-            use crate::markdown::parser::AlertType;
-
-            let (icon, label, border_color, text_color) = match alert_type {
-                AlertType::Note => (
-                    "ℹ",
-                    "NOTE",
-                    theme.alert.note.border(),
-                    theme.alert.note.text(),
-                ),
-                AlertType::Tip => (
-                    "💡",
-                    "TIP",
-                    theme.alert.tip.border(),
-                    theme.alert.tip.text(),
-                ),
-                AlertType::Important => (
-                    "❗",
-                    "IMPORTANT",
-                    theme.alert.important.border(),
-                    theme.alert.important.text(),
-                ),
-                AlertType::Warning => (
-                    "⚠",
-                    "WARNING",
-                    theme.alert.warning.border(),
-                    theme.alert.warning.text(),
-                ),
-                AlertType::Caution => (
-                    "🛑",
-                    "CAUTION",
-                    theme.alert.caution.border(),
-                    theme.alert.caution.text(),
-                ),
-            };
+            let (icon, label, border_color, text_color) = alert_decoration(*alert_type, theme);
 
             let border_style = Style::default()
                 .fg(border_color)
@@ -590,11 +1239,12 @@ fn parsed_line_to_ratatui_lines(
             let footer_prefix_len = visible_text_len("┗");
             let side_border_len = visible_text_len("┃ ");
 
-            let mut max_content_len = 0;
-            for line in content.split('\n') {
-                let line_len = visible_text_len(line);
-                max_content_len = max_content_len.max(line_len);
-            }
+            let content_lines = split_lines(content);
+            let max_content_len = content_lines
+                .iter()
+                .map(|line| spans_width(line))
+                .max()
+                .unwrap_or(0);
 
             let total_width = (header_prefix_len + max_content_len + side_border_len)
                 .max(footer_prefix_len + 60)
@@ -610,21 +1260,13 @@ fn parsed_line_to_ratatui_lines(
                 ]),
             ];
 
-            for line in content.split('\n') {
+            for line in &content_lines {
                 let mut spans = vec![Span::styled("┃ ", border_style)];
-
-                if line.contains("⟨INLINE_CODE⟩") {
-                    spans.extend(parse_inline_code_to_spans(line, text_style, theme));
-                } else {
-                    spans.push(Span::styled(line.to_string(), text_style));
-                }
+                let line_idx = result.len();
+                push_inline(&mut spans, line, text_style, theme, line_idx, links);
 
                 // Pad to align with border
-                let current_len = visible_text_len(
-                    &line
-                        .replace("⟨INLINE_CODE⟩", "")
-                        .replace("⟨/INLINE_CODE⟩", ""),
-                );
+                let current_len = spans_width(line);
                 let padding_needed = max_content_len.saturating_sub(current_len);
                 if padding_needed > 0 {
                     spans.push(Span::raw(" ".repeat(padding_needed)));
@@ -646,7 +1288,7 @@ fn parsed_line_to_ratatui_lines(
             headers,
             rows,
             alignments,
-        } => render_table(headers, rows, alignments, theme, area_width),
+        } => render_table(headers, rows, alignments, theme, area_width, links),
         ParsedLine::HorizontalRule => {
             let rule_width = area_width.saturating_sub(4).min(120); // Responsive, max 120
             vec![
@@ -682,20 +1324,468 @@ fn parsed_line_to_ratatui_lines(
             ]),
             Line::from(""),
         ],
+        ParsedLine::FootnoteDefinition {
+            number, content, ..
+        } => {
+            let marker = format!("{number}. ");
+            let marker_width = marker.chars().count();
+            let marker_style = Style::default()
+                .fg(theme.footnote.marker())
+                .add_modifier(Modifier::BOLD);
+
+            let mut result: Vec<Line> = Vec::new();
+            for line in split_lines(content) {
+                for wrapped_line in wrap_spans(&line, area_width.saturating_sub(4 + marker_width)) {
+                    // 2行目以降は番号の幅だけ字下げする
+                    let mut spans = if result.is_empty() {
+                        vec![Span::styled(marker.clone(), marker_style)]
+                    } else {
+                        vec![Span::raw(" ".repeat(marker_width))]
+                    };
+                    let line_idx = result.len();
+                    push_inline(
+                        &mut spans,
+                        &wrapped_line,
+                        Style::default(),
+                        theme,
+                        line_idx,
+                        links,
+                    );
+                    result.push(Line::from(spans));
+                }
+            }
+            result
+        }
+        ParsedLine::Details { summary, open, .. } => {
+            let marker_style = Style::default()
+                .fg(theme.list.bullet())
+                .add_modifier(Modifier::BOLD);
+            let marker = if *open { "▼ " } else { "▶ " };
+
+            let mut spans = vec![Span::styled(marker, marker_style)];
+            push_inline(
+                &mut spans,
+                summary,
+                Style::default().add_modifier(Modifier::BOLD),
+                theme,
+                0,
+                links,
+            );
+            let mut result = vec![Line::from(spans)];
+            if !open {
+                result.push(Line::from(""));
+            }
+            result
+        }
         ParsedLine::Empty => vec![Line::from("")],
     }
 }
 
+/// Marker of a list item: the number or bullet followed by the checkbox
+fn list_marker_spans(
+    depth: usize,
+    marker: &ListMarker,
+    checked: Option<bool>,
+    theme: &UiTheme,
+) -> Vec<Span<'static>> {
+    let bullet_style = Style::default()
+        .fg(theme.list.bullet())
+        .add_modifier(Modifier::BOLD);
+    let checkbox = checked.map(|checked| {
+        if checked {
+            (
+                "[✓] ",
+                Style::default()
+                    .fg(theme.list.checked())
+                    .add_modifier(Modifier::BOLD),
+            )
+        } else {
+            (
+                "[ ] ",
+                Style::default()
+                    .fg(theme.list.unchecked())
+                    .add_modifier(Modifier::BOLD),
+            )
+        }
+    });
+
+    let mut spans = Vec::new();
+    match marker {
+        ListMarker::Ordered { number, delimiter } => {
+            let number = format_list_number(*number, depth, theme.layout.nested_list_numbering());
+            spans.push(Span::styled(format!("{number}{delimiter} "), bullet_style));
+            if let Some((text, style)) = checkbox {
+                spans.push(Span::styled(text, style));
+            }
+        }
+        ListMarker::Bullet(_) => match checkbox {
+            Some((text, style)) => spans.push(Span::styled(text, style)),
+            None => spans.push(Span::styled("● ", bullet_style)),
+        },
+    }
+    spans
+}
+
+/// Render a list item whose marker is preceded by `indent` levels of spacing
+#[allow(clippy::too_many_arguments)]
+fn render_list_item(
+    depth: usize,
+    indent: usize,
+    content: &[InlineSpan],
+    checked: Option<bool>,
+    marker: &ListMarker,
+    theme: &UiTheme,
+    area_width: usize,
+    links: &mut Vec<LinkRegion>,
+) -> Vec<Line<'static>> {
+    let mut prefix = vec![Span::raw("  ".repeat(indent))];
+    prefix.extend(list_marker_spans(depth, marker, checked, theme));
+
+    // 折り返した行はマーカーの後ろの文字位置に揃える
+    let prefix_width: usize = prefix.iter().map(|span| span.width()).sum();
+    let hanging = " ".repeat(prefix_width);
+    let mut result: Vec<Line> = Vec::new();
+
+    for line in split_lines(content) {
+        for wrapped_line in wrap_spans(&line, area_width.saturating_sub(4 + prefix_width)) {
+            let mut spans = if result.is_empty() {
+                prefix.clone()
+            } else {
+                vec![Span::raw(hanging.clone())]
+            };
+            let line_idx = result.len();
+            push_inline(
+                &mut spans,
+                &wrapped_line,
+                Style::default(),
+                theme,
+                line_idx,
+                links,
+            );
+            result.push(Line::from(spans));
+        }
+    }
+    if result.is_empty() {
+        result.push(Line::from(prefix));
+    }
+    result
+}
+
+/// Icon, label, border color and text color of an alert
+pub fn alert_decoration(
+    alert_type: AlertType,
+    theme: &UiTheme,
+) -> (&'static str, &'static str, Color, Color) {
+    match alert_type {
+        AlertType::Note => (
+            "ℹ",
+            "NOTE",
+            theme.alert.note.border(),
+            theme.alert.note.text(),
+        ),
+        AlertType::Tip => (
+            "💡",
+            "TIP",
+            theme.alert.tip.border(),
+            theme.alert.tip.text(),
+        ),
+        AlertType::Important => (
+            "❗",
+            "IMPORTANT",
+            theme.alert.important.border(),
+            theme.alert.important.text(),
+        ),
+        AlertType::Warning => (
+            "⚠",
+            "WARNING",
+            theme.alert.warning.border(),
+            theme.alert.warning.text(),
+        ),
+        AlertType::Caution => (
+            "🛑",
+            "CAUTION",
+            theme.alert.caution.border(),
+            theme.alert.caution.text(),
+        ),
+    }
+}
+
+/// Render a block inside its enclosing containers (quotes, alerts, list items)
+///
+/// Each container contributes a prefix to every rendered line, and an alert
+/// spanning several blocks gets its header and footer on its first and last block.
+pub fn render_nested_line(
+    line: &ParsedLine,
+    nesting: &[Nesting],
+    theme: &UiTheme,
+    area_width: usize,
+    links: &mut Vec<LinkRegion>,
+) -> Vec<Line<'static>> {
+    render_in_containers(
+        nesting,
+        theme,
+        area_width,
+        links,
+        |width, links| match line {
+            // 入れ子のリストは親項目の字下げがすでに付いている
+            ParsedLine::ListItem {
+                indent,
+                content,
+                checked,
+                marker,
+            } if !nesting.is_empty() => {
+                render_list_item(*indent, 0, content, *checked, marker, theme, width, links)
+            }
+            _ => render_parsed_line(line, theme, width, links),
+        },
+    )
+}
+
+/// Render a folded block: a heading followed by `▸ … (N lines)`, or a one-line
+/// header for a code block or table
+pub fn render_folded_line(
+    line: &ParsedLine,
+    nesting: &[Nesting],
+    theme: &UiTheme,
+    area_width: usize,
+    hidden_lines: usize,
+    links: &mut Vec<LinkRegion>,
+) -> Vec<Line<'static>> {
+    let muted = Style::default().fg(theme.text.muted());
+    let border = Style::default().fg(theme.code.border());
+    let count = |unit: &str| {
+        let plural = if hidden_lines == 1 { "" } else { "s" };
+        format!(" … ({hidden_lines} {unit}{plural})")
+    };
+
+    render_in_containers(
+        nesting,
+        theme,
+        area_width,
+        links,
+        |width, links| match line {
+            ParsedLine::Heading { .. } => {
+                let mut lines = render_parsed_line(line, theme, width, links);
+                if let Some(heading) = lines.iter_mut().rev().find(|line| line.width() > 0) {
+                    heading.spans.push(Span::styled(" ▸", border));
+                    heading.spans.push(Span::styled(count("line"), muted));
+                }
+                lines
+            }
+            ParsedLine::Code { lang, .. } => {
+                let (lang_text, file_name) = code_labels(lang.as_deref());
+                let mut spans = vec![
+                    Span::styled("▸ ", border),
+                    Span::styled(
+                        lang_text,
+                        Style::default()
+                            .fg(theme.code.lang_label())
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                ];
+                if let Some(name) = file_name {
+                    spans.push(Span::styled(format!(" {name}"), muted));
+                }
+                spans.push(Span::styled(count("line"), muted));
+                vec![Line::from(spans)]
+            }
+            ParsedLine::Table { headers, .. } => {
+                let header: Vec<String> = headers.iter().map(|cell| plain_text(cell)).collect();
+                vec![Line::from(vec![
+                    Span::styled("▸ ", border),
+                    Span::styled(format!("[ {} ]", header.join(" │ ")), border),
+                    Span::styled(count("row"), muted),
+                ])]
+            }
+            _ => render_parsed_line(line, theme, width, links),
+        },
+    )
+}
+
+/// Wrap the lines drawn by `render` (given the inner width) in the borders and
+/// indentation of the enclosing containers
+fn render_in_containers(
+    nesting: &[Nesting],
+    theme: &UiTheme,
+    area_width: usize,
+    links: &mut Vec<LinkRegion>,
+    render: impl FnOnce(usize, &mut Vec<LinkRegion>) -> Vec<Line<'static>>,
+) -> Vec<Line<'static>> {
+    if nesting.is_empty() {
+        return render(area_width, links);
+    }
+
+    let mut prefix: Vec<Span<'static>> = Vec::new();
+    let mut headers: Vec<Line<'static>> = Vec::new();
+    let mut footers: Vec<Line<'static>> = Vec::new();
+    let mut list_depth: usize = 0;
+    let mut alignment = Alignment::None;
+
+    for entry in nesting {
+        let prefix_width: usize = prefix.iter().map(|span| span.width()).sum();
+        match &entry.kind {
+            ContainerKind::BlockQuote => {
+                prefix.push(Span::styled(
+                    "▐ ",
+                    Style::default().fg(theme.blockquote.border()),
+                ));
+            }
+            ContainerKind::Alert(alert_type) => {
+                let (icon, label, border_color, _) = alert_decoration(*alert_type, theme);
+                let border_style = Style::default()
+                    .fg(border_color)
+                    .add_modifier(Modifier::BOLD);
+                let width = area_width.saturating_sub(4 + prefix_width).min(64);
+                if entry.first {
+                    let title = format!("┏━━ {icon} {label} ");
+                    let fill = width.saturating_sub(visible_text_len(&title));
+                    let mut spans = prefix.clone();
+                    spans.push(Span::styled(title, border_style));
+                    spans.push(Span::styled("━".repeat(fill), border_style));
+                    headers.push(Line::from(spans));
+                }
+                if entry.last {
+                    let mut spans = prefix.clone();
+                    spans.push(Span::styled(
+                        format!("┗{}", "━".repeat(width.saturating_sub(1))),
+                        border_style,
+                    ));
+                    footers.insert(0, Line::from(spans));
+                }
+                prefix.push(Span::styled("┃ ", border_style));
+            }
+            ContainerKind::List { .. } => list_depth += 1,
+            ContainerKind::ListItem { marker, checked } => {
+                // 項目の本文の位置に揃える
+                let depth = list_depth.saturating_sub(1);
+                let width: usize = list_marker_spans(depth, marker, *checked, theme)
+                    .iter()
+                    .map(|span| span.width())
+                    .sum();
+                prefix.push(Span::raw(" ".repeat(width)));
+            }
+            ContainerKind::FootnoteDefinition { number, .. } => {
+                prefix.push(Span::raw(" ".repeat(format!("{number}. ").chars().count())));
+            }
+            // `<details>` の中身は `▶` の後ろに揃える
+            ContainerKind::Details { .. } => prefix.push(Span::raw("  ")),
+            ContainerKind::Aligned(value) => alignment = *value,
+        }
+    }
+
+    let prefix_width: usize = prefix.iter().map(|span| span.width()).sum();
+    let inner_width = area_width.saturating_sub(prefix_width);
+    let mut inner_links = Vec::new();
+    let lines = render(inner_width, &mut inner_links);
+
+    // 中央・右寄せは行ごとに左に空白を足す
+    let available = inner_width.saturating_sub(4);
+    let pads: Vec<usize> = lines
+        .iter()
+        .map(|line| match alignment {
+            Alignment::Center => available.saturating_sub(line.width()) / 2,
+            Alignment::Right => available.saturating_sub(line.width()),
+            Alignment::Left | Alignment::None => 0,
+        })
+        .collect();
+
+    links.extend(inner_links.into_iter().map(|region| LinkRegion {
+        line: region.line + headers.len(),
+        column: region.column + prefix_width + pads[region.line],
+        ..region
+    }));
+
+    let mut result = headers;
+    for (rendered, pad) in lines.into_iter().zip(pads) {
+        if rendered.width() == 0 {
+            // Paragraph wraps whitespace-only lines into an extra row, so keep only the borders
+            result.push(Line::from(trim_trailing_spaces(&prefix)));
+            continue;
+        }
+        let mut spans = prefix.clone();
+        if pad > 0 {
+            spans.push(Span::raw(" ".repeat(pad)));
+        }
+        spans.extend(rendered.spans);
+        result.push(Line::from(spans));
+    }
+    result.extend(footers);
+    result
+}
+
+fn trim_trailing_spaces(spans: &[Span<'static>]) -> Vec<Span<'static>> {
+    let mut spans = spans.to_vec();
+    while let Some(last) = spans.last_mut() {
+        let trimmed = last.content.trim_end().to_string();
+        if trimmed.is_empty() {
+            spans.pop();
+        } else {
+            last.content = trimmed.into();
+            break;
+        }
+    }
+    spans
+}
+
+/// 番号付きリストの番号。`nested` なら入れ子の深さに応じて `1.` → `a.` → `i.` と切り替える
+fn format_list_number(number: u64, indent: usize, nested: bool) -> String {
+    if !nested {
+        return number.to_string();
+    }
+
+    match indent % 3 {
+        1 if number > 0 => {
+            // a, b, ..., z, aa, ab, ...
+            let mut n = number;
+            let mut letters = Vec::new();
+            while n > 0 {
+                n -= 1;
+                letters.push((b'a' + (n % 26) as u8) as char);
+                n /= 26;
+            }
+            letters.iter().rev().collect()
+        }
+        2 if (1..4000).contains(&number) => {
+            const NUMERALS: [(u64, &str); 13] = [
+                (1000, "m"),
+                (900, "cm"),
+                (500, "d"),
+                (400, "cd"),
+                (100, "c"),
+                (90, "xc"),
+                (50, "l"),
+                (40, "xl"),
+                (10, "x"),
+                (9, "ix"),
+                (5, "v"),
+                (4, "iv"),
+                (1, "i"),
+            ];
+            let mut n = number;
+            let mut roman = String::new();
+            for (value, numeral) in NUMERALS {
+                while n >= value {
+                    roman.push_str(numeral);
+                    n -= value;
+                }
+            }
+            roman
+        }
+        _ => number.to_string(),
+    }
+}
+
 fn syntect_to_ratatui_color(color: SyntectColor) -> Color {
     Color::Rgb(color.r, color.g, color.b)
 }
 
 fn render_table(
-    headers: &[String],
-    rows: &[Vec<String>],
+    headers: &[Vec<InlineSpan>],
+    rows: &[Vec<Vec<InlineSpan>>],
     alignments: &[Alignment],
     theme: &UiTheme,
     area_width: usize,
+    links: &mut Vec<LinkRegion>,
 ) -> Vec<Line<'static>> {
     // Handle empty table (malformed Markdown)
     if headers.is_empty() {
@@ -715,11 +1805,11 @@ fn render_table(
         .add_modifier(Modifier::BOLD);
     let cell_style = Style::default().fg(theme.table.cell());
 
-    // 各列の最大幅を計算（マーカーを除外した可視文字数）
-    let mut col_widths: Vec<usize> = headers.iter().map(|h| visible_text_len(h)).collect();
+    // 各列の最大幅を計算（可視文字数）
+    let mut col_widths: Vec<usize> = headers.iter().map(|h| spans_width(h)).collect();
     for row in rows {
         for (i, cell) in row.iter().enumerate() {
-            let visible_len = visible_text_len(cell);
+            let visible_len = spans_width(cell);
             if i < col_widths.len() {
                 col_widths[i] = col_widths[i].max(visible_len);
             } else {
@@ -766,31 +1856,28 @@ fn render_table(
     lines.push(Line::from(Span::styled(top_border, border_style)));
 
     // ヘッダー行
-    let header_cells: Vec<Span> = headers
-        .iter()
-        .enumerate()
-        .flat_map(|(i, header)| {
-            let width = col_widths.get(i).copied().unwrap_or(10);
-            let aligned = align_text(
-                header,
-                width,
-                alignments.get(i).copied().unwrap_or(Alignment::Left),
-            );
-
-            let mut cell_spans = vec![Span::styled("│ ", border_style)];
-
-            // インラインコードマーカーをチェック
-            if aligned.contains("⟨INLINE_CODE⟩") {
-                cell_spans.extend(parse_inline_code_to_spans(&aligned, header_style, theme));
-            } else {
-                cell_spans.push(Span::styled(aligned, header_style));
-            }
-
-            cell_spans.push(Span::raw(" "));
-            cell_spans
-        })
-        .chain(std::iter::once(Span::styled("│", border_style)))
-        .collect();
+    let mut header_cells: Vec<Span> = Vec::new();
+    let line_idx = lines.len();
+    for (i, header) in headers.iter().enumerate() {
+        let width = col_widths.get(i).copied().unwrap_or(10);
+        let aligned = align_spans(
+            header,
+            width,
+            alignments.get(i).copied().unwrap_or(Alignment::Left),
+        );
+
+        header_cells.push(Span::styled("│ ", border_style));
+        push_inline(
+            &mut header_cells,
+            &aligned,
+            header_style,
+            theme,
+            line_idx,
+            links,
+        );
+        header_cells.push(Span::raw(" "));
+    }
+    header_cells.push(Span::styled("│", border_style));
     lines.push(Line::from(header_cells));
 
     // ヘッダー区切り
@@ -806,30 +1893,21 @@ fn render_table(
 
     // データ行
     for (row_idx, row) in rows.iter().enumerate() {
-        let row_cells: Vec<Span> = (0..col_widths.len())
-            .flat_map(|i| {
-                let cell = row.get(i).map(|s| s.as_str()).unwrap_or("");
-                let width = col_widths[i];
-                let aligned = align_text(
-                    cell,
-                    width,
-                    alignments.get(i).copied().unwrap_or(Alignment::Left),
-                );
-
-                let mut cell_spans = vec![Span::styled("│ ", border_style)];
-
-                // インラインコードマーカーをチェック
-                if aligned.contains("⟨INLINE_CODE⟩") {
-                    cell_spans.extend(parse_inline_code_to_spans(&aligned, cell_style, theme));
-                } else {
-                    cell_spans.push(Span::styled(aligned, cell_style));
-                }
+        let mut row_cells: Vec<Span> = Vec::new();
+        let line_idx = lines.len();
+        for (i, width) in col_widths.iter().enumerate() {
+            let cell = row.get(i).map(|c| c.as_slice()).unwrap_or(&[]);
+            let aligned = align_spans(
+                cell,
+                *width,
+                alignments.get(i).copied().unwrap_or(Alignment::Left),
+            );
 
-                cell_spans.push(Span::raw(" "));
-                cell_spans
-            })
-            .chain(std::iter::once(Span::styled("│", border_style)))
-            .collect();
+            row_cells.push(Span::styled("│ ", border_style));
+            push_inline(&mut row_cells, &aligned, cell_style, theme, line_idx, links);
+            row_cells.push(Span::raw(" "));
+        }
+        row_cells.push(Span::styled("│", border_style));
         lines.push(Line::from(row_cells));
 
         // 行間の区切り（最後の行以外）
@@ -861,68 +1939,60 @@ fn render_table(
     lines
 }
 
-// マーカーを除外した可視テキスト長を計算
+/// 表示幅を計算（CJKは2、曖昧幅の文字は1）
+/// The `[ lang ]` label of a code block and the `file=` / `title=` name from its info string
+fn code_labels(info: Option<&str>) -> (String, Option<String>) {
+    let info = info.map(CodeInfo::parse).unwrap_or_default();
+    let label = format!("[ {} ]", info.language.as_deref().unwrap_or("text"));
+    (label, info.file_name().map(str::to_string))
+}
+
 pub fn visible_text_len(text: &str) -> usize {
     use unicode_width::UnicodeWidthStr;
 
-    let open_marker = "⟨INLINE_CODE⟩";
-    let close_marker = "⟨/INLINE_CODE⟩";
-
-    let without_markers = text.replace(open_marker, "").replace(close_marker, "");
-
     // Use width() instead of width_cjk() to treat ambiguous characters (like arrows) as width 1
-    without_markers.width()
+    text.width()
+}
+
+/// スパン列全体の表示幅
+pub fn spans_width(spans: &[InlineSpan]) -> usize {
+    spans.iter().map(|s| visible_text_len(&s.text)).sum()
 }
 
-fn align_text(text: &str, width: usize, alignment: Alignment) -> String {
-    let text_len = visible_text_len(text);
-    let content = if text_len > width {
-        // 長すぎる場合は切り詰める - マーカーを保持しながら可視文字数で切り詰め
-        truncate_with_markers(text, width)
+fn align_spans(spans: &[InlineSpan], width: usize, alignment: Alignment) -> Vec<InlineSpan> {
+    let content = if spans_width(spans) > width {
+        // 長すぎる場合は装飾を保持したまま可視文字数で切り詰める
+        truncate_spans(spans, width)
     } else {
-        text.to_string()
+        spans.to_vec()
     };
 
-    let content_len = visible_text_len(&content);
-    let padding = width.saturating_sub(content_len);
+    let padding = width.saturating_sub(spans_width(&content));
+    let (left_pad, right_pad) = match alignment {
+        Alignment::Left | Alignment::None => (0, padding),
+        Alignment::Right => (padding, 0),
+        Alignment::Center => (padding / 2, padding - padding / 2),
+    };
 
-    match alignment {
-        Alignment::Left | Alignment::None => {
-            format!("{content}{}", " ".repeat(padding))
-        }
-        Alignment::Right => {
-            format!("{}{content}", " ".repeat(padding))
-        }
-        Alignment::Center => {
-            let left_pad = padding / 2;
-            let right_pad = padding - left_pad;
-            format!("{}{content}{}", " ".repeat(left_pad), " ".repeat(right_pad))
-        }
+    let mut result = Vec::with_capacity(content.len() + 2);
+    push_span(&mut result, InlineSpan::plain(" ".repeat(left_pad)));
+    for span in content {
+        push_span(&mut result, span);
     }
+    push_span(&mut result, InlineSpan::plain(" ".repeat(right_pad)));
+    result
 }
 
-// マーカーを保持しながらテキストを切り詰める（O(n)アルゴリズム）
-pub fn truncate_with_markers(text: &str, max_visible: usize) -> String {
-    let open_marker = "⟨INLINE_CODE⟩";
-    let close_marker = "⟨/INLINE_CODE⟩";
-
-    let mut result = String::with_capacity(text.len().min(max_visible * 2));
+/// 装飾を保持しながら表示幅 `max_visible` までスパン列を切り詰める
+pub fn truncate_spans(spans: &[InlineSpan], max_visible: usize) -> Vec<InlineSpan> {
+    let mut result = Vec::new();
     let mut current_visible_width = 0;
-    let mut i = 0;
-    let mut in_code = false;
-
-    while i < text.len() {
-        let remaining = &text[i..];
-
-        if remaining.starts_with(open_marker) {
-            result.push_str(open_marker);
-            i += open_marker.len();
-            in_code = true;
-        } else if remaining.starts_with(close_marker) {
-            result.push_str(close_marker);
-            i += close_marker.len();
-            in_code = false;
-        } else if let Some(ch) = text[i..].chars().next() {
+
+    for span in spans {
+        let mut text = String::new();
+        let mut truncated = false;
+
+        for ch in span.text.chars() {
             let char_width = match ch.width() {
                 Some(w) => w,
                 None => {
@@ -935,203 +2005,246 @@ pub fn truncate_with_markers(text: &str, max_visible: usize) -> String {
             };
 
             if current_visible_width + char_width > max_visible {
+                truncated = true;
                 break;
             }
 
-            result.push(ch);
+            text.push(ch);
             current_visible_width += char_width;
-            i += ch.len_utf8();
-        } else {
-            break;
         }
-    }
 
-    if in_code {
-        result.push_str(close_marker);
+        push_span(
+            &mut result,
+            InlineSpan {
+                text,
+                ..span.clone()
+            },
+        );
+
+        if truncated {
+            break;
+        }
     }
 
     result
 }
 
-// テーブルセル用のインラインコードパーサー（ベーススタイルを保持）
-fn parse_inline_code_to_spans(
-    text: &str,
+/// Convert inline spans to ratatui spans, layering inline styles over `base_style`
+pub fn inline_spans_to_ratatui(
+    spans: &[InlineSpan],
     base_style: Style,
-    theme: &crate::tui::UiTheme,
+    theme: &UiTheme,
 ) -> Vec<Span<'static>> {
-    let mut spans = Vec::new();
-    let mut current = String::new();
-
-    let open_marker = "⟨INLINE_CODE⟩";
-    let close_marker = "⟨/INLINE_CODE⟩";
+    let mut result: Vec<Span<'static>> = spans
+        .iter()
+        .map(|span| Span::styled(span.text.clone(), inline_style(span, base_style, theme)))
+        .collect();
 
-    let mut i = 0;
-    let chars: Vec<char> = text.chars().collect();
+    if result.is_empty() {
+        result.push(Span::styled(String::new(), base_style));
+    }
 
-    while i < chars.len() {
-        let remaining: String = chars[i..].iter().collect();
-        if remaining.starts_with(open_marker) {
-            // Push accumulated normal text with base style
-            if !current.is_empty() {
-                spans.push(Span::styled(current.clone(), base_style));
-                current.clear();
-            }
+    result
+}
 
-            i += open_marker.chars().count();
+/// Append inline spans to a line being built, recording link positions
+fn push_inline(
+    spans: &mut Vec<Span<'static>>,
+    inline: &[InlineSpan],
+    base_style: Style,
+    theme: &UiTheme,
+    line: usize,
+    links: &mut Vec<LinkRegion>,
+) {
+    let mut column: usize = spans.iter().map(|span| span.width()).sum();
 
-            let mut code_content = String::new();
-            let mut found_close = false;
-            while i < chars.len() {
-                let remaining_inner: String = chars[i..].iter().collect();
-                if remaining_inner.starts_with(close_marker) {
-                    found_close = true;
-                    i += close_marker.chars().count();
-                    break;
+    for (inline_span, span) in inline
+        .iter()
+        .zip(inline_spans_to_ratatui(inline, base_style, theme))
+    {
+        let width = span.width();
+        if let Some(link) = &inline_span.link {
+            match links.last_mut() {
+                // 装飾の違いで分かれた同じリンクはひとつの領域にまとめる
+                Some(region)
+                    if region.index == link.index
+                        && region.line == line
+                        && region.column + region.width == column =>
+                {
+                    region.width += width;
                 }
-                code_content.push(chars[i]);
-                i += 1;
-            }
-
-            // Add styled code span
-            if found_close {
-                spans.push(Span::styled(
-                    code_content,
-                    Style::default()
-                        .fg(theme.inline_code.foreground())
-                        .bg(theme.inline_code.background())
-                        .add_modifier(Modifier::BOLD),
-                ));
-            } else {
-                current.push_str(open_marker);
-                current.push_str(&code_content);
+                _ => links.push(LinkRegion {
+                    line,
+                    column,
+                    width,
+                    index: link.index,
+                    url: link.url.clone(),
+                }),
             }
-        } else {
-            current.push(chars[i]);
-            i += 1;
         }
+        column += width;
+        spans.push(span);
     }
-
-    if !current.is_empty() {
-        spans.push(Span::styled(current, base_style));
-    }
-
-    if spans.is_empty() {
-        spans.push(Span::styled("".to_string(), base_style));
-    }
-
-    spans
 }
 
-fn parse_inline_code(text: &str, theme: &UiTheme) -> Line<'static> {
-    let mut spans = Vec::new();
-    let mut current = String::new();
-
-    let open_marker = "⟨INLINE_CODE⟩";
-    let close_marker = "⟨/INLINE_CODE⟩";
-
-    let mut i = 0;
-    let chars: Vec<char> = text.chars().collect();
-
-    while i < chars.len() {
-        // Check for opening marker
-        let remaining: String = chars[i..].iter().collect();
-        if remaining.starts_with(open_marker) {
-            // Push accumulated normal text
-            if !current.is_empty() {
-                spans.push(Span::raw(current.clone()));
-                current.clear();
-            }
-
-            // Skip the opening marker
-            i += open_marker.chars().count();
-
-            // Collect code content until closing marker
-            let mut code_content = String::new();
-            let mut found_close = false;
-            while i < chars.len() {
-                let remaining_inner: String = chars[i..].iter().collect();
-                if remaining_inner.starts_with(close_marker) {
-                    found_close = true;
-                    i += close_marker.chars().count();
-                    break;
-                }
-                code_content.push(chars[i]);
-                i += 1;
-            }
+fn inline_style(span: &InlineSpan, base_style: Style, theme: &UiTheme) -> Style {
+    let mut style = if span.style.code {
+        Style::default()
+            .fg(theme.inline_code.foreground())
+            .bg(theme.inline_code.background())
+            .add_modifier(Modifier::BOLD)
+    } else {
+        base_style
+    };
 
-            // Add styled code span
-            if found_close {
-                spans.push(Span::styled(
-                    code_content,
-                    Style::default()
-                        .fg(theme.inline_code.foreground())
-                        .bg(theme.inline_code.background())
-                        .add_modifier(Modifier::BOLD),
-                ));
-            } else {
-                // If no closing marker found, treat as normal text
-                current.push_str(open_marker);
-                current.push_str(&code_content);
-            }
-        } else {
-            current.push(chars[i]);
-            i += 1;
-        }
+    if span.style.emphasis {
+        style = style.add_modifier(Modifier::ITALIC);
     }
-
-    // Add remaining text
-    if !current.is_empty() {
-        spans.push(Span::raw(current));
+    if span.style.strong {
+        style = style.add_modifier(Modifier::BOLD);
     }
-
-    // If no spans were created, return empty line
-    if spans.is_empty() {
-        spans.push(Span::raw(""));
+    if span.style.strikethrough {
+        style = style.add_modifier(Modifier::CROSSED_OUT);
+    }
+    if span.style.math {
+        style = style.fg(theme.math.text());
+    }
+    if span.style.kbd {
+        style = style
+            .fg(theme.kbd.foreground())
+            .bg(theme.kbd.background())
+            .add_modifier(Modifier::BOLD);
+    }
+    if span.style.html {
+        style = style.fg(theme.text.muted());
+    }
+    if span.footnote.is_some() {
+        style = style
+            .fg(theme.footnote.marker())
+            .add_modifier(Modifier::BOLD);
+    }
+    if span.link.is_some() {
+        if !span.style.code {
+            style = style.fg(theme.link.text());
+        }
+        style = style.add_modifier(Modifier::UNDERLINED);
     }
 
-    Line::from(spans)
+    style
 }
 
 #[cfg(test)]
 mod test_inline_code_spans {
     use super::*;
+    use crate::markdown::InlineStyle;
     use ratatui::style::{Color, Modifier, Style};
 
     #[test]
-    fn test_parse_inline_code_to_spans_basic() {
-        let text = "⟨INLINE_CODE⟩test⟨/INLINE_CODE⟩";
+    fn test_inline_spans_to_ratatui_code() {
+        let spans = vec![InlineSpan::code("test")];
         let base_style = Style::default().fg(Color::White);
         let theme = crate::tui::UiTheme::dark();
-        let spans = parse_inline_code_to_spans(text, base_style, &theme);
+        let result = inline_spans_to_ratatui(&spans, base_style, &theme);
 
-        assert_eq!(spans.len(), 1);
-        assert_eq!(spans[0].content, "test");
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].content, "test");
         let expected_style = Style::default()
             .fg(theme.inline_code.foreground())
             .bg(theme.inline_code.background())
             .add_modifier(Modifier::BOLD);
-        assert_eq!(spans[0].style, expected_style);
+        assert_eq!(result[0].style, expected_style);
     }
 
     #[test]
-    fn test_parse_inline_code_to_spans_with_normal_text() {
-        let text = "normal ⟨INLINE_CODE⟩code⟨/INLINE_CODE⟩ more";
+    fn test_inline_spans_to_ratatui_with_normal_text() {
+        let spans = vec![
+            InlineSpan::plain("normal "),
+            InlineSpan::code("code"),
+            InlineSpan::plain(" more"),
+        ];
         let base_style = Style::default().fg(Color::White);
         let theme = crate::tui::UiTheme::dark();
-        let spans = parse_inline_code_to_spans(text, base_style, &theme);
+        let result = inline_spans_to_ratatui(&spans, base_style, &theme);
 
-        assert_eq!(spans.len(), 3);
-        assert_eq!(spans[0].content, "normal ");
-        assert_eq!(spans[0].style, base_style);
+        assert_eq!(result.len(), 3);
+        assert_eq!(result[0].content, "normal ");
+        assert_eq!(result[0].style, base_style);
 
-        assert_eq!(spans[1].content, "code");
+        assert_eq!(result[1].content, "code");
         let expected_code_style = Style::default()
             .fg(theme.inline_code.foreground())
             .bg(theme.inline_code.background())
             .add_modifier(Modifier::BOLD);
-        assert_eq!(spans[1].style, expected_code_style);
+        assert_eq!(result[1].style, expected_code_style);
+
+        assert_eq!(result[2].content, " more");
+        assert_eq!(result[2].style, base_style);
+    }
 
-        assert_eq!(spans[2].content, " more");
-        assert_eq!(spans[2].style, base_style);
+    #[test]
+    fn test_inline_spans_to_ratatui_modifiers() {
+        let spans = vec![InlineSpan {
+            text: "both".to_string(),
+            style: InlineStyle {
+                emphasis: true,
+                strong: true,
+                strikethrough: true,
+                ..InlineStyle::default()
+            },
+            link: None,
+            footnote: None,
+        }];
+        let theme = crate::tui::UiTheme::dark();
+        let result = inline_spans_to_ratatui(&spans, Style::default(), &theme);
+
+        let modifiers = result[0].style.add_modifier;
+        assert!(modifiers.contains(Modifier::ITALIC));
+        assert!(modifiers.contains(Modifier::BOLD));
+        assert!(modifiers.contains(Modifier::CROSSED_OUT));
+    }
+
+    #[test]
+    fn test_wrap_spans_keeps_styles_across_lines() {
+        let spans = vec![
+            InlineSpan::plain("aaa "),
+            InlineSpan::code("bbb ccc"),
+            InlineSpan::plain(" ddd"),
+        ];
+        let lines = wrap_spans(&spans, 8);
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(crate::markdown::plain_text(&lines[0]), "aaa bbb");
+        assert_eq!(crate::markdown::plain_text(&lines[1]), "ccc ddd");
+        assert!(lines[1][0].style.code);
+        assert!(!lines[1][1].style.code);
+    }
+
+    #[test]
+    fn test_format_list_number() {
+        assert_eq!(format_list_number(3, 1, false), "3");
+        assert_eq!(format_list_number(3, 0, true), "3");
+        assert_eq!(format_list_number(3, 1, true), "c");
+        assert_eq!(format_list_number(28, 1, true), "ab");
+        assert_eq!(format_list_number(4, 2, true), "iv");
+        assert_eq!(format_list_number(14, 5, true), "xiv");
+        assert_eq!(format_list_number(2, 3, true), "2");
+    }
+
+    #[test]
+    fn test_list_item_hanging_indent() {
+        let theme = crate::tui::UiTheme::dark();
+        let item = ParsedLine::ListItem {
+            indent: 0,
+            content: vec![InlineSpan::plain("alpha beta gamma delta")],
+            checked: None,
+            marker: ListMarker::Ordered {
+                number: 10,
+                delimiter: '.',
+            },
+        };
+        let lines = parsed_line_to_ratatui_lines(&item, &theme, 20);
+        let text: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+
+        assert_eq!(text, ["10. alpha beta", "    gamma delta"]);
     }
 }
@@ -1,7 +1,7 @@
 use crate::error::MdError;
 use notify::{RecommendedWatcher, RecursiveMode};
 use notify_debouncer_full::{new_debouncer, Debouncer, RecommendedCache};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 use tokio::sync::mpsc;
 use tokio_util::sync::CancellationToken;
@@ -13,14 +13,15 @@ pub enum ReloadEvent {
 }
 
 pub struct LiveReloader {
-    _debouncer: Debouncer<RecommendedWatcher, RecommendedCache>,
+    debouncer: Debouncer<RecommendedWatcher, RecommendedCache>,
     rx: mpsc::Receiver<ReloadEvent>,
     cancel_token: CancellationToken,
     _task_handles: Vec<tokio::task::JoinHandle<()>>,
 }
 
 impl LiveReloader {
-    pub fn new(path: PathBuf) -> Result<Self, MdError> {
+    /// `paths` のファイルをすべて監視する
+    pub fn new(paths: &[PathBuf]) -> Result<Self, MdError> {
         let (tx, rx) = mpsc::channel(100);
         let cancel_token = CancellationToken::new();
 
@@ -73,16 +74,30 @@ impl LiveReloader {
             },
         )?;
 
-        debouncer.watch(&path, RecursiveMode::NonRecursive)?;
+        for path in paths {
+            debouncer.watch(path, RecursiveMode::NonRecursive)?;
+        }
 
         Ok(Self {
-            _debouncer: debouncer,
+            debouncer,
             rx,
             cancel_token,
             _task_handles: Vec::new(),
         })
     }
 
+    /// 監視するファイルを加える
+    pub fn watch(&mut self, path: &Path) -> Result<(), MdError> {
+        self.debouncer.watch(path, RecursiveMode::NonRecursive)?;
+        Ok(())
+    }
+
+    /// ディレクトリ以下を監視する（ファイルの追加・削除も通知する）
+    pub fn watch_directory(&mut self, path: &Path) -> Result<(), MdError> {
+        self.debouncer.watch(path, RecursiveMode::Recursive)?;
+        Ok(())
+    }
+
     pub async fn next_event(&mut self) -> Option<ReloadEvent> {
         self.rx.recv().await
     }
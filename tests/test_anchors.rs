@@ -0,0 +1,75 @@
+use crossterm::event::{KeyCode, KeyModifiers};
+use mdv::markdown::{CodeHighlighter, MarkdownDocument};
+use mdv::tui::{App, AppAction, ThemeManager};
+use std::path::PathBuf;
+
+const DOC: &str = r#"# mdv Viewer
+
+## Usage
+
+## Usage
+
+## Install `cargo` & Run!
+
+## Custom {#my-id}
+
+Body.
+"#;
+
+fn parse(md: &str) -> MarkdownDocument {
+    let highlighter = CodeHighlighter::new("base16-ocean.dark".to_string());
+    MarkdownDocument::parse(
+        PathBuf::from("docs/README.md"),
+        md.to_string(),
+        &highlighter,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_heading_anchors_follow_github_rules() {
+    let doc = parse(DOC);
+
+    let anchors: Vec<_> = doc.toc.iter().map(|e| e.anchor.as_str()).collect();
+    assert_eq!(
+        anchors,
+        [
+            "mdv-viewer",
+            "usage",
+            "usage-1",
+            "install-cargo--run",
+            "my-id"
+        ]
+    );
+    assert_eq!(doc.toc[4].title, "Custom");
+}
+
+#[test]
+fn test_jump_to_anchor() {
+    let theme_manager = ThemeManager::new();
+    let mut app = App::new(parse(DOC), false, None, &theme_manager);
+
+    assert!(app.jump_to_anchor("usage-1"));
+    assert_eq!(app.current_heading(), Some(2));
+
+    assert!(app.jump_to_anchor("MY-ID"));
+    assert_eq!(app.current_heading(), Some(4));
+
+    assert!(!app.jump_to_anchor("missing"));
+}
+
+#[test]
+fn test_copy_heading_anchor() {
+    let theme_manager = ThemeManager::new();
+    let mut app = App::new(parse(DOC), false, None, &theme_manager);
+
+    app.jump_to_anchor("install-cargo--run");
+    app.handle_key(KeyCode::Char('y'), KeyModifiers::NONE);
+
+    assert_eq!(
+        app.pending_action.take(),
+        Some(AppAction::CopyToClipboard(
+            "README.md#install-cargo--run".to_string()
+        ))
+    );
+}
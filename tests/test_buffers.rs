@@ -0,0 +1,150 @@
+use crossterm::event::{KeyCode, KeyModifiers};
+use mdv::markdown::{CodeHighlighter, MarkdownDocument};
+use mdv::tui::{App, ThemeManager};
+use std::path::PathBuf;
+
+fn parse(name: &str, md: &str) -> MarkdownDocument {
+    let highlighter = CodeHighlighter::new("base16-ocean.dark".to_string());
+    MarkdownDocument::parse(PathBuf::from(name), md.to_string(), &highlighter).unwrap()
+}
+
+fn long_doc(title: &str) -> String {
+    let mut md = format!("# {title}\n\n");
+    for i in 0..40 {
+        md.push_str(&format!("Paragraph {i}.\n\n"));
+    }
+    md.push_str("## End\n\nDone.\n");
+    md
+}
+
+fn new_app(theme_manager: &ThemeManager) -> App<'_> {
+    let mut app = App::new(
+        parse("a.md", &long_doc("Alpha")),
+        false,
+        None,
+        theme_manager,
+    );
+    app.add_buffer(parse("b.md", &long_doc("Beta")));
+    app.add_buffer(parse("notes/c.md", &long_doc("Gamma")));
+    app.viewport_height = 10;
+    app.ensure_layout(80);
+    app
+}
+
+fn keys(app: &mut App, keys: &str) {
+    for c in keys.chars() {
+        let modifiers = if c.is_ascii_uppercase() {
+            KeyModifiers::SHIFT
+        } else {
+            KeyModifiers::NONE
+        };
+        app.handle_key(KeyCode::Char(c), modifiers);
+        app.ensure_layout(80);
+    }
+}
+
+fn command(app: &mut App, command: &str) {
+    keys(app, ":");
+    keys(app, command);
+    app.handle_key(KeyCode::Enter, KeyModifiers::NONE);
+    app.ensure_layout(80);
+}
+
+#[test]
+fn test_switching_keeps_state_per_buffer() {
+    let theme_manager = ThemeManager::default();
+    let mut app = new_app(&theme_manager);
+    assert_eq!(app.buffer_count(), 3);
+
+    keys(&mut app, "jjjj");
+    assert_eq!(app.scroll_offset, 4);
+
+    keys(&mut app, "gt");
+    assert_eq!(app.current_buffer(), 1);
+    assert_eq!(app.document.path, PathBuf::from("b.md"));
+    assert_eq!(app.scroll_offset, 0);
+    keys(&mut app, "G");
+    let bottom = app.scroll_offset;
+    assert!(bottom > 4);
+
+    // 末尾から先頭へ巡回する
+    keys(&mut app, "gtgt");
+    assert_eq!(app.current_buffer(), 0);
+    assert_eq!(app.scroll_offset, 4);
+
+    keys(&mut app, "gT");
+    assert_eq!(app.current_buffer(), 2);
+    keys(&mut app, "gT");
+    assert_eq!(app.scroll_offset, bottom);
+
+    keys(&mut app, "gg");
+    assert_eq!(app.current_buffer(), 1);
+    assert_eq!(app.scroll_offset, 0);
+}
+
+#[test]
+fn test_buffer_commands() {
+    let theme_manager = ThemeManager::default();
+    let mut app = new_app(&theme_manager);
+
+    command(&mut app, "b 3");
+    assert_eq!(app.current_buffer(), 2);
+    command(&mut app, "b1");
+    assert_eq!(app.current_buffer(), 0);
+    command(&mut app, "b gamma");
+    assert_eq!(app.current_buffer(), 0);
+    assert_eq!(
+        app.status_message.as_deref(),
+        Some("No matching buffer for gamma")
+    );
+    command(&mut app, "b C.MD");
+    assert_eq!(app.current_buffer(), 2);
+    command(&mut app, "b .md");
+    assert_eq!(app.current_buffer(), 2);
+    assert_eq!(
+        app.status_message.as_deref(),
+        Some("More than one match for .md")
+    );
+    command(&mut app, "b 7");
+    assert_eq!(app.status_message.as_deref(), Some("No such buffer: 7"));
+    command(&mut app, "bn");
+    assert_eq!(app.current_buffer(), 0);
+    command(&mut app, "bp");
+    assert_eq!(app.current_buffer(), 2);
+
+    command(&mut app, "ls");
+    assert_eq!(
+        app.status_message.as_deref(),
+        Some("1:a.md 2:b.md 3:notes/c.md*")
+    );
+    command(&mut app, "frobnicate");
+    assert_eq!(
+        app.status_message.as_deref(),
+        Some("Not a command: frobnicate")
+    );
+
+    // Esc で入力を取り消す
+    keys(&mut app, ":b1");
+    assert_eq!(app.command_line.as_deref(), Some("b1"));
+    app.handle_key(KeyCode::Esc, KeyModifiers::NONE);
+    assert_eq!(app.command_line, None);
+    assert_eq!(app.current_buffer(), 2);
+}
+
+#[test]
+fn test_update_inactive_buffer() {
+    let theme_manager = ThemeManager::default();
+    let mut app = new_app(&theme_manager);
+    keys(&mut app, "jj");
+
+    app.update_buffer(1, parse("b.md", "# Beta 2\n\nChanged.\n"));
+    // 表示中のバッファはそのまま
+    assert_eq!(app.current_buffer(), 0);
+    assert_eq!(app.document.toc[0].title, "Alpha");
+    assert_eq!(app.scroll_offset, 2);
+
+    keys(&mut app, "gt");
+    assert_eq!(app.document.toc[0].title, "Beta 2");
+    assert_eq!(app.find_buffer(&PathBuf::from("notes/c.md")), Some(2));
+    assert_eq!(app.find_buffer(&PathBuf::from("d.md")), None);
+}
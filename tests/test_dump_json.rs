@@ -0,0 +1,159 @@
+use mdv::markdown::{CodeHighlighter, MarkdownDocument};
+use mdv::output::json::{dump_documents, dump_toc, SCHEMA_VERSION};
+use mdv::output::TocFormat;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+const MARKDOWN: &str = "---
+title: Runbook
+tags: [ops]
+---
+# Runbook
+
+> [!WARNING]
+> Read [this](#deploy) first.
+
+### Deploy
+
+```bash file=deploy.sh title=\"Deploy script\"
+make deploy
+```
+
+| Step | Time |
+|:-----|-----:|
+| one  | 5m   |
+
+![diagram](diagram.png)
+";
+
+fn parse(path: &str) -> MarkdownDocument {
+    let highlighter = CodeHighlighter::new("base16-ocean.dark".to_string());
+    MarkdownDocument::parse(PathBuf::from(path), MARKDOWN.to_string(), &highlighter).unwrap()
+}
+
+#[test]
+fn test_dump_json_schema() {
+    let output: Value = serde_json::from_str(&dump_documents(&[parse("runbook.md")])).unwrap();
+    assert_eq!(output["version"], SCHEMA_VERSION);
+    let json = &output["documents"][0];
+
+    assert_eq!(json["path"], "runbook.md");
+    assert_eq!(
+        json["metadata"],
+        json!({ "title": "Runbook", "tags": ["ops"] })
+    );
+    assert_eq!(
+        json["toc"][1],
+        json!({ "level": 3, "title": "Deploy", "anchor": "deploy", "line": 10 })
+    );
+
+    let blocks = json["blocks"].as_array().unwrap();
+    let block = |kind: &str| {
+        blocks
+            .iter()
+            .find(|block| block["type"] == kind)
+            .unwrap_or_else(|| panic!("no {kind} block"))
+    };
+    assert_eq!(
+        block("alert"),
+        &json!({ "line": 7, "type": "alert", "kind": "warning", "text": "Read this first." })
+    );
+    assert_eq!(block("code")["lang"], "bash");
+    assert_eq!(
+        block("code")["attributes"],
+        json!({ "file": "deploy.sh", "title": "Deploy script" })
+    );
+    assert_eq!(block("code")["content"], "make deploy\n");
+    assert_eq!(block("table")["headers"], json!(["Step", "Time"]));
+    assert_eq!(block("table")["rows"], json!([["one", "5m"]]));
+    assert_eq!(block("table")["alignments"], json!(["left", "right"]));
+    assert_eq!(block("image")["url"], "diagram.png");
+
+    let link = &json["links"][0];
+    assert_eq!(link["url"], "#deploy");
+    assert_eq!(
+        blocks[link["block"].as_u64().unwrap() as usize]["type"],
+        "alert"
+    );
+}
+
+#[test]
+fn test_dump_json_alert_shape() {
+    let markdown = "> [!NOTE]
+> One paragraph.
+
+> [!TIP]
+> First [link](#x).
+>
+> Second.
+
+> [!CAUTION]
+>
+> ```sh
+> rm -rf build
+> ```
+";
+    let highlighter = CodeHighlighter::new("base16-ocean.dark".to_string());
+    let document =
+        MarkdownDocument::parse(PathBuf::from("a.md"), markdown.to_string(), &highlighter).unwrap();
+    let output: Value = serde_json::from_str(&dump_documents(&[document])).unwrap();
+    let json = &output["documents"][0];
+
+    // 本文の段落の数によらず、アラートごとに `alert` ブロックが一つある
+    assert_eq!(
+        json["blocks"],
+        json!([
+            { "line": 1, "type": "alert", "kind": "note", "text": "One paragraph." },
+            { "line": 4, "type": "alert", "kind": "tip", "text": "First link." },
+            { "line": 7, "containers": ["alert:tip"], "type": "paragraph", "text": "Second." },
+            { "line": 11, "type": "alert", "kind": "caution", "text": "" },
+            {
+                "line": 11,
+                "containers": ["alert:caution"],
+                "type": "code",
+                "lang": "sh",
+                "content": "rm -rf build\n"
+            },
+        ])
+    );
+    assert_eq!(json["links"][0]["block"], 1);
+}
+
+/// 出力の最上位のキーと `documents`
+fn envelope(json: &str) -> (Vec<String>, Vec<Value>) {
+    let output: Value = serde_json::from_str(json).unwrap();
+    let keys = output.as_object().unwrap().keys().cloned().collect();
+    (keys, output["documents"].as_array().unwrap().clone())
+}
+
+#[test]
+fn test_dump_json_envelope() {
+    let (keys, documents) = envelope(&dump_documents(&[parse("a.md")]));
+    assert_eq!(keys, ["version", "documents"]);
+    assert_eq!(documents.len(), 1);
+    assert_eq!(documents[0]["path"], "a.md");
+
+    let (keys, documents) = envelope(&dump_documents(&[parse("a.md"), parse("b.md")]));
+    assert_eq!(keys, ["version", "documents"]);
+    let paths: Vec<&Value> = documents.iter().map(|document| &document["path"]).collect();
+    assert_eq!(paths, ["a.md", "b.md"]);
+}
+
+#[test]
+fn test_dump_toc() {
+    let documents = [parse("runbook.md")];
+    let (keys, tocs) = envelope(&dump_toc(&documents, TocFormat::Json));
+    assert_eq!(keys, ["version", "documents"]);
+    assert_eq!(tocs.len(), 1);
+    assert_eq!(tocs[0]["path"], "runbook.md");
+    assert_eq!(tocs[0]["toc"][0]["anchor"], "runbook");
+
+    let (_, tocs) = envelope(&dump_toc(&[parse("a.md"), parse("b.md")], TocFormat::Json));
+    assert_eq!(tocs[1]["path"], "b.md");
+    assert_eq!(tocs[1]["toc"][1]["title"], "Deploy");
+
+    assert_eq!(
+        dump_toc(&documents, TocFormat::Markdown),
+        "- [Runbook](#runbook)\n  - [Deploy](#deploy)\n"
+    );
+}
@@ -0,0 +1,63 @@
+use crossterm::event::{KeyCode, KeyModifiers};
+use mdv::markdown::{CodeHighlighter, MarkdownDocument};
+use mdv::tui::{App, ThemeManager};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+fn parse(md: &str) -> MarkdownDocument {
+    let highlighter = CodeHighlighter::new("base16-ocean.dark".to_string());
+    MarkdownDocument::parse(PathBuf::from("events.md"), md.to_string(), &highlighter).unwrap()
+}
+
+#[test]
+fn test_resize_invalidates_toc_width_and_layout() {
+    let theme_manager = ThemeManager::new();
+    let mut app = App::new(parse("# Title\n\nBody.\n"), true, None, &theme_manager);
+    app.viewport_height = 10;
+    app.ensure_layout(80);
+    app.toc_width_cache = Some(20);
+
+    app.handle_resize();
+    assert!(app.toc_width_cache.is_none());
+    assert!(app.layout.is_none());
+}
+
+#[test]
+fn test_paste_goes_to_search_prompt_only() {
+    let theme_manager = ThemeManager::new();
+    let mut app = App::new(parse("# Title\n\nfoo bar\n"), false, None, &theme_manager);
+    app.viewport_height = 10;
+    app.ensure_layout(80);
+
+    // 入力欄がなければ無視する
+    app.handle_paste("foo");
+    assert!(app.search.is_none());
+
+    app.handle_key(KeyCode::Char('/'), KeyModifiers::NONE);
+    app.handle_paste("foo\nbar");
+    let search = app.search.as_ref().unwrap();
+    assert_eq!(search.query.pattern, "foo bar");
+    assert_eq!(search.matches.len(), 1);
+}
+
+#[test]
+fn test_status_message_expires() {
+    let theme_manager = ThemeManager::new();
+    let mut app = App::new(parse("# Title\n"), false, None, &theme_manager);
+    let start = Instant::now();
+
+    app.status_message = Some("Copied".to_string());
+    // 最初の tick で表示を始めた時刻を記録する
+    assert!(!app.expire_status(start));
+    assert!(!app.expire_status(start + Duration::from_secs(4)));
+    assert!(app.expire_status(start + Duration::from_secs(5)));
+    assert!(app.status_message.is_none());
+
+    // 別のメッセージに変わったら計り直す
+    app.status_message = Some("First".to_string());
+    assert!(!app.expire_status(start));
+    app.status_message = Some("Second".to_string());
+    assert!(!app.expire_status(start + Duration::from_secs(4)));
+    assert!(!app.expire_status(start + Duration::from_secs(8)));
+    assert_eq!(app.status_message.as_deref(), Some("Second"));
+}
@@ -0,0 +1,75 @@
+use mdv::markdown::{CodeHighlighter, MarkdownDocument};
+use mdv::output::html::export_html;
+use mdv::tui::ThemeManager;
+
+const MARKDOWN: &str = r#"# Guide <&>
+
+> [!TIP]
+> Use `mdv`.
+
+| Left | Right |
+|:-----|------:|
+| a    | b     |
+
+```rust file=main.rs
+fn main() {}
+```
+
+- [x] done
+
+![logo](logo.png) ![remote](https://example.com/a.png)
+"#;
+
+#[test]
+fn test_export_is_self_contained() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("logo.png"), b"\x89PNG").unwrap();
+    let highlighter = CodeHighlighter::new("base16-ocean.dark".to_string());
+    let document = MarkdownDocument::parse(
+        dir.path().join("guide.md"),
+        MARKDOWN.to_string(),
+        &highlighter,
+    )
+    .unwrap();
+    let theme = ThemeManager::default().current_theme();
+    let html = export_html(&document, &theme, &highlighter);
+
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<title>guide</title>"));
+    // 目次のサイドバーと見出しのアンカー
+    assert!(html.contains("<li class=\"toc-1\"><a href=\"#guide-\">Guide &lt;&amp;&gt;</a></li>"));
+    assert!(html.contains("<h1 id=\"guide-\">Guide &lt;&amp;&gt;</h1>"));
+    assert!(html.contains("<div class=\"alert alert-tip\">"));
+    assert!(html.contains("<p>Use <code>mdv</code>.</p>"));
+    assert!(html.contains(
+        "<th style=\"text-align:left\">Left</th><th style=\"text-align:right\">Right</th>"
+    ));
+    assert!(html.contains("<span class=\"code-lang\">rust · main.rs</span>"));
+    assert!(html.contains("<span style=\"color:#"));
+    assert!(html.contains("<input type=\"checkbox\" disabled checked>"));
+    // ローカルの画像は埋め込み、リモートの画像はそのまま
+    assert!(html.contains("src=\"data:image/png;base64,iVBORw==\""));
+    assert!(html.contains("src=\"https://example.com/a.png\""));
+}
+
+#[test]
+fn test_export_drops_unsafe_urls() {
+    let highlighter = CodeHighlighter::new("base16-ocean.dark".to_string());
+    let document = MarkdownDocument::parse(
+        "unsafe.md".into(),
+        "[click](javascript:alert(document.cookie)) [vb](vbscript:msgbox) [ok](https://example.com) [top](#unsafe)\n\n![x](data:text/html,<script>alert(1)</script>)\n".to_string(),
+        &highlighter,
+    )
+    .unwrap();
+    let theme = ThemeManager::default().current_theme();
+    let html = export_html(&document, &theme, &highlighter);
+
+    // リンクの文字は残し、危険なスキームの href は書かない
+    assert!(!html.contains("javascript:"));
+    assert!(!html.contains("vbscript:"));
+    assert!(html.contains("click"));
+    assert!(html.contains("<a href=\"https://example.com\">ok</a>"));
+    assert!(html.contains("<a href=\"#unsafe\">top</a>"));
+    assert!(!html.contains("data:text/html"));
+    assert!(html.contains("<img alt=\"x\">"));
+}
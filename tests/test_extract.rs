@@ -0,0 +1,110 @@
+use mdv::error::MdError;
+use mdv::markdown::{CodeHighlighter, MarkdownDocument};
+use mdv::output::extract::{concat, extract, group_by_file, write_file, CodeFilter};
+use std::path::PathBuf;
+
+const MARKDOWN: &str = r#"# Runbook
+
+## Setup
+
+```bash file=setup.sh
+apt install postgresql
+```
+
+```sql
+CREATE DATABASE app;
+```
+
+## Deploy
+
+```Bash title="deploy.sh"
+make build
+```
+
+```bash
+echo done
+```
+
+```bash file=deploy.sh
+make deploy
+```
+"#;
+
+fn parse() -> MarkdownDocument {
+    let highlighter = CodeHighlighter::new("base16-ocean.dark".to_string());
+    MarkdownDocument::parse(
+        PathBuf::from("runbook.md"),
+        MARKDOWN.to_string(),
+        &highlighter,
+    )
+    .unwrap()
+}
+
+fn filter(lang: Option<&str>, heading: Option<&str>, index: Option<usize>) -> CodeFilter {
+    CodeFilter {
+        lang: lang.map(str::to_string),
+        heading: heading.map(str::to_string),
+        index,
+    }
+}
+
+#[test]
+fn test_extract_filters() {
+    let document = parse();
+
+    let blocks = extract(&document, &filter(Some("bash"), None, None)).unwrap();
+    assert_eq!(
+        concat(&blocks),
+        "apt install postgresql\nmake build\necho done\nmake deploy\n"
+    );
+    assert_eq!(blocks[0].line, 5);
+
+    let blocks = extract(&document, &filter(None, Some("deploy"), Some(2))).unwrap();
+    assert_eq!(concat(&blocks), "echo done\n");
+
+    let blocks = extract(&document, &filter(Some("sql"), Some("setup"), None)).unwrap();
+    assert_eq!(concat(&blocks), "CREATE DATABASE app;\n");
+
+    assert!(matches!(
+        extract(&document, &filter(Some("sql"), Some("deploy"), None)),
+        Err(MdError::NoCodeBlocks)
+    ));
+    assert!(matches!(
+        extract(&document, &filter(None, Some("rollback"), None)),
+        Err(MdError::HeadingNotFound(_))
+    ));
+}
+
+#[test]
+fn test_tangle_to_files() {
+    let document = parse();
+    let blocks = extract(&document, &filter(Some("bash"), None, None)).unwrap();
+    let groups = group_by_file(&blocks);
+    assert_eq!(
+        groups,
+        vec![
+            (
+                Some("setup.sh".to_string()),
+                "apt install postgresql\n".to_string()
+            ),
+            (
+                Some("deploy.sh".to_string()),
+                "make build\nmake deploy\n".to_string()
+            ),
+            (None, "echo done\n".to_string()),
+        ]
+    );
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_file(dir.path(), "scripts/deploy.sh", &groups[1].1).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(path).unwrap(),
+        "make build\nmake deploy\n"
+    );
+    // 出力先の外には書かない
+    assert!(matches!(
+        write_file(dir.path(), "../deploy.sh", ""),
+        Err(MdError::UnsafeOutputPath(_))
+    ));
+    assert!(write_file(dir.path(), "/tmp/deploy.sh", "").is_err());
+}
@@ -0,0 +1,127 @@
+use crossterm::event::{KeyCode, KeyModifiers};
+use mdv::markdown::{CodeHighlighter, MarkdownDocument};
+use mdv::tui::file_tree::FileTree;
+use mdv::tui::{App, AppAction, ThemeManager};
+use std::fs;
+use std::path::Path;
+
+fn write(root: &Path, path: &str, content: &str) {
+    let path = root.join(path);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+fn docs_dir() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    write(dir.path(), "README.md", "# Docs\n");
+    write(dir.path(), "intro.md", "# Intro\n");
+    write(dir.path(), "guide/setup.md", "# Setup\n");
+    write(dir.path(), "guide/notes.txt", "not markdown\n");
+    write(dir.path(), "build/generated.md", "# Generated\n");
+    write(dir.path(), ".hidden/secret.md", "# Secret\n");
+    write(dir.path(), ".gitignore", "build/\n");
+    dir
+}
+
+fn names(tree: &FileTree) -> Vec<String> {
+    tree.rows()
+        .iter()
+        .map(|&index| {
+            let entry = &tree.entries[index];
+            format!("{}{}", "  ".repeat(entry.depth), entry.name())
+        })
+        .collect()
+}
+
+#[test]
+fn test_lists_markdown_files_respecting_gitignore() {
+    let dir = docs_dir();
+    let mut tree = FileTree::new(dir.path());
+
+    assert_eq!(
+        names(&tree),
+        vec!["guide", "  setup.md", "README.md", "intro.md"]
+    );
+    assert_eq!(tree.default_file(), Some(dir.path().join("README.md")));
+
+    // 追加・削除したファイルは読み直すと一覧に反映する
+    tree.selected = tree.position(&dir.path().join("intro.md")).unwrap();
+    write(dir.path(), "guide/advanced.md", "# Advanced\n");
+    fs::remove_file(dir.path().join("README.md")).unwrap();
+    assert!(tree.rescan());
+    assert!(!tree.rescan());
+    assert_eq!(
+        names(&tree),
+        vec!["guide", "  advanced.md", "  setup.md", "intro.md"]
+    );
+    assert_eq!(tree.selected_entry().unwrap().name(), "intro.md");
+    assert_eq!(
+        tree.default_file(),
+        Some(dir.path().join("guide/advanced.md"))
+    );
+
+    assert!(tree.is_affected_by(&dir.path().join("guide/new.md")));
+    assert!(!tree.is_affected_by(&dir.path().join("guide/notes.txt")));
+    assert!(!tree.is_affected_by(Path::new("/elsewhere/other.md")));
+}
+
+#[test]
+fn test_tree_keys_preview_files() {
+    let dir = docs_dir();
+    let highlighter = CodeHighlighter::new("base16-ocean.dark".to_string());
+    let readme = dir.path().join("README.md");
+    let document =
+        MarkdownDocument::parse(readme.clone(), "# Docs\n".to_string(), &highlighter).unwrap();
+    let theme_manager = ThemeManager::default();
+    let mut app = App::new(document, false, None, &theme_manager);
+    app.set_file_tree(FileTree::new(dir.path()));
+
+    // 表示中のファイルを選んだ状態で始まる
+    assert!(app.file_tree_focused());
+    assert_eq!(
+        app.file_tree
+            .as_ref()
+            .unwrap()
+            .selected_entry()
+            .unwrap()
+            .path,
+        readme
+    );
+
+    app.handle_key(KeyCode::Char('j'), KeyModifiers::NONE);
+    assert_eq!(
+        app.pending_action.take(),
+        Some(AppAction::OpenFile {
+            path: dir.path().join("intro.md"),
+            anchor: None,
+        })
+    );
+
+    // ディレクトリは `h` で折りたたみ、中のファイルは表示しない
+    app.handle_key(KeyCode::Char('k'), KeyModifiers::NONE);
+    app.handle_key(KeyCode::Char('k'), KeyModifiers::NONE);
+    app.handle_key(KeyCode::Char('k'), KeyModifiers::NONE);
+    app.handle_key(KeyCode::Char('h'), KeyModifiers::NONE);
+    let tree = app.file_tree.as_ref().unwrap();
+    assert_eq!(names(tree), vec!["guide", "README.md", "intro.md"]);
+    app.pending_action = None;
+
+    app.handle_key(KeyCode::Char('l'), KeyModifiers::NONE);
+    app.handle_key(KeyCode::Char('l'), KeyModifiers::NONE);
+    assert_eq!(
+        app.pending_action.take(),
+        Some(AppAction::OpenFile {
+            path: dir.path().join("guide").join("setup.md"),
+            anchor: None,
+        })
+    );
+
+    // 目次を開くとキーは目次に渡り、`e` でツリーに戻る
+    app.handle_key(KeyCode::Char('t'), KeyModifiers::NONE);
+    assert!(!app.file_tree_focused());
+    app.handle_key(KeyCode::Char('e'), KeyModifiers::NONE);
+    assert!(app.file_tree_focused());
+    assert!(!app.show_toc);
+    app.handle_key(KeyCode::Char('e'), KeyModifiers::NONE);
+    assert!(!app.show_file_tree);
+}
@@ -0,0 +1,127 @@
+use crossterm::event::{KeyCode, KeyModifiers};
+use mdv::markdown::{CodeHighlighter, MarkdownDocument};
+use mdv::tui::{App, ThemeManager};
+use std::path::PathBuf;
+
+const DOC: &str = r#"# Spec
+
+Overview.
+
+## Details
+
+```rust file=spec.rs
+fn hidden() {}
+```
+
+More details.
+
+## Appendix
+
+The end.
+"#;
+
+fn parse(md: &str) -> MarkdownDocument {
+    let highlighter = CodeHighlighter::new("base16-ocean.dark".to_string());
+    MarkdownDocument::parse(PathBuf::from("fold.md"), md.to_string(), &highlighter).unwrap()
+}
+
+fn new_app(theme_manager: &ThemeManager) -> App<'_> {
+    let mut app = App::new(parse(DOC), false, None, theme_manager);
+    app.viewport_height = 40;
+    app.ensure_layout(80);
+    app
+}
+
+fn keys(app: &mut App, keys: &str) {
+    for c in keys.chars() {
+        let modifiers = if c.is_ascii_uppercase() {
+            KeyModifiers::SHIFT
+        } else {
+            KeyModifiers::NONE
+        };
+        app.handle_key(KeyCode::Char(c), modifiers);
+    }
+}
+
+fn screen_text(app: &App) -> String {
+    let layout = app.layout.as_ref().unwrap();
+    layout
+        .rows(0..layout.row_count())
+        .iter()
+        .map(|line| line.to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn test_fold_all_and_open_all() {
+    let theme_manager = ThemeManager::new();
+    let mut app = new_app(&theme_manager);
+
+    keys(&mut app, "zM");
+    let text = screen_text(&app);
+    assert!(text.contains("Spec ══╗ ▸ … (14 lines)"));
+    assert!(!text.contains("Overview"));
+
+    // 外側のセクションを開いても内側は折りたたんだまま
+    keys(&mut app, "zo");
+    let text = screen_text(&app);
+    assert!(text.contains("Overview"));
+    assert!(text.contains("Details ▸ … (6 lines)"));
+    assert!(!text.contains("hidden"));
+
+    keys(&mut app, "zR");
+    let text = screen_text(&app);
+    assert!(text.contains("fn hidden() {}"));
+    assert!(text.contains("┌─[ rust ] spec.rs ──"));
+    assert!(!text.contains("file="));
+    assert_eq!(app.document.folded_blocks().count(), 0);
+}
+
+#[test]
+fn test_fold_code_block_and_search_unfolds() {
+    let theme_manager = ThemeManager::new();
+    let mut app = new_app(&theme_manager);
+    app.viewport_height = 3;
+    let code = app
+        .document
+        .parsed_lines
+        .iter()
+        .position(|line| matches!(line, mdv::markdown::ParsedLine::Code { .. }))
+        .unwrap();
+    app.scroll_to_block(code);
+
+    keys(&mut app, "za");
+    assert!(app.document.is_folded(code));
+    // info string の属性はそのまま出さず、言語とファイル名に分ける
+    assert!(screen_text(&app).contains("▸ [ rust ] spec.rs … (1 line)"));
+
+    // 折りたたんだ中身も検索でき、移動すると開く
+    keys(&mut app, "/hidden");
+    app.handle_key(KeyCode::Enter, KeyModifiers::NONE);
+    assert!(!app.document.is_folded(code));
+    assert_eq!(
+        app.search.as_ref().unwrap().counter().as_deref(),
+        Some("1/1")
+    );
+}
+
+#[test]
+fn test_folds_survive_reload() {
+    let theme_manager = ThemeManager::new();
+    let mut app = new_app(&theme_manager);
+    keys(&mut app, "zM");
+
+    // 見出しを足しても同じアンカーのセクションが折りたたまれる
+    let edited = DOC.replace("## Appendix", "## New\n\nAdded.\n\n## Appendix");
+    app.update_document(parse(&edited));
+    let folded: Vec<String> = app
+        .document
+        .folded_blocks()
+        .filter_map(|block| match &app.document.parsed_lines[block] {
+            mdv::markdown::ParsedLine::Heading { anchor, .. } => Some(anchor.clone()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(folded, ["spec", "details", "appendix"]);
+}
@@ -0,0 +1,57 @@
+use crossterm::event::{KeyCode, KeyModifiers};
+use mdv::markdown::{CodeHighlighter, MarkdownDocument};
+use mdv::tui::{App, ThemeManager};
+use std::path::PathBuf;
+
+const DOC: &str = r#"# Notes
+
+A claim[^claim] and another[^other].
+
+Plain paragraph.
+
+[^claim]: Supported by evidence.
+[^other]: Another note.
+"#;
+
+fn parse(md: &str) -> MarkdownDocument {
+    let highlighter = CodeHighlighter::new("base16-ocean.dark".to_string());
+    MarkdownDocument::parse(PathBuf::from("notes.md"), md.to_string(), &highlighter).unwrap()
+}
+
+#[test]
+fn test_footnote_popup_keeps_scroll_position() {
+    let theme_manager = ThemeManager::new();
+    let mut app = App::new(parse(DOC), false, None, &theme_manager);
+    app.viewport_height = 2;
+    app.ensure_layout(80);
+    app.scroll_to_block(1);
+    let top = app.scroll_offset;
+
+    app.handle_key(KeyCode::Char('f'), KeyModifiers::NONE);
+    assert_eq!(app.footnote_popup, [1, 2]);
+
+    // ポップアップ表示中はスクロールしない
+    app.handle_key(KeyCode::Char('j'), KeyModifiers::NONE);
+    assert_eq!(app.scroll_offset, top);
+    assert_eq!(app.footnote_popup, [1, 2]);
+
+    app.handle_key(KeyCode::Esc, KeyModifiers::NONE);
+    assert!(app.footnote_popup.is_empty());
+    assert_eq!(app.scroll_offset, top);
+}
+
+#[test]
+fn test_footnote_popup_without_references() {
+    let theme_manager = ThemeManager::new();
+    let mut app = App::new(parse(DOC), false, None, &theme_manager);
+    app.viewport_height = 1;
+    app.ensure_layout(80);
+    app.scroll_to_block(2);
+
+    app.handle_key(KeyCode::Char('f'), KeyModifiers::NONE);
+    assert!(app.footnote_popup.is_empty());
+    assert_eq!(
+        app.status_message.as_deref(),
+        Some("No footnotes on screen")
+    );
+}
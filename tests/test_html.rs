@@ -0,0 +1,104 @@
+use mdv::markdown::{
+    plain_text, CodeHighlighter, ContainerKind, InlineSpan, MarkdownDocument, ParsedLine,
+};
+use std::path::PathBuf;
+
+fn parse(md: &str) -> MarkdownDocument {
+    let highlighter = CodeHighlighter::new("base16-ocean.dark".to_string());
+    MarkdownDocument::parse(PathBuf::from("test.md"), md.to_string(), &highlighter).unwrap()
+}
+
+fn text_spans(doc: &MarkdownDocument) -> Vec<&InlineSpan> {
+    doc.parsed_lines
+        .iter()
+        .filter_map(|line| match line {
+            ParsedLine::Text { content } => Some(content),
+            _ => None,
+        })
+        .flatten()
+        .collect()
+}
+
+#[test]
+fn test_inline_html_tags() {
+    let doc = parse("Press <kbd>Ctrl</kbd>, H<sub>2</sub>O, x<sup>2</sup><br>next <!-- note --><span>kept</span>\n");
+    let spans = text_spans(&doc);
+
+    let kbd: Vec<_> = spans.iter().filter(|span| span.style.kbd).collect();
+    assert_eq!(kbd.len(), 1);
+    assert_eq!(kbd[0].text, "Ctrl");
+
+    let text: String = spans.iter().map(|span| span.text.as_str()).collect();
+    assert_eq!(text, "Press Ctrl, H₂O, x²\nnext <span>kept</span>");
+
+    // 解釈できないタグは薄く表示するために印を付けて残す
+    let tags: Vec<_> = spans
+        .iter()
+        .filter(|span| span.style.html)
+        .map(|span| span.text.as_str())
+        .collect();
+    assert_eq!(tags, ["<span>", "</span>"]);
+}
+
+#[test]
+fn test_unclosed_inline_tags_end_with_block() {
+    let doc = parse("Lead <sup>up <kbd>K\n\nTrailing\n\n- a <sub>x\n- b\n");
+
+    let texts: Vec<String> = doc
+        .parsed_lines
+        .iter()
+        .filter_map(|line| match line {
+            ParsedLine::Text { content } | ParsedLine::ListItem { content, .. } => {
+                Some(plain_text(content))
+            }
+            _ => None,
+        })
+        .collect();
+    assert_eq!(texts, ["Lead ᵘᵖ ᴷ", "Trailing", "a ₓ", "b"]);
+    // 次の段落に `<kbd>` の装飾も持ち越さない
+    assert!(text_spans(&doc)
+        .iter()
+        .filter(|span| span.text == "Trailing")
+        .all(|span| !span.style.kbd));
+}
+
+#[test]
+fn test_html_block_image_and_alignment() {
+    let doc = parse("<p align=\"center\">\n  <img src=\"logo.png\" alt=\"Logo\">\n</p>\n\n<h1 align=\"center\">Project</h1>\n");
+
+    assert!(matches!(
+        &doc.parsed_lines[0],
+        ParsedLine::Image { alt_text, url } if alt_text == "Logo" && url == "logo.png"
+    ));
+    assert!(matches!(
+        doc.nesting[0][..],
+        [mdv::markdown::Nesting {
+            kind: ContainerKind::Aligned(mdv::markdown::Alignment::Center),
+            ..
+        }]
+    ));
+
+    assert!(matches!(
+        &doc.parsed_lines[1],
+        ParsedLine::Heading { level: 1, .. }
+    ));
+    assert_eq!(doc.toc[0].title, "Project");
+    assert_eq!(doc.toc[0].anchor, "project");
+}
+
+#[test]
+fn test_details_block() {
+    let doc = parse("<details>\n<summary>More</summary>\n\nHidden text.\n\n</details>\n\nAfter.\n");
+
+    assert!(matches!(
+        &doc.parsed_lines[0],
+        ParsedLine::Details { index: 0, summary, open: false } if plain_text(summary) == "More"
+    ));
+    assert!(!doc.is_hidden(0));
+    assert!(doc.is_hidden(1));
+    assert!(!doc.is_hidden(2));
+
+    let mut doc = doc;
+    doc.set_details_open(0, true);
+    assert!(!doc.is_hidden(1));
+}
@@ -0,0 +1,83 @@
+use crossterm::event::{KeyCode, KeyModifiers};
+use mdv::markdown::{CodeHighlighter, MarkdownDocument};
+use mdv::tui::{App, AppAction, ThemeManager};
+use std::path::PathBuf;
+
+const DOC: &str = r#"# Intro
+
+See [install](#installation), the [guide](docs/guide.md#usage)
+and [the site](https://example.com).
+
+## Installation
+
+Run it.
+"#;
+
+fn parse(md: &str) -> MarkdownDocument {
+    let highlighter = CodeHighlighter::new("base16-ocean.dark".to_string());
+    MarkdownDocument::parse(PathBuf::from("README.md"), md.to_string(), &highlighter).unwrap()
+}
+
+#[test]
+fn test_links_are_collected_in_order() {
+    let doc = parse(DOC);
+
+    let urls: Vec<_> = doc.links.iter().map(|l| l.url.as_str()).collect();
+    assert_eq!(
+        urls,
+        [
+            "#installation",
+            "docs/guide.md#usage",
+            "https://example.com"
+        ]
+    );
+    assert_eq!(doc.links[2].text, "the site");
+}
+
+#[test]
+fn test_tab_cycles_links() {
+    let theme_manager = ThemeManager::new();
+    let mut app = App::new(parse(DOC), false, None, &theme_manager);
+
+    app.handle_key(KeyCode::Tab, KeyModifiers::NONE);
+    assert_eq!(app.selected_link, Some(0));
+    app.handle_key(KeyCode::Tab, KeyModifiers::NONE);
+    assert_eq!(app.selected_link, Some(1));
+    app.handle_key(KeyCode::BackTab, KeyModifiers::SHIFT);
+    app.handle_key(KeyCode::BackTab, KeyModifiers::SHIFT);
+    assert_eq!(app.selected_link, Some(2));
+
+    app.handle_key(KeyCode::Esc, KeyModifiers::NONE);
+    assert_eq!(app.selected_link, None);
+}
+
+#[test]
+fn test_follow_links() {
+    let theme_manager = ThemeManager::new();
+    let mut app = App::new(parse(DOC), false, None, &theme_manager);
+
+    // アンカーリンクはドキュメント内の見出しへジャンプ
+    app.handle_key(KeyCode::Tab, KeyModifiers::NONE);
+    app.handle_key(KeyCode::Enter, KeyModifiers::NONE);
+    assert_eq!(app.toc_selected, 1);
+    assert_eq!(app.pending_action, None);
+
+    // 相対 .md リンクはビューアーで開く
+    app.selected_link = Some(1);
+    app.handle_key(KeyCode::Enter, KeyModifiers::NONE);
+    assert_eq!(
+        app.pending_action.take(),
+        Some(AppAction::OpenFile {
+            path: PathBuf::from("docs/guide.md"),
+            anchor: Some("usage".to_string()),
+        })
+    );
+
+    // 外部URLはオープナーに渡す
+    app.selected_link = Some(2);
+    app.handle_key(KeyCode::Enter, KeyModifiers::NONE);
+    assert_eq!(
+        app.pending_action.take(),
+        Some(AppAction::OpenExternal("https://example.com".to_string()))
+    );
+}
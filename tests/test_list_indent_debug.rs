@@ -1,4 +1,4 @@
-use mdv::markdown::{CodeHighlighter, MarkdownDocument, ParsedLine};
+use mdv::markdown::{plain_text, CodeHighlighter, MarkdownDocument, ParsedLine};
 use std::path::PathBuf;
 
 #[test]
@@ -23,7 +23,7 @@ fn test_list_indent_detailed() {
                 indent, content, ..
             } = line
             {
-                Some((*indent, content.clone()))
+                Some((*indent, plain_text(content)))
             } else {
                 None
             }
@@ -1,4 +1,4 @@
-use mdv::markdown::{CodeHighlighter, MarkdownDocument, ParsedLine};
+use mdv::markdown::{plain_text, CodeHighlighter, ListMarker, MarkdownDocument, ParsedLine};
 use std::path::PathBuf;
 
 #[test]
@@ -58,7 +58,7 @@ fn test_nested_list() {
                 indent, content, ..
             } = line
             {
-                Some((*indent, content.clone()))
+                Some((*indent, plain_text(content)))
             } else {
                 None
             }
@@ -73,3 +73,50 @@ fn test_nested_list() {
     let has_nested = list_items.iter().any(|(indent, _)| *indent > 0);
     assert!(has_nested, "Should have nested items");
 }
+
+#[test]
+fn test_ordered_list_numbers() {
+    let md = r#"
+7. Seventh
+8. Eighth
+   1) Nested one
+   2) Nested two
+- Bullet
+"#;
+
+    let highlighter = CodeHighlighter::new("base16-ocean.dark".to_string());
+    let doc =
+        MarkdownDocument::parse(PathBuf::from("test.md"), md.to_string(), &highlighter).unwrap();
+
+    let markers: Vec<_> = doc
+        .parsed_lines
+        .iter()
+        .filter_map(|line| match line {
+            ParsedLine::ListItem { marker, .. } => Some(*marker),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(
+        markers,
+        [
+            ListMarker::Ordered {
+                number: 7,
+                delimiter: '.'
+            },
+            ListMarker::Ordered {
+                number: 8,
+                delimiter: '.'
+            },
+            ListMarker::Ordered {
+                number: 1,
+                delimiter: ')'
+            },
+            ListMarker::Ordered {
+                number: 2,
+                delimiter: ')'
+            },
+            ListMarker::Bullet('-'),
+        ]
+    );
+}
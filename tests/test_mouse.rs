@@ -0,0 +1,135 @@
+use crossterm::event::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use mdv::markdown::{CodeHighlighter, MarkdownDocument};
+use mdv::tui::{App, AppAction, ThemeManager};
+use ratatui::{backend::TestBackend, Terminal};
+use std::path::PathBuf;
+
+fn long_doc() -> String {
+    let mut md = String::from("# Intro\n\nSee [the site](https://example.com).\n");
+    for i in 1..=12 {
+        md.push_str(&format!("\n## Section {i}\n\nParagraph {i}.\n"));
+    }
+    md
+}
+
+fn parse(md: &str) -> MarkdownDocument {
+    let highlighter = CodeHighlighter::new("base16-ocean.dark".to_string());
+    MarkdownDocument::parse(PathBuf::from("mouse.md"), md.to_string(), &highlighter).unwrap()
+}
+
+fn draw<'a>(
+    terminal: &mut Terminal<TestBackend>,
+    app: &mut App<'a>,
+    theme_manager: &'a ThemeManager,
+) {
+    terminal
+        .draw(|frame| mdv::tui::ui::render(frame, app, theme_manager))
+        .unwrap();
+}
+
+fn mouse(kind: MouseEventKind, column: u16, row: u16) -> MouseEvent {
+    MouseEvent {
+        kind,
+        column,
+        row,
+        modifiers: KeyModifiers::NONE,
+    }
+}
+
+#[test]
+fn test_wheel_scrolls_content_and_toc_independently() {
+    let theme_manager = ThemeManager::new();
+    let mut app = App::new(parse(&long_doc()), true, None, &theme_manager);
+    let mut terminal = Terminal::new(TestBackend::new(80, 12)).unwrap();
+    draw(&mut terminal, &mut app, &theme_manager);
+
+    let content = app.screen_areas.content;
+    app.handle_mouse(mouse(MouseEventKind::ScrollDown, content.x + 1, content.y));
+    assert_eq!(app.scroll_offset, 3);
+    assert_eq!(app.toc_offset, 0);
+
+    let toc = app.screen_areas.toc.unwrap();
+    app.handle_mouse(mouse(MouseEventKind::ScrollDown, toc.x + 1, toc.y));
+    assert_eq!(app.toc_offset, 3);
+    assert_eq!(app.scroll_offset, 3);
+    assert_eq!(app.toc_selected, 0);
+}
+
+#[test]
+fn test_click_toc_entry_jumps_to_heading() {
+    let theme_manager = ThemeManager::new();
+    let mut app = App::new(parse(&long_doc()), true, None, &theme_manager);
+    let mut terminal = Terminal::new(TestBackend::new(80, 12)).unwrap();
+    draw(&mut terminal, &mut app, &theme_manager);
+
+    // 3行目は `Section 2`
+    let toc = app.screen_areas.toc.unwrap();
+    app.handle_mouse(mouse(
+        MouseEventKind::Down(MouseButton::Left),
+        toc.x + 4,
+        toc.y + 2,
+    ));
+
+    assert_eq!(app.toc_selected, 2);
+    assert!(!app.show_toc);
+    assert_eq!(app.document.toc[2].title, "Section 2");
+    assert_eq!(
+        app.document.source_line(app.top_block()),
+        app.document.toc[2].line_number
+    );
+}
+
+#[test]
+fn test_click_link_follows_it() {
+    let theme_manager = ThemeManager::new();
+    let mut app = App::new(parse(&long_doc()), false, None, &theme_manager);
+    let mut terminal = Terminal::new(TestBackend::new(80, 12)).unwrap();
+    draw(&mut terminal, &mut app, &theme_manager);
+
+    let link = app.link_regions[0].area;
+    app.handle_mouse(mouse(
+        MouseEventKind::Down(MouseButton::Left),
+        link.x + 2,
+        link.y,
+    ));
+
+    assert_eq!(
+        app.pending_action,
+        Some(AppAction::OpenExternal("https://example.com".to_string()))
+    );
+}
+
+#[test]
+fn test_drag_scrollbar() {
+    let theme_manager = ThemeManager::new();
+    let mut app = App::new(parse(&long_doc()), false, None, &theme_manager);
+    let mut terminal = Terminal::new(TestBackend::new(80, 12)).unwrap();
+    draw(&mut terminal, &mut app, &theme_manager);
+
+    let scrollbar = app.screen_areas.scrollbar.unwrap();
+    let bottom = scrollbar.y + scrollbar.height - 1;
+    app.handle_mouse(mouse(
+        MouseEventKind::Down(MouseButton::Left),
+        scrollbar.x,
+        scrollbar.y,
+    ));
+    app.handle_mouse(mouse(
+        MouseEventKind::Drag(MouseButton::Left),
+        scrollbar.x,
+        bottom,
+    ));
+    assert_eq!(app.scroll_offset, app.max_scroll());
+
+    app.handle_mouse(mouse(
+        MouseEventKind::Up(MouseButton::Left),
+        scrollbar.x,
+        bottom,
+    ));
+    // ボタンを離した後のドラッグではスクロールしない
+    app.handle_mouse(mouse(
+        MouseEventKind::Drag(MouseButton::Left),
+        scrollbar.x,
+        scrollbar.y,
+    ));
+    assert_eq!(app.scroll_offset, app.max_scroll());
+}
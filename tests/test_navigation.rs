@@ -0,0 +1,160 @@
+use crossterm::event::{KeyCode, KeyModifiers};
+use mdv::markdown::{CodeHighlighter, MarkdownDocument};
+use mdv::tui::{App, AppAction, ThemeManager};
+use std::path::PathBuf;
+
+fn parse(name: &str, md: &str) -> MarkdownDocument {
+    let highlighter = CodeHighlighter::new("base16-ocean.dark".to_string());
+    MarkdownDocument::parse(PathBuf::from(name), md.to_string(), &highlighter).unwrap()
+}
+
+fn doc(sections: &[&str]) -> String {
+    let mut md = String::new();
+    for section in sections {
+        md.push_str(&format!("## {section}\n\n"));
+        for i in 0..10 {
+            md.push_str(&format!("{section} paragraph {i}.\n\n"));
+        }
+    }
+    md
+}
+
+fn guide() -> MarkdownDocument {
+    let md = format!(
+        "Skip to [usage](#usage).\n\n{}See [other](other.md).\n",
+        doc(&["Intro", "Install", "Usage"])
+    );
+    parse("guide.md", &md)
+}
+
+fn new_app(theme_manager: &ThemeManager) -> App<'_> {
+    let mut app = App::new(guide(), false, None, theme_manager);
+    app.viewport_height = 10;
+    app.ensure_layout(80);
+    app
+}
+
+fn key(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
+    app.handle_key(code, modifiers);
+    app.ensure_layout(80);
+}
+
+fn back(app: &mut App) {
+    key(app, KeyCode::Char('o'), KeyModifiers::CONTROL);
+}
+
+fn forward(app: &mut App) {
+    key(app, KeyCode::Char('i'), KeyModifiers::CONTROL);
+}
+
+#[test]
+fn test_back_and_forward_across_jumps() {
+    let theme_manager = ThemeManager::default();
+    let mut app = new_app(&theme_manager);
+
+    key(&mut app, KeyCode::Char('j'), KeyModifiers::NONE);
+    key(&mut app, KeyCode::Char('j'), KeyModifiers::NONE);
+    let start = app.scroll_offset;
+
+    // 目次から見出しへ
+    key(&mut app, KeyCode::Char('t'), KeyModifiers::NONE);
+    key(&mut app, KeyCode::Char('j'), KeyModifiers::NONE);
+    key(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+    let install = app.scroll_offset;
+    assert!(install > start);
+
+    key(&mut app, KeyCode::Char('G'), KeyModifiers::SHIFT);
+    let bottom = app.scroll_offset;
+    assert_eq!(app.breadcrumbs(), vec!["Intro", "Install", "Usage"]);
+
+    back(&mut app);
+    assert_eq!(app.scroll_offset, install);
+    key(&mut app, KeyCode::Backspace, KeyModifiers::NONE);
+    assert_eq!(app.scroll_offset, start);
+    back(&mut app);
+    assert_eq!(app.scroll_offset, start);
+    assert_eq!(
+        app.status_message.as_deref(),
+        Some("Already at oldest position")
+    );
+
+    forward(&mut app);
+    assert_eq!(app.scroll_offset, install);
+    // `Ctrl+I` を区別できない端末では `Tab` として届くのでリンクの選択になる
+    key(&mut app, KeyCode::Tab, KeyModifiers::NONE);
+    assert!(app.selected_link.is_some());
+    key(&mut app, KeyCode::Esc, KeyModifiers::NONE);
+    // `]` はどの端末でも進む
+    key(&mut app, KeyCode::Char(']'), KeyModifiers::NONE);
+    assert_eq!(app.scroll_offset, bottom);
+    forward(&mut app);
+    assert_eq!(
+        app.status_message.as_deref(),
+        Some("Already at newest position")
+    );
+}
+
+#[test]
+fn test_search_and_anchor_jumps_are_recorded() {
+    let theme_manager = ThemeManager::default();
+    let mut app = new_app(&theme_manager);
+
+    // `#usage` へのリンク
+    key(&mut app, KeyCode::Tab, KeyModifiers::NONE);
+    key(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+    let usage = app.scroll_offset;
+    assert!(usage > 0);
+    back(&mut app);
+    assert_eq!(app.scroll_offset, 0);
+
+    for c in "/paragraph 5".chars() {
+        key(&mut app, KeyCode::Char(c), KeyModifiers::NONE);
+    }
+    key(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+    let first = app.scroll_offset;
+    key(&mut app, KeyCode::Char('n'), KeyModifiers::NONE);
+    assert!(app.scroll_offset > first);
+
+    back(&mut app);
+    assert_eq!(app.scroll_offset, first);
+    back(&mut app);
+    assert_eq!(app.scroll_offset, 0);
+}
+
+#[test]
+fn test_back_reopens_previous_file() {
+    let theme_manager = ThemeManager::default();
+    let mut app = new_app(&theme_manager);
+    key(&mut app, KeyCode::Char('G'), KeyModifiers::SHIFT);
+    let bottom = app.scroll_offset;
+
+    // 別のファイルへのリンクを開く（読み込みは App の外で行う）
+    key(&mut app, KeyCode::Tab, KeyModifiers::NONE);
+    key(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+    assert_eq!(
+        app.pending_action.take(),
+        Some(AppAction::OpenFile {
+            path: PathBuf::from("other.md"),
+            anchor: None,
+        })
+    );
+    app.open_document(parse("other.md", &doc(&["Other"])), None);
+    app.ensure_layout(80);
+    assert_eq!(
+        app.breadcrumbs(),
+        vec!["guide.md: Top", "guide.md: Usage", "Other"]
+    );
+
+    back(&mut app);
+    assert_eq!(
+        app.pending_action.take(),
+        Some(AppAction::OpenFile {
+            path: PathBuf::from("guide.md"),
+            anchor: None,
+        })
+    );
+    app.open_document(guide(), None);
+    app.ensure_layout(80);
+    assert_eq!(app.document.path, PathBuf::from("guide.md"));
+    assert_eq!(app.scroll_offset, bottom);
+}
@@ -0,0 +1,143 @@
+use mdv::markdown::{CodeHighlighter, MarkdownDocument};
+use mdv::output::print::render_document;
+use mdv::output::PrintOptions;
+use mdv::tui::ThemeManager;
+use std::path::PathBuf;
+use unicode_width::UnicodeWidthStr;
+
+const MARKDOWN: &str = "# Title
+
+> [!WARNING]
+> Careful
+
+- item
+  - nested
+- [x] done
+
+| a | b |
+|:--|--:|
+| 1 | 22 |
+
+A paragraph that is long enough to wrap at the given width.
+";
+
+fn render(markdown: &str, options: PrintOptions) -> String {
+    let highlighter = CodeHighlighter::new("base16-ocean.dark".to_string());
+    let document =
+        MarkdownDocument::parse(PathBuf::from("test.md"), markdown.to_string(), &highlighter)
+            .unwrap();
+    let theme = ThemeManager::default().current_theme();
+    render_document(&document, &theme, options)
+}
+
+#[test]
+fn test_plain_output_is_ascii_and_wrapped() {
+    let output = render(
+        MARKDOWN,
+        PrintOptions {
+            width: 30,
+            color: false,
+            ascii: true,
+        },
+    );
+    assert!(output.is_ascii());
+    assert!(output.lines().all(|line| line.width() <= 30));
+    assert!(!output.contains('\x1b'));
+    assert!(output.starts_with("\n+== Title ==+\n"));
+    assert!(output.contains("\n+-- ! WARNING ---"));
+    assert!(output.contains("\n* item\n  * nested\n[x] done\n"));
+    assert!(output.contains("| a   |   b |\n+-----+-----+\n| 1   |  22 |\n"));
+    assert!(output.contains("\nA paragraph that is long\nenough to wrap at the given\nwidth.\n"));
+}
+
+#[test]
+fn test_colored_output_keeps_box_drawing() {
+    let output = render(
+        MARKDOWN,
+        PrintOptions {
+            width: 30,
+            color: true,
+            ascii: false,
+        },
+    );
+    assert!(output.contains("\x1b["));
+    let escapes = regex::Regex::new("\x1b\\[[0-9;]*m").unwrap();
+    let visible = escapes.replace_all(&output, "");
+    assert!(visible.starts_with("\n╔══ Title ══╗\n"));
+    assert!(visible.lines().all(|line| line.width() <= 30));
+}
+
+#[test]
+fn test_nested_code_block_fits_width() {
+    let markdown = "- item\n\n  > quote\n  >\n  > ```bash\n  > echo hello\n  > ```\n";
+    let output = render(
+        markdown,
+        PrintOptions {
+            width: 40,
+            color: false,
+            ascii: true,
+        },
+    );
+    // 枠が折り返さず、上下の辺と中身の右端が揃う
+    assert!(output.lines().all(|line| line.width() <= 40), "{output}");
+    let lines: Vec<&str> = output.lines().collect();
+    let top = lines
+        .iter()
+        .position(|line| line.contains("[ bash ]"))
+        .unwrap();
+    let code_box = &lines[top..top + 3];
+    assert!(code_box[1].contains("echo hello"));
+    assert!(code_box
+        .iter()
+        .all(|line| line.width() == code_box[0].width()));
+}
+
+#[test]
+fn test_plain_output_transliterates_math_and_keeps_boxes_aligned() {
+    let markdown = "Text with a note[^1] and $x^2 + \\alpha_i$ inline.
+
+$$
+\\sum_{i=1}^{n} x_i^2
+$$
+
+> [!TIP]
+> Use “quotes” — and café.
+
+> [!CAUTION]
+> Stop.
+
+| 名前 | 値 |
+|---|---|
+| α | ² |
+
+[^1]: Footnote ≥ 1.
+";
+    let output = render(
+        markdown,
+        PrintOptions {
+            width: 50,
+            color: false,
+            ascii: true,
+        },
+    );
+    assert!(output.bytes().all(|byte| byte.is_ascii()));
+    assert!(output.contains("note[1] and x^2 + alpha_i inline."));
+    assert!(output.contains("| \\sum_{i=1}^{n} x_i^2"));
+    assert!(output.contains("Use \"quotes\" -- and cafe."));
+    assert!(output.contains("| alpha | ^2  |"));
+    assert!(output.contains("1. Footnote >= 1."));
+
+    // 絵文字のアイコンを置き換えても見出しと下端の罫線は同じ幅
+    let lines: Vec<&str> = output.lines().collect();
+    for title in ["TIP", "CAUTION"] {
+        let header = lines
+            .iter()
+            .position(|line| line.starts_with("+--") && line.contains(title))
+            .unwrap();
+        let footer = lines[header..]
+            .iter()
+            .find(|line| line.starts_with("+---"))
+            .unwrap();
+        assert_eq!(lines[header].width(), footer.width(), "{title}");
+    }
+}
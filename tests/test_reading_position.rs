@@ -0,0 +1,158 @@
+use crossterm::event::{KeyCode, KeyModifiers};
+use mdv::markdown::{CodeHighlighter, MarkdownDocument, ParsedLine};
+use mdv::state::reading_position::MAX_FILES;
+use mdv::state::{ReadingPosition, StateStore};
+use mdv::tui::{App, ThemeManager};
+use std::path::{Path, PathBuf};
+
+fn parse(md: &str) -> MarkdownDocument {
+    let highlighter = CodeHighlighter::new("base16-ocean.dark".to_string());
+    MarkdownDocument::parse(PathBuf::from("guide.md"), md.to_string(), &highlighter).unwrap()
+}
+
+fn doc(intro_paragraphs: usize) -> String {
+    let mut md = String::from("# Guide\n\n");
+    for i in 0..intro_paragraphs {
+        md.push_str(&format!("Intro {i}.\n\n"));
+    }
+    for section in ["Install", "Usage", "License"] {
+        md.push_str(&format!("## {section}\n\n"));
+        for i in 0..8 {
+            md.push_str(&format!("{section} {i}.\n\n"));
+        }
+    }
+    md
+}
+
+fn keys(app: &mut App, keys: &str) {
+    for c in keys.chars() {
+        let modifiers = if c.is_ascii_uppercase() {
+            KeyModifiers::SHIFT
+        } else {
+            KeyModifiers::NONE
+        };
+        app.handle_key(KeyCode::Char(c), modifiers);
+        app.ensure_layout(80);
+    }
+}
+
+fn top_text(app: &App) -> String {
+    match &app.document.parsed_lines[app.top_block()] {
+        ParsedLine::Text { content } => content.iter().map(|span| span.text.as_str()).collect(),
+        ParsedLine::Heading { anchor, .. } => format!("#{anchor}"),
+        _ => String::new(),
+    }
+}
+
+#[test]
+fn test_position_survives_edits_before_the_section() {
+    let theme_manager = ThemeManager::default();
+    let mut app = App::new(parse(&doc(2)), false, None, &theme_manager);
+    app.viewport_height = 10;
+    app.ensure_layout(80);
+
+    // Usage の 2 段落目まで読み進め、License を折りたたむ
+    app.jump_to_heading_by_name("License");
+    app.ensure_layout(80);
+    keys(&mut app, "zc");
+    app.jump_to_heading_by_name("Usage");
+    app.ensure_layout(80);
+    keys(&mut app, "jjjjjj");
+    let reading = top_text(&app);
+    assert_eq!(reading, "Usage 1.");
+
+    let position = app.reading_position();
+    assert_eq!(position.heading.as_deref(), Some("usage"));
+    assert_eq!(position.folds.len(), 1);
+
+    // 前に段落が増えても同じ段落から表示する
+    let mut app = App::new(parse(&doc(5)), false, None, &theme_manager);
+    app.viewport_height = 10;
+    app.restore_reading_position(&position);
+    app.ensure_layout(80);
+    assert_eq!(top_text(&app), reading);
+    assert_eq!(app.reading_position().folds, position.folds);
+
+    // 見出しがなくなっていたらソースの行番号で探す
+    let renamed = doc(2).replace("## Usage", "## How to use");
+    let mut app = App::new(parse(&renamed), false, None, &theme_manager);
+    app.restore_reading_position(&position);
+    app.ensure_layout(80);
+    assert_eq!(top_text(&app), reading);
+}
+
+#[test]
+fn test_state_file_round_trip_and_eviction() {
+    let dir = tempfile::tempdir().unwrap();
+    let state_path = dir.path().join("state").join("state.toml");
+    let file = dir.path().join("guide.md");
+    std::fs::write(&file, "# Guide\n").unwrap();
+
+    // 壊れたファイルは空として読む
+    std::fs::create_dir_all(state_path.parent().unwrap()).unwrap();
+    std::fs::write(&state_path, "not = [valid").unwrap();
+    let mut store = StateStore::load(&state_path);
+    assert!(store.is_empty());
+
+    let position = ReadingPosition {
+        heading: Some("install".to_string()),
+        blocks: 3,
+        row: 1,
+        line: 20,
+        show_toc: true,
+        folds: vec![mdv::markdown::FoldKey::Block {
+            section: None,
+            nth: 0,
+        }],
+    };
+    store.set(&file, position.clone());
+    store.save(&state_path).unwrap();
+
+    let mut store = StateStore::load(&state_path);
+    // 相対パスやシンボリックリンクの違いは同じファイルとして扱う
+    let same_file = dir.path().join(".").join("guide.md");
+    assert_eq!(store.get(&same_file), Some(&position));
+
+    // 上限を超えたら最も長く開いていないファイルから忘れる
+    for i in 0..MAX_FILES {
+        store.set(
+            Path::new(&format!("/nonexistent/{i}.md")),
+            ReadingPosition::default(),
+        );
+    }
+    assert_eq!(store.len(), MAX_FILES);
+    assert_eq!(store.get(&file), None);
+    assert!(store.get(Path::new("/nonexistent/0.md")).is_some());
+
+    store.set(Path::new("/nonexistent/0.md"), ReadingPosition::default());
+    store.set(&file, position);
+    assert!(store.get(Path::new("/nonexistent/0.md")).is_some());
+    assert_eq!(store.get(Path::new("/nonexistent/1.md")), None);
+}
+
+#[test]
+fn test_following_a_link_keeps_the_position_of_the_file_left() {
+    let theme_manager = ThemeManager::default();
+    let mut app = App::new(parse(&doc(2)), false, None, &theme_manager);
+    app.viewport_height = 10;
+    app.ensure_layout(80);
+    app.jump_to_heading_by_name("Usage");
+    app.ensure_layout(80);
+    keys(&mut app, "jjt");
+    let position = app.reading_position();
+
+    let highlighter = CodeHighlighter::new("base16-ocean.dark".to_string());
+    let other = MarkdownDocument::parse(
+        PathBuf::from("other.md"),
+        "# Other\n".to_string(),
+        &highlighter,
+    )
+    .unwrap();
+    app.open_document(other, None);
+
+    let closed = app.take_closed_positions();
+    assert_eq!(closed, vec![(PathBuf::from("guide.md"), position.clone())]);
+    assert_eq!(position.heading.as_deref(), Some("usage"));
+    assert!(position.show_toc);
+    assert!(app.take_closed_positions().is_empty());
+}
@@ -0,0 +1,100 @@
+use crossterm::event::{KeyCode, KeyModifiers};
+use mdv::markdown::{CodeHighlighter, MarkdownDocument};
+use mdv::tui::{App, ThemeManager};
+use std::path::PathBuf;
+
+fn parse(md: &str) -> MarkdownDocument {
+    let highlighter = CodeHighlighter::new("base16-ocean.dark".to_string());
+    MarkdownDocument::parse(PathBuf::from("test.md"), md.to_string(), &highlighter).unwrap()
+}
+
+/// 見出し、80行のコードブロック、見出しと20行のリストの順の文書
+fn long_code_doc() -> String {
+    let code: Vec<String> = (1..=80).map(|i| format!("line {i}")).collect();
+    let items: Vec<String> = (1..=20).map(|i| format!("- item {i}")).collect();
+    format!(
+        "# Top\n\n```\n{}\n```\n\n## End\n\n{}\n",
+        code.join("\n"),
+        items.join("\n")
+    )
+}
+
+#[test]
+fn test_scroll_moves_one_row_inside_a_block() {
+    let theme_manager = ThemeManager::new();
+    let mut app = App::new(parse(&long_code_doc()), false, None, &theme_manager);
+    app.viewport_height = 10;
+    app.ensure_layout(80);
+
+    let code_rows = app.layout.as_ref().unwrap().block_rows(1);
+    assert!(code_rows.len() > 80);
+
+    app.scroll_to_block(1);
+    for _ in 0..5 {
+        app.handle_key(KeyCode::Char('j'), KeyModifiers::NONE);
+    }
+    // コードブロックの途中から表示している
+    assert_eq!(app.scroll_offset, code_rows.start + 5);
+    assert_eq!(app.top_block(), 1);
+
+    app.handle_key(KeyCode::PageDown, KeyModifiers::NONE);
+    assert_eq!(app.scroll_offset, code_rows.start + 15);
+    app.handle_key(KeyCode::Char('k'), KeyModifiers::NONE);
+    assert_eq!(app.scroll_offset, code_rows.start + 14);
+}
+
+#[test]
+fn test_scroll_to_bottom_shows_a_full_last_page() {
+    let theme_manager = ThemeManager::new();
+    let mut app = App::new(parse(&long_code_doc()), false, None, &theme_manager);
+    app.viewport_height = 10;
+    app.ensure_layout(80);
+    let row_count = app.layout.as_ref().unwrap().row_count();
+
+    app.handle_key(KeyCode::Char('G'), KeyModifiers::SHIFT);
+    assert_eq!(app.scroll_offset, row_count - 10);
+
+    // 最後のページより先には進まない
+    app.handle_key(KeyCode::Char('j'), KeyModifiers::NONE);
+    app.handle_key(KeyCode::PageDown, KeyModifiers::NONE);
+    assert_eq!(app.scroll_offset, row_count - 10);
+}
+
+#[test]
+fn test_resize_keeps_position_within_block() {
+    let theme_manager = ThemeManager::new();
+    let mut app = App::new(parse(&long_code_doc()), false, None, &theme_manager);
+    app.viewport_height = 10;
+    app.ensure_layout(80);
+    app.scroll_to_block(1);
+    app.handle_key(KeyCode::Char('j'), KeyModifiers::NONE);
+    app.handle_key(KeyCode::Char('j'), KeyModifiers::NONE);
+    let offset_in_block = app.scroll_offset - app.layout.as_ref().unwrap().block_rows(1).start;
+
+    app.ensure_layout(60);
+    let layout = app.layout.as_ref().unwrap();
+    assert_eq!(layout.area_width, 60);
+    assert_eq!(
+        app.scroll_offset,
+        layout.block_rows(1).start + offset_in_block
+    );
+}
+
+#[test]
+fn test_reload_and_heading_jump_use_rows() {
+    let theme_manager = ThemeManager::new();
+    let mut app = App::new(parse(&long_code_doc()), false, None, &theme_manager);
+    app.viewport_height = 10;
+    app.ensure_layout(80);
+
+    app.jump_to_heading_by_name("End");
+    assert_eq!(app.top_block(), 2);
+    let heading_row = app.layout.as_ref().unwrap().block_rows(2).start;
+    assert_eq!(app.scroll_offset, heading_row);
+
+    // 再読み込みするとレイアウトを作り直し、同じ見出しを表示する
+    app.update_document(parse(&long_code_doc()));
+    assert!(app.layout.is_none());
+    app.ensure_layout(80);
+    assert_eq!(app.scroll_offset, heading_row);
+}
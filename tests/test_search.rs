@@ -0,0 +1,133 @@
+use crossterm::event::{KeyCode, KeyModifiers};
+use mdv::markdown::{CodeHighlighter, MarkdownDocument};
+use mdv::tui::{App, ThemeManager};
+use std::path::PathBuf;
+
+const DOC: &str = r#"# Search
+
+A **bold** statement about Rust.
+
+```rust
+fn rust() {}
+```
+
+<details>
+<summary>Hidden</summary>
+
+The rust inside details.
+
+</details>
+
+Last rust mention.
+"#;
+
+fn parse(md: &str) -> MarkdownDocument {
+    let highlighter = CodeHighlighter::new("base16-ocean.dark".to_string());
+    MarkdownDocument::parse(PathBuf::from("search.md"), md.to_string(), &highlighter).unwrap()
+}
+
+fn type_text(app: &mut App, text: &str) {
+    for c in text.chars() {
+        app.handle_key(KeyCode::Char(c), KeyModifiers::NONE);
+    }
+}
+
+fn new_app(theme_manager: &ThemeManager) -> App<'_> {
+    let mut app = App::new(parse(DOC), false, None, theme_manager);
+    app.viewport_height = 40;
+    app.ensure_layout(80);
+    app
+}
+
+fn counter(app: &App) -> Option<String> {
+    app.search.as_ref().and_then(|search| search.counter())
+}
+
+#[test]
+fn test_search_rendered_text_with_smart_case() {
+    let theme_manager = ThemeManager::new();
+    let mut app = new_app(&theme_manager);
+
+    app.handle_key(KeyCode::Char('/'), KeyModifiers::NONE);
+    type_text(&mut app, "**bold");
+    // マークダウンの記号は検索対象にならない
+    assert_eq!(app.search.as_ref().unwrap().matches.len(), 0);
+
+    for _ in 0..6 {
+        app.handle_key(KeyCode::Backspace, KeyModifiers::NONE);
+    }
+    type_text(&mut app, "rust");
+    assert_eq!(app.search.as_ref().unwrap().matches.len(), 5);
+    assert_eq!(counter(&app).as_deref(), Some("1/5"));
+
+    // 大文字を含むと大文字・小文字を区別する
+    app.handle_key(KeyCode::Backspace, KeyModifiers::NONE);
+    app.handle_key(KeyCode::Backspace, KeyModifiers::NONE);
+    app.handle_key(KeyCode::Backspace, KeyModifiers::NONE);
+    app.handle_key(KeyCode::Backspace, KeyModifiers::NONE);
+    type_text(&mut app, "Rust");
+    assert_eq!(app.search.as_ref().unwrap().matches.len(), 1);
+}
+
+#[test]
+fn test_next_and_previous_match() {
+    let theme_manager = ThemeManager::new();
+    let mut app = new_app(&theme_manager);
+
+    app.handle_key(KeyCode::Char('/'), KeyModifiers::NONE);
+    type_text(&mut app, "rust");
+    app.handle_key(KeyCode::Enter, KeyModifiers::NONE);
+    assert!(app.search_prompt.is_none());
+
+    app.handle_key(KeyCode::Char('n'), KeyModifiers::NONE);
+    assert_eq!(counter(&app).as_deref(), Some("2/5"));
+    app.handle_key(KeyCode::Char('N'), KeyModifiers::SHIFT);
+    app.handle_key(KeyCode::Char('N'), KeyModifiers::SHIFT);
+    assert_eq!(counter(&app).as_deref(), Some("5/5"));
+    assert_eq!(
+        app.status_message.as_deref(),
+        Some("Search wrapped to bottom")
+    );
+
+    app.handle_key(KeyCode::Esc, KeyModifiers::NONE);
+    assert!(app.search.is_none());
+}
+
+#[test]
+fn test_match_in_closed_details_is_reachable() {
+    let theme_manager = ThemeManager::new();
+    let mut app = new_app(&theme_manager);
+
+    app.handle_key(KeyCode::Char('/'), KeyModifiers::NONE);
+    type_text(&mut app, "inside");
+    app.handle_key(KeyCode::Enter, KeyModifiers::NONE);
+
+    // 閉じていた `<details>` が開き、マッチが表示される
+    let search = app.search.as_ref().unwrap();
+    assert_eq!(search.matches.len(), 1);
+    assert!(search.matches[0].row.is_some());
+    assert_eq!(search.current, Some(0));
+    assert_eq!(app.toggled_details.len(), 1);
+}
+
+#[test]
+fn test_regex_mode_and_cancel() {
+    let theme_manager = ThemeManager::new();
+    let mut app = new_app(&theme_manager);
+
+    app.handle_key(KeyCode::Char('/'), KeyModifiers::NONE);
+    type_text(&mut app, "r.st");
+    assert_eq!(app.search.as_ref().unwrap().matches.len(), 0);
+
+    app.handle_key(KeyCode::Char('r'), KeyModifiers::CONTROL);
+    assert_eq!(app.search.as_ref().unwrap().matches.len(), 5);
+
+    type_text(&mut app, "(");
+    assert!(app.search.as_ref().unwrap().error.is_some());
+
+    // Esc で入力前の状態に戻る
+    app.handle_key(KeyCode::Esc, KeyModifiers::NONE);
+    assert!(app.search_prompt.is_none());
+    assert!(app.search.is_none());
+    assert_eq!(app.scroll_offset, 0);
+}
@@ -0,0 +1,74 @@
+use mdv::markdown::{CodeHighlighter, MarkdownDocument, ParsedLine};
+use mdv::tui::{App, ThemeManager};
+use std::path::PathBuf;
+
+const DOC: &str = r#"# Title
+
+Intro paragraph
+spanning two lines.
+
+```rust
+fn main() {}
+```
+
+- item one
+- item two
+
+## Second
+"#;
+
+fn parse(md: &str) -> MarkdownDocument {
+    let highlighter = CodeHighlighter::new("base16-ocean.dark".to_string());
+    MarkdownDocument::parse(PathBuf::from("test.md"), md.to_string(), &highlighter).unwrap()
+}
+
+#[test]
+fn test_blocks_carry_source_lines() {
+    let doc = parse(DOC);
+    let lines: Vec<_> = doc
+        .sources
+        .iter()
+        .map(|source| (source.start_line, source.end_line))
+        .collect();
+
+    assert_eq!(
+        lines,
+        [(1, 1), (3, 4), (6, 8), (10, 10), (11, 11), (13, 13)]
+    );
+    assert_eq!(
+        &DOC[doc.sources[2].bytes.clone()],
+        "```rust\nfn main() {}\n```"
+    );
+
+    assert!(matches!(
+        doc.parsed_lines[5],
+        ParsedLine::Heading { line_num: 13, .. }
+    ));
+    let toc_lines: Vec<_> = doc.toc.iter().map(|entry| entry.line_number).collect();
+    assert_eq!(toc_lines, [1, 13]);
+}
+
+#[test]
+fn test_block_at_line() {
+    let doc = parse(DOC);
+
+    assert_eq!(doc.block_at_line(1), 0);
+    assert_eq!(doc.block_at_line(4), 1);
+    assert_eq!(doc.block_at_line(7), 2);
+    // 空行は次のブロックに寄せる
+    assert_eq!(doc.block_at_line(9), 3);
+    assert_eq!(doc.block_at_line(100), doc.parsed_lines.len() - 1);
+}
+
+#[test]
+fn test_initial_line_is_source_line() {
+    let theme_manager = ThemeManager::new();
+    let mut app = App::new(parse(DOC), false, Some(11), &theme_manager);
+    assert_eq!(app.top_block(), 4);
+
+    // レイアウトができたら行単位の位置に置き換わる
+    app.viewport_height = 3;
+    app.ensure_layout(80);
+    assert_eq!(app.top_block(), 4);
+    assert_eq!(app.document.source_line(app.top_block()), 11);
+}
@@ -1,4 +1,4 @@
-use mdv::markdown::{CodeHighlighter, MarkdownDocument, ParsedLine};
+use mdv::markdown::{plain_text, CodeHighlighter, MarkdownDocument, ParsedLine};
 use std::path::PathBuf;
 
 #[test]
@@ -33,9 +33,9 @@ fn test_table_parsing() {
     println!("Rows: {rows:?}");
 
     assert_eq!(headers.len(), 3, "Should have 3 headers");
-    assert_eq!(headers[0].trim(), "Header 1");
-    assert_eq!(headers[1].trim(), "Header 2");
-    assert_eq!(headers[2].trim(), "Header 3");
+    assert_eq!(plain_text(&headers[0]).trim(), "Header 1");
+    assert_eq!(plain_text(&headers[1]).trim(), "Header 2");
+    assert_eq!(plain_text(&headers[2]).trim(), "Header 3");
 
     assert_eq!(rows.len(), 2, "Should have 2 rows");
     assert_eq!(rows[0].len(), 3, "First row should have 3 cells");
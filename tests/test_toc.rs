@@ -0,0 +1,122 @@
+use crossterm::event::{KeyCode, KeyModifiers};
+use mdv::markdown::{CodeHighlighter, MarkdownDocument};
+use mdv::tui::{App, ThemeManager};
+use std::path::PathBuf;
+
+const DOC: &str = r#"# Guide
+
+intro
+
+## Install
+
+text
+
+### Linux
+
+linux text
+
+### macOS
+
+mac text
+
+## Usage
+
+usage text
+
+# License
+
+MIT
+"#;
+
+fn parse(md: &str) -> MarkdownDocument {
+    let highlighter = CodeHighlighter::new("base16-ocean.dark".to_string());
+    MarkdownDocument::parse(PathBuf::from("toc.md"), md.to_string(), &highlighter).unwrap()
+}
+
+fn key(app: &mut App, code: KeyCode) {
+    app.handle_key(code, KeyModifiers::NONE);
+}
+
+fn row_titles(app: &App) -> Vec<String> {
+    app.toc_rows()
+        .iter()
+        .map(|row| app.document.toc[row.index].title.clone())
+        .collect()
+}
+
+#[test]
+fn test_fold_and_unfold_sections() {
+    let theme_manager = ThemeManager::new();
+    let mut app = App::new(parse(DOC), true, None, &theme_manager);
+
+    key(&mut app, KeyCode::Char('j'));
+    key(&mut app, KeyCode::Char('h'));
+    assert_eq!(row_titles(&app), ["Guide", "Install", "Usage", "License"]);
+
+    // 折りたたまれた見出しは飛ばして移動する
+    key(&mut app, KeyCode::Char('j'));
+    assert_eq!(app.document.toc[app.toc_selected].title, "Usage");
+
+    // 子のない見出しでは親に移る
+    key(&mut app, KeyCode::Left);
+    assert_eq!(app.toc_selected, 0);
+    key(&mut app, KeyCode::Left);
+    assert_eq!(row_titles(&app), ["Guide", "License"]);
+
+    key(&mut app, KeyCode::Right);
+    key(&mut app, KeyCode::Right);
+    assert_eq!(app.document.toc[app.toc_selected].title, "Install");
+    key(&mut app, KeyCode::Char('l'));
+    assert_eq!(row_titles(&app).len(), 6);
+
+    // 折りたたみは再読み込み後も残る
+    key(&mut app, KeyCode::Char('h'));
+    app.update_document(parse(DOC));
+    assert_eq!(row_titles(&app).len(), 4);
+}
+
+#[test]
+fn test_filter_headings() {
+    let theme_manager = ThemeManager::new();
+    let mut app = App::new(parse(DOC), true, None, &theme_manager);
+
+    key(&mut app, KeyCode::Char('/'));
+    assert!(app.search_prompt.is_none());
+    for c in "mos".chars() {
+        key(&mut app, KeyCode::Char(c));
+    }
+    assert_eq!(row_titles(&app), ["macOS"]);
+    assert_eq!(app.document.toc[app.toc_selected].title, "macOS");
+
+    key(&mut app, KeyCode::Enter);
+    assert!(!app.show_toc);
+    assert!(app.toc_view.filter.is_none());
+    assert_eq!(
+        app.document.source_line(app.top_block()),
+        app.document.toc[3].line_number
+    );
+}
+
+#[test]
+fn test_selection_follows_scroll() {
+    let theme_manager = ThemeManager::new();
+    let mut app = App::new(parse(DOC), false, None, &theme_manager);
+    app.viewport_height = 5;
+    app.ensure_layout(80);
+
+    app.scroll_to_block(app.document.block_at_line(app.document.toc[2].line_number) + 1);
+    app.sync_toc_selection();
+    assert_eq!(app.toc_selected, 2);
+
+    // 折りたたまれた見出しの中なら、表示されている祖先を選ぶ
+    app.toc_view
+        .set_collapsed(&app.document.toc[1].clone(), true);
+    let rows = app.toc_rows();
+    assert_eq!(app.toc_selected_row(&rows), Some(1));
+
+    // 目次で選択を動かしただけでは上書きしない
+    app.show_toc = true;
+    key(&mut app, KeyCode::Char('k'));
+    app.sync_toc_selection();
+    assert_eq!(app.toc_selected, 0);
+}